@@ -0,0 +1,216 @@
+use std::fmt::{Debug, Display, Formatter};
+use crate::instruction::{Condition, RealInstruction};
+use crate::value::function::Function;
+
+/// How control gets from one basic block to another.
+#[derive(Clone, Copy)]
+pub enum EdgeKind {
+    /// The next instruction in the function runs after the block.
+    FallThrough,
+    /// A `Goto` is taken when the condition holds.
+    Jump(Condition),
+}
+
+impl Display for EdgeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdgeKind::FallThrough => write!(f, "fallthrough"),
+            EdgeKind::Jump(condition) => write!(f, "{}", condition),
+        }
+    }
+}
+
+impl Debug for EdgeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Edge {
+    pub target: usize,
+    pub kind: EdgeKind,
+}
+
+/// A straight line run of instructions.
+/// Control only enters at `start` and only leaves after the last instruction.
+#[derive(Clone, Debug)]
+pub struct BasicBlock {
+    /// Address of the first instruction in the block.
+    pub start: usize,
+    /// Address one past the last instruction in the block.
+    pub end: usize,
+    pub successors: Vec<Edge>,
+    pub predecessors: Vec<usize>,
+    /// If the block can leave the function through a `Return` or `Halt`.
+    pub exits: bool,
+}
+
+impl BasicBlock {
+    pub fn addresses(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// The control flow graph of a function.
+/// Block 0 is always the entry block (unless the function is empty).
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+    block_of: Vec<usize>,
+}
+
+/// Returns true if the instruction ends a basic block.
+fn is_terminator(instruction: &RealInstruction) -> bool {
+    use RealInstruction::*;
+    matches!(instruction, Goto(_, _) | CompareGoto(_, _, _) | Return(_) | Halt | FunctionCall(_, _))
+}
+
+impl ControlFlowGraph {
+    pub fn new(function: &Function) -> ControlFlowGraph {
+        let code = &function.code;
+        let len = code.len();
+
+        let mut leaders = vec![false; len];
+        if len > 0 {
+            leaders[0] = true;
+        }
+        for (address, instruction) in code.iter().enumerate() {
+            let instruction = &instruction.instruction;
+            if let Some(target) = instruction.jump_target().and_then(|(target, _)| target.resolve(address)) {
+                if target < len {
+                    leaders[target] = true;
+                }
+            }
+            if is_terminator(instruction) && address + 1 < len {
+                leaders[address + 1] = true;
+            }
+        }
+
+        let mut blocks = Vec::new();
+        let mut block_of = vec![0; len];
+        for address in 0..len {
+            if leaders[address] {
+                blocks.push(BasicBlock {
+                    start: address,
+                    end: address,
+                    successors: Vec::new(),
+                    predecessors: Vec::new(),
+                    exits: false,
+                });
+            }
+            let block = blocks.len() - 1;
+            blocks[block].end = address + 1;
+            block_of[address] = block;
+        }
+
+        for block in 0..blocks.len() {
+            let last = blocks[block].end - 1;
+            let next = blocks[block].end;
+            let mut successors = Vec::new();
+            let mut exits = false;
+            let mut add_edge = |target: usize, kind: EdgeKind| {
+                if target < len {
+                    successors.push(Edge { target: block_of[target], kind });
+                }
+            };
+
+            match &code[last].instruction {
+                RealInstruction::Goto(target, condition) | RealInstruction::CompareGoto(_, target, condition) => {
+                    if let Some(target) = target.resolve(last) {
+                        add_edge(target, EdgeKind::Jump(*condition));
+                    }
+                    if !matches!(condition, Condition::Always) {
+                        add_edge(next, EdgeKind::FallThrough);
+                    }
+                }
+                RealInstruction::Return(condition) => {
+                    exits = true;
+                    if !matches!(condition, Condition::Always) {
+                        add_edge(next, EdgeKind::FallThrough);
+                    }
+                }
+                RealInstruction::Halt => exits = true,
+                _ => add_edge(next, EdgeKind::FallThrough),
+            }
+
+            for edge in successors.iter() {
+                if !blocks[edge.target].predecessors.contains(&block) {
+                    blocks[edge.target].predecessors.push(block);
+                }
+            }
+            blocks[block].successors = successors;
+            blocks[block].exits = exits;
+        }
+
+        ControlFlowGraph {
+            blocks,
+            block_of,
+        }
+    }
+
+    pub fn entry(&self) -> usize {
+        0
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Returns the block holding the instruction at `address`.
+    pub fn block_containing(&self, address: usize) -> Option<usize> {
+        self.block_of.get(address).copied()
+    }
+
+    /// Returns which blocks can be reached from the entry block.
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        if self.blocks.is_empty() {
+            return reachable;
+        }
+        let mut worklist = vec![self.entry()];
+        reachable[self.entry()] = true;
+        while let Some(block) = worklist.pop() {
+            for edge in self.blocks[block].successors.iter() {
+                if !reachable[edge.target] {
+                    reachable[edge.target] = true;
+                    worklist.push(edge.target);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Returns the reachable blocks in reverse postorder starting at the entry block.
+    pub fn reverse_postorder(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.blocks.len());
+        if self.blocks.is_empty() {
+            return order;
+        }
+        let mut visited = vec![false; self.blocks.len()];
+        // Each stack entry is a block and the index of the next successor to visit.
+        let mut stack = vec![(self.entry(), 0)];
+        visited[self.entry()] = true;
+        while let Some((block, next)) = stack.last_mut() {
+            let block = *block;
+            match self.blocks[block].successors.get(*next) {
+                Some(edge) => {
+                    *next += 1;
+                    if !visited[edge.target] {
+                        visited[edge.target] = true;
+                        stack.push((edge.target, 0));
+                    }
+                }
+                None => {
+                    order.push(block);
+                    stack.pop();
+                }
+            }
+        }
+        order.reverse();
+        order
+    }
+}
@@ -0,0 +1,87 @@
+use crate::analysis::cfg::ControlFlowGraph;
+
+/// The dominator tree of a control flow graph.
+/// Computed with the iterative algorithm from Cooper, Harvey and Kennedy's
+/// "A Simple, Fast Dominance Algorithm".
+pub struct Dominators {
+    immediate_dominators: Vec<Option<usize>>,
+}
+
+impl Dominators {
+    pub fn new(cfg: &ControlFlowGraph) -> Dominators {
+        let mut immediate_dominators = vec![None; cfg.len()];
+        if cfg.is_empty() {
+            return Dominators { immediate_dominators };
+        }
+
+        let order = cfg.reverse_postorder();
+        let mut position = vec![usize::MAX; cfg.len()];
+        for (index, block) in order.iter().enumerate() {
+            position[*block] = index;
+        }
+
+        let entry = cfg.entry();
+        immediate_dominators[entry] = Some(entry);
+
+        let intersect = |immediate_dominators: &[Option<usize>], mut left: usize, mut right: usize| {
+            while left != right {
+                while position[left] > position[right] {
+                    left = immediate_dominators[left].unwrap();
+                }
+                while position[right] > position[left] {
+                    right = immediate_dominators[right].unwrap();
+                }
+            }
+            left
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in order.iter().skip(1) {
+                let mut new_dominator = None;
+                for predecessor in cfg.blocks[*block].predecessors.iter() {
+                    if immediate_dominators[*predecessor].is_none() {
+                        continue;
+                    }
+                    new_dominator = match new_dominator {
+                        None => Some(*predecessor),
+                        Some(dominator) => Some(intersect(&immediate_dominators, *predecessor, dominator)),
+                    };
+                }
+                if new_dominator.is_some() && immediate_dominators[*block] != new_dominator {
+                    immediate_dominators[*block] = new_dominator;
+                    changed = true;
+                }
+            }
+        }
+
+        Dominators { immediate_dominators }
+    }
+
+    /// Returns the immediate dominator of a block.
+    /// The entry block and unreachable blocks have none.
+    pub fn immediate_dominator(&self, block: usize) -> Option<usize> {
+        match self.immediate_dominators.get(block).copied().flatten() {
+            Some(dominator) if dominator != block => Some(dominator),
+            _ => None,
+        }
+    }
+
+    /// Returns true if every path from the entry block to `block` goes through `dominator`.
+    pub fn dominates(&self, dominator: usize, block: usize) -> bool {
+        if self.immediate_dominators.get(block).copied().flatten().is_none() {
+            return false;
+        }
+        let mut current = block;
+        loop {
+            if current == dominator {
+                return true;
+            }
+            match self.immediate_dominator(current) {
+                Some(next) => current = next,
+                None => return false,
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::analysis::cfg::{ControlFlowGraph, EdgeKind};
+use crate::value::function::Function;
+
+/// Escapes text so it can be placed inside a Graphviz record label.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '"' | '{' | '}' | '<' | '>' | '|' | '\\' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Renders the control flow graph of a function in the Graphviz DOT format.
+/// Each node lists the instructions of a basic block with their addresses.
+/// Unreachable blocks are drawn dashed.
+pub fn to_dot(name: &str, function: &Function, cfg: &ControlFlowGraph) -> String {
+    let reachable = cfg.reachable();
+    let mut dot = String::new();
+    dot.push_str(&format!("digraph \"{}\" {{\n", escape(name)));
+    dot.push_str("    node [shape=record, fontname=\"monospace\"];\n");
+
+    for (block, basic_block) in cfg.blocks.iter().enumerate() {
+        let mut label = format!("block {}", block);
+        label.push('|');
+        for address in basic_block.addresses() {
+            label.push_str(&escape(&format!("{:#06x}: {}", address, function.code[address].instruction)));
+            label.push_str("\\l");
+        }
+        let style = if reachable[block] { "solid" } else { "dashed" };
+        let peripheries = if basic_block.exits { 2 } else { 1 };
+        dot.push_str(&format!("    block{} [label=\"{{{}}}\", style={}, peripheries={}];\n", block, label, style, peripheries));
+    }
+
+    for (block, basic_block) in cfg.blocks.iter().enumerate() {
+        for edge in basic_block.successors.iter() {
+            match edge.kind {
+                EdgeKind::FallThrough => dot.push_str(&format!("    block{} -> block{} [style=dotted];\n", block, edge.target)),
+                EdgeKind::Jump(condition) => dot.push_str(&format!("    block{} -> block{} [label=\"{}\"];\n", block, edge.target, condition)),
+            }
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeSet;
+use crate::analysis::cfg::ControlFlowGraph;
+use crate::instruction::RealInstruction;
+use crate::value::function::Function;
+
+/// Live variable analysis over the names a function keeps in its `Environment`.
+/// A name is live at a point if some path from that point reads it before writing it.
+pub struct Liveness {
+    live_in: Vec<BTreeSet<Box<str>>>,
+    live_out: Vec<BTreeSet<Box<str>>>,
+    names: BTreeSet<Box<str>>,
+}
+
+/// Returns the names read and written by the instruction.
+/// `all_names` is used for `ClosureNew`, which captures the whole environment, and `argument_names`
+/// for `HostCall`, which reads the arguments of the function it sits in.
+fn uses_and_definitions<'a>(
+    instruction: &'a RealInstruction,
+    all_names: &'a BTreeSet<Box<str>>,
+    argument_names: &'a [Box<str>],
+) -> (Vec<&'a str>, Vec<&'a str>) {
+    use RealInstruction::*;
+    match instruction {
+        Lookup(name) => (vec![name], vec![]),
+        Store(name) => (vec![], vec![name]),
+        IncrementLocal(name, _) | DecrementLocal(name, _) => (vec![name], vec![name]),
+        LookupPairOperation(left, right, _) => (vec![left, right], vec![]),
+        ClosureNew(_) => (all_names.iter().map(|name| name.as_ref()).collect(), vec![]),
+        HostCall(_) => (argument_names.iter().map(|name| name.as_ref()).collect(), vec![]),
+        _ => (vec![], vec![]),
+    }
+}
+
+/// Returns every name the function reads or writes, including its arguments.
+fn collect_names(function: &Function) -> BTreeSet<Box<str>> {
+    let empty = BTreeSet::new();
+    let mut names = function.argument_names.iter().cloned().collect::<BTreeSet<_>>();
+    for instruction in function.code.iter() {
+        let (uses, definitions) = uses_and_definitions(&instruction.instruction, &empty, &function.argument_names);
+        names.extend(uses.into_iter().map(Box::from));
+        names.extend(definitions.into_iter().map(Box::from));
+    }
+    names
+}
+
+impl Liveness {
+    pub fn new(function: &Function, cfg: &ControlFlowGraph) -> Liveness {
+        let names = collect_names(function);
+
+        let mut uses = vec![BTreeSet::new(); cfg.len()];
+        let mut definitions = vec![BTreeSet::new(); cfg.len()];
+        for (block, basic_block) in cfg.blocks.iter().enumerate() {
+            for address in basic_block.addresses() {
+                let (read, written) = uses_and_definitions(&function.code[address].instruction, &names, &function.argument_names);
+                for name in read {
+                    if !definitions[block].contains(name) {
+                        uses[block].insert(Box::from(name));
+                    }
+                }
+                for name in written {
+                    definitions[block].insert(Box::from(name));
+                }
+            }
+        }
+
+        let mut live_in: Vec<BTreeSet<Box<str>>> = vec![BTreeSet::new(); cfg.len()];
+        let mut live_out: Vec<BTreeSet<Box<str>>> = vec![BTreeSet::new(); cfg.len()];
+        let order = cfg.reverse_postorder();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            // Liveness flows backwards so visiting in postorder converges faster.
+            for block in order.iter().rev() {
+                let mut out = BTreeSet::new();
+                for edge in cfg.blocks[*block].successors.iter() {
+                    out.extend(live_in[edge.target].iter().cloned());
+                }
+                let mut input = uses[*block].clone();
+                input.extend(out.difference(&definitions[*block]).cloned());
+
+                if input != live_in[*block] || out != live_out[*block] {
+                    live_in[*block] = input;
+                    live_out[*block] = out;
+                    changed = true;
+                }
+            }
+        }
+
+        Liveness {
+            live_in,
+            live_out,
+            names,
+        }
+    }
+
+    /// Names that are live on entry to the block.
+    pub fn live_in(&self, block: usize) -> &BTreeSet<Box<str>> {
+        &self.live_in[block]
+    }
+
+    /// Names that are live when the block finishes.
+    pub fn live_out(&self, block: usize) -> &BTreeSet<Box<str>> {
+        &self.live_out[block]
+    }
+
+    /// Names that are live just before the instruction at `address` runs.
+    pub fn live_before(&self, function: &Function, cfg: &ControlFlowGraph, address: usize) -> BTreeSet<Box<str>> {
+        let block = match cfg.block_containing(address) {
+            Some(block) => block,
+            None => return BTreeSet::new(),
+        };
+        let mut live = self.live_out[block].clone();
+        for current in cfg.blocks[block].addresses().rev() {
+            let (read, written) = uses_and_definitions(&function.code[current].instruction, &self.names, &function.argument_names);
+            for name in written {
+                live.remove(name);
+            }
+            for name in read {
+                live.insert(Box::from(name));
+            }
+            if current == address {
+                break;
+            }
+        }
+        live
+    }
+
+    /// Names that are live just after the instruction at `address` runs.
+    pub fn live_after(&self, function: &Function, cfg: &ControlFlowGraph, address: usize) -> BTreeSet<Box<str>> {
+        match cfg.block_containing(address) {
+            Some(block) if cfg.blocks[block].end == address + 1 => self.live_out[block].clone(),
+            Some(_) => self.live_before(function, cfg, address + 1),
+            None => BTreeSet::new(),
+        }
+    }
+
+    /// Returns true if the value written by a `Store` at `address` can never be read.
+    pub fn is_dead_store(&self, function: &Function, cfg: &ControlFlowGraph, address: usize) -> bool {
+        match &function.code[address].instruction {
+            RealInstruction::Store(name) => !self.live_after(function, cfg, address).contains(name),
+            _ => false,
+        }
+    }
+}
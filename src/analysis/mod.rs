@@ -0,0 +1,4 @@
+pub mod cfg;
+pub mod dominators;
+pub mod liveness;
+pub mod dot;
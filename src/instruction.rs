@@ -1,9 +1,10 @@
 use std::fmt::{Debug, Display, Formatter};
 use crate::program::{FunctionPath, StringTablePath};
-use crate::value::decimal::Decimal;
-use crate::value::integer::Integer;
+use crate::value::decimal::{Decimal, DecimalType, Rounding};
+use crate::value::integer::{DivisionMode, Integer};
 use crate::value::{Value, ValueType};
 use crate::value::vector::VectorType;
+use crate::value::vector_math::{VectorOperation, VectorReduction};
 
 #[derive(Clone)]
 pub struct Instruction {
@@ -113,11 +114,18 @@ pub enum Condition {
     Zero,
     /// When the last instruction resulted in a non-zero result.
     NotZero,
-    /// When the last instruction resulted in a carry result.
     /// When the last instruction resulted in a negative result.
     Negative,
     /// When the last instruction resulted in a non-negative result.
     NotNegative,
+    /// When the last integer instruction carried out of the top bit, borrowed or shifted out a one bit.
+    Carry,
+    /// When the last integer instruction didn't carry.
+    NoCarry,
+    /// When the last integer instruction had to wrap or saturate its result.
+    Overflow,
+    /// When the last integer instruction produced its exact result.
+    NoOverflow,
 }
 
 impl Display for Condition {
@@ -135,6 +143,10 @@ impl Display for Condition {
             NotZero => write!(f, "not_zero"),
             Negative => write!(f, "negative"),
             NotNegative => write!(f, "not_negative"),
+            Carry => write!(f, "carry"),
+            NoCarry => write!(f, "no_carry"),
+            Overflow => write!(f, "overflow"),
+            NoOverflow => write!(f, "no_overflow"),
         }
     }
 }
@@ -145,7 +157,7 @@ impl Debug for Condition {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 pub enum ComparisonType {
     Equal,
     NotEqual,
@@ -175,6 +187,71 @@ impl Debug for ComparisonType {
     }
 }
 
+/// A binary operation that can be fused into a superinstruction.
+#[derive(Clone, Copy)]
+pub enum BinaryOperation {
+    IntegerAdd,
+    IntegerSubtract,
+    IntegerMultiply,
+    IntegerDivide,
+    IntegerModulo,
+    IntegerBitwiseAnd,
+    IntegerBitwiseOr,
+    IntegerBitwiseXor,
+    DecimalAdd,
+    DecimalSubtract,
+    DecimalMultiply,
+    DecimalDivide,
+}
+
+impl BinaryOperation {
+    /// Returns the operation performed by the instruction, if it is a fusable binary operation.
+    pub fn from_instruction(instruction: &RealInstruction) -> Option<BinaryOperation> {
+        use RealInstruction::*;
+        match instruction {
+            IntegerAdd => Some(BinaryOperation::IntegerAdd),
+            IntegerSubtract => Some(BinaryOperation::IntegerSubtract),
+            IntegerMultiply => Some(BinaryOperation::IntegerMultiply),
+            IntegerDivide => Some(BinaryOperation::IntegerDivide),
+            IntegerModulo => Some(BinaryOperation::IntegerModulo),
+            IntegerBitwiseAnd => Some(BinaryOperation::IntegerBitwiseAnd),
+            IntegerBitwiseOr => Some(BinaryOperation::IntegerBitwiseOr),
+            IntegerBitwiseXor => Some(BinaryOperation::IntegerBitwiseXor),
+            DecimalAdd => Some(BinaryOperation::DecimalAdd),
+            DecimalSubtract => Some(BinaryOperation::DecimalSubtract),
+            DecimalMultiply => Some(BinaryOperation::DecimalMultiply),
+            DecimalDivide => Some(BinaryOperation::DecimalDivide),
+            _ => None,
+        }
+    }
+}
+
+impl Display for BinaryOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use BinaryOperation::*;
+        match self {
+            IntegerAdd => write!(f, "integer.add"),
+            IntegerSubtract => write!(f, "integer.subtract"),
+            IntegerMultiply => write!(f, "integer.multiply"),
+            IntegerDivide => write!(f, "integer.divide"),
+            IntegerModulo => write!(f, "integer.modulo"),
+            IntegerBitwiseAnd => write!(f, "integer.bitwise_and"),
+            IntegerBitwiseOr => write!(f, "integer.bitwise_or"),
+            IntegerBitwiseXor => write!(f, "integer.bitwise_xor"),
+            DecimalAdd => write!(f, "decimal.add"),
+            DecimalSubtract => write!(f, "decimal.subtract"),
+            DecimalMultiply => write!(f, "decimal.multiply"),
+            DecimalDivide => write!(f, "decimal.divide"),
+        }
+    }
+}
+
+impl Debug for BinaryOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
 #[derive(Clone)]
 pub enum RealInstruction {
     Halt,
@@ -185,13 +262,106 @@ pub enum RealInstruction {
     // Duplicate
     Duplicate,
     // Tuple
+    /// Pops a length and that many values, and pushes a tuple of the values in the order they were pushed.
     TupleNew,
+    /// Pops an index and a tuple, and pushes the tuple and the element. Faults with `OutOfBounds` if there is no such element.
     TupleGet,
+    /// Pops a value, an index and a tuple, and pushes a new tuple with the element replaced.
+    TupleSet,
+    /// Pops a tuple, and pushes the tuple and its length.
+    TupleLength,
+    /// Pops a tuple, and pushes its elements in order, so the last one ends up on top.
+    /// `TupleUnpack` followed by the length and `TupleNew` gives back the same tuple.
+    TupleUnpack,
+    /// Pops a vector, and pushes a tuple of its elements.
+    TupleFromVector,
+    /// Pops a tuple, and pushes an `Any` vector of its elements.
+    TupleToVector,
+    // Map
+    MapNew,
+    /// Pops a value, a key and a map, and pushes the map with the entry inserted or replaced.
+    MapInsert,
+    /// Pops a key and a map, and pushes the map and the value. Faults with `KeyNotFound` if it is missing.
+    MapGet,
+    /// Like `MapGet`, but the entry is taken out of the map.
+    MapRemove,
+    MapContains,
+    MapLength,
+    /// Pushes the map and a tuple of its keys.
+    MapKeys,
+    /// Pushes the map and a tuple of its values, in the same order as `MapKeys`.
+    MapValues,
     // Vector
     VectorNew(VectorType),
     VectorGet,
     VectorSet,
     VectorLength,
+    /// Pops a value and a vector, and pushes the vector with the value appended.
+    VectorPush,
+    /// Pops a vector, and pushes it without its last element followed by that element.
+    VectorPop,
+    /// Pops a value, an index and a vector, and pushes the vector with the value inserted before the index.
+    VectorInsert,
+    /// Pops an index and a vector, and pushes the vector without that element followed by the element.
+    VectorRemove,
+    /// Pops a length and a vector, and pushes the vector shortened to that length.
+    VectorTruncate,
+    /// Pops a fill value, a length and a vector, and pushes the vector resized to that length.
+    VectorResize,
+    /// Pops an end, a start and a vector, and pushes the vector followed by a new vector of `start..end`.
+    VectorSlice,
+    /// Pops two vectors, and pushes a new vector with the elements of the first followed by the second.
+    VectorConcat,
+    /// Pops an end, a start, a source vector, an offset and a destination vector, and pushes the
+    /// destination with `source[start..end]` copied over the elements from the offset on.
+    VectorCopy,
+    /// Pops two operands, at least one of them a vector and the other a vector of the same type and
+    /// length or a scalar of its element type, and pushes a new vector with the element-wise result.
+    VectorArithmetic(VectorOperation),
+    /// Pops two vectors of the same type and length, and pushes the sum of their products.
+    VectorDot,
+    /// Pops a vector of numbers, and pushes its sum, min, max or mean.
+    VectorReduce(VectorReduction),
+    /// Pops a vector, and pushes it sorted in ascending order.
+    VectorSort,
+    /// Pops a comparator closure and a vector, and pushes the vector sorted by the comparator.
+    /// The comparator gets two elements and returns a negative, zero or positive integer.
+    VectorSortBy,
+    /// Pops a value and a sorted vector, and pushes the vector and the value's position as an `I64`.
+    /// If the value is missing the position is `-insertion_point - 1`, which is always negative.
+    VectorBinarySearch,
+    VectorReverse,
+    /// Pops a vector, and pushes it without consecutive duplicates.
+    VectorDedup,
+    /// Pops a closure and a vector, and pushes a new vector of the closure's results for each element.
+    VectorMap,
+    /// Pops a closure and a vector, and pushes a new vector of the elements the closure returns true for.
+    VectorFilter,
+    /// Pops a closure, an initial accumulator and a vector, and pushes the accumulator after calling
+    /// the closure with it and each element in turn.
+    VectorFold,
+    // Array
+    /// Pops a shape tuple, and pushes an array of zeros of that shape.
+    ArrayNew(VectorType),
+    /// Pops a shape tuple and a vector, and pushes an array with the vector's elements in row-major order.
+    ArrayFromVector,
+    /// Pops a tuple of indices and an array, and pushes the array and the element.
+    ArrayGet,
+    /// Pops a value, a tuple of indices and an array, and pushes the array with the element set.
+    ArraySet,
+    /// Pops an array, and pushes it and its shape tuple.
+    ArrayShape,
+    /// Pops a shape tuple and an array, and pushes the array with the same elements in the new shape.
+    ArrayReshape,
+    /// Pops an array, and pushes it with its axes reversed.
+    ArrayTranspose,
+    /// Pops an end, a start, an axis and an array, and pushes the array followed by a view of
+    /// `start..end` along the axis.
+    ArraySlice,
+    /// Pops two matrices, and pushes their product.
+    ArrayMatrixMultiply,
+    /// Pops an array, and pushes its elements in row-major order as a vector.
+    ArrayToVector,
     // Product
     ProductNew(usize),
     ProductGet(Box<str>),
@@ -204,6 +374,9 @@ pub enum RealInstruction {
     FunctionCall(FunctionSource, Condition),
     Return(Condition),
     ClosureNew(FunctionSource),
+    /// Calls a host function with the arguments bound in the current environment.
+    /// Only used by the functions [`crate::program::host::HostFunction::shim`] creates.
+    HostCall(FunctionPath),
     // Reference
     ReferenceNew,
     ReferenceGet,
@@ -224,6 +397,41 @@ pub enum RealInstruction {
     IntegerBitwiseNot,
     IntegerShiftLeft,
     IntegerShiftRight,
+    IntegerWrappingAdd,
+    IntegerWrappingSubtract,
+    IntegerWrappingMultiply,
+    IntegerWrappingPower,
+    IntegerWrappingShiftLeft,
+    IntegerSaturatingAdd,
+    IntegerSaturatingSubtract,
+    IntegerSaturatingMultiply,
+    IntegerSaturatingPower,
+    IntegerSaturatingShiftLeft,
+    /// Pushes the number of one bits as a `U64`.
+    IntegerPopCount,
+    /// Pushes the number of bits in the absolute value as a `U64`.
+    IntegerBitLength,
+    /// Pops a bit index and an integer and pushes whether that bit is set.
+    IntegerTestBit,
+    /// Pops a bit index and an integer and pushes the integer with that bit set.
+    IntegerSetBit,
+    /// Pops a bit index and an integer and pushes the integer with that bit cleared.
+    IntegerClearBit,
+    IntegerFloorDivide,
+    IntegerFloorModulo,
+    IntegerEuclideanDivide,
+    IntegerEuclideanModulo,
+    /// Pushes the quotient and then the remainder.
+    IntegerDivMod(DivisionMode),
+    IntegerGcd,
+    IntegerLcm,
+    IntegerFactorial,
+    IntegerBinomial,
+    IntegerSquareRoot,
+    /// Pops the modulus, then the exponent, then the base.
+    IntegerPowMod,
+    IntegerModInverse,
+    IntegerIsPrime,
     // Decimal
     DecimalNew(Decimal),
     DecimalAdd,
@@ -233,6 +441,13 @@ pub enum RealInstruction {
     DecimalModulo,
     DecimalPower,
     DecimalNegate,
+    /// Rounds to a whole number, keeping the decimal type.
+    DecimalRound(Rounding),
+    /// Pops the number of digits after the decimal point and then the value to round.
+    DecimalRoundTo(Rounding),
+    DecimalConvert(DecimalType),
+    /// Pops the number of digits to round the quotient to, then the divisor and the dividend.
+    DecimalDivideTo(Rounding),
     // String
     StringNew(Box<str>),
     StringConcat,
@@ -262,6 +477,64 @@ pub enum RealInstruction {
     GetStringRef(StringTablePath, usize),
     // Cast
     Cast(ValueType),
+    // Superinstructions
+    /// Adds the integer to the local variable in place.
+    /// Equivalent to `Lookup x; IntegerNew k; IntegerAdd; Store x`.
+    IncrementLocal(Box<str>, Integer),
+    /// Subtracts the integer from the local variable in place.
+    /// Equivalent to `Lookup x; IntegerNew k; IntegerSubtract; Store x`.
+    DecrementLocal(Box<str>, Integer),
+    /// Equivalent to `Compare(c); Goto(target, condition)`.
+    CompareGoto(ComparisonType, JumpTarget, Condition),
+    /// Equivalent to `Lookup a; Lookup b; op`.
+    LookupPairOperation(Box<str>, Box<str>, BinaryOperation),
+}
+
+impl RealInstruction {
+    /// Returns the jump target of the instruction if it can transfer control to another instruction.
+    pub fn jump_target(&self) -> Option<(&JumpTarget, &Condition)> {
+        match self {
+            RealInstruction::Goto(target, condition) => Some((target, condition)),
+            RealInstruction::CompareGoto(_, target, condition) => Some((target, condition)),
+            _ => None,
+        }
+    }
+
+    /// Mutable version of [`RealInstruction::jump_target`].
+    pub fn jump_target_mut(&mut self) -> Option<(&mut JumpTarget, &mut Condition)> {
+        match self {
+            RealInstruction::Goto(target, condition) => Some((target, condition)),
+            RealInstruction::CompareGoto(_, target, condition) => Some((target, condition)),
+            _ => None,
+        }
+    }
+}
+
+impl JumpTarget {
+    /// Resolves the jump target into an absolute address for an instruction at `address`.
+    /// Returns `None` if a relative jump would go before the start of the function.
+    pub fn resolve(&self, address: usize) -> Option<usize> {
+        match self {
+            JumpTarget::Relative(offset) => {
+                let target = address as isize + offset;
+                if target < 0 {
+                    None
+                } else {
+                    Some(target as usize)
+                }
+            }
+            JumpTarget::Absolute(target) => Some(*target),
+        }
+    }
+
+    /// Points the jump target of the instruction at `address` to the absolute address `target`,
+    /// keeping the kind of the jump target.
+    pub fn retarget(&mut self, address: usize, target: usize) {
+        match self {
+            JumpTarget::Relative(offset) => *offset = target as isize - address as isize,
+            JumpTarget::Absolute(absolute) => *absolute = target,
+        }
+    }
 }
 
 impl Display for RealInstruction {
@@ -275,10 +548,53 @@ impl Display for RealInstruction {
             Duplicate => write!(f, "duplicate"),
             TupleNew => write!(f, "tuple.new"),
             TupleGet => write!(f, "tuple.get"),
+            TupleSet => write!(f, "tuple.set"),
+            TupleLength => write!(f, "tuple.length"),
+            TupleUnpack => write!(f, "tuple.unpack"),
+            TupleFromVector => write!(f, "tuple.from_vector"),
+            TupleToVector => write!(f, "tuple.to_vector"),
+            MapNew => write!(f, "map.new"),
+            MapInsert => write!(f, "map.insert"),
+            MapGet => write!(f, "map.get"),
+            MapRemove => write!(f, "map.remove"),
+            MapContains => write!(f, "map.contains"),
+            MapLength => write!(f, "map.length"),
+            MapKeys => write!(f, "map.keys"),
+            MapValues => write!(f, "map.values"),
             VectorNew(ty) => write!(f, "vector.new {}", ty),
             VectorGet => write!(f, "vector.get"),
             VectorSet => write!(f, "vector.set"),
             VectorLength => write!(f, "vector.length"),
+            VectorPush => write!(f, "vector.push"),
+            VectorPop => write!(f, "vector.pop"),
+            VectorInsert => write!(f, "vector.insert"),
+            VectorRemove => write!(f, "vector.remove"),
+            VectorTruncate => write!(f, "vector.truncate"),
+            VectorResize => write!(f, "vector.resize"),
+            VectorSlice => write!(f, "vector.slice"),
+            VectorConcat => write!(f, "vector.concat"),
+            VectorCopy => write!(f, "vector.copy"),
+            VectorArithmetic(operation) => write!(f, "vector.arithmetic {}", operation),
+            VectorDot => write!(f, "vector.dot"),
+            VectorReduce(reduction) => write!(f, "vector.reduce {}", reduction),
+            VectorSort => write!(f, "vector.sort"),
+            VectorSortBy => write!(f, "vector.sort_by"),
+            VectorBinarySearch => write!(f, "vector.binary_search"),
+            VectorReverse => write!(f, "vector.reverse"),
+            VectorDedup => write!(f, "vector.dedup"),
+            VectorMap => write!(f, "vector.map"),
+            VectorFilter => write!(f, "vector.filter"),
+            VectorFold => write!(f, "vector.fold"),
+            ArrayNew(ty) => write!(f, "array.new {}", ty),
+            ArrayFromVector => write!(f, "array.from_vector"),
+            ArrayGet => write!(f, "array.get"),
+            ArraySet => write!(f, "array.set"),
+            ArrayShape => write!(f, "array.shape"),
+            ArrayReshape => write!(f, "array.reshape"),
+            ArrayTranspose => write!(f, "array.transpose"),
+            ArraySlice => write!(f, "array.slice"),
+            ArrayMatrixMultiply => write!(f, "array.matrix_multiply"),
+            ArrayToVector => write!(f, "array.to_vector"),
             ProductNew(size) => write!(f, "product.new {}", size),
             ProductGet(name) => write!(f, "product.get {}", name),
             ProductSet(index) => write!(f, "product.set {}", index),
@@ -288,9 +604,11 @@ impl Display for RealInstruction {
             FunctionCall(source, condition) => write!(f, "function.call {} when {}", source, condition),
             Return(condition) => write!(f, "return when {}", condition),
             ClosureNew(source) => write!(f, "closure.new {}", source),
+            HostCall(path) => write!(f, "host.call {}", path),
             ReferenceNew => write!(f, "reference.new"),
             ReferenceGet => write!(f, "reference.get"),
             ReferenceSet => write!(f, "reference.set"),
+            ReferenceSetShared => write!(f, "reference.set_shared"),
             IntegerNew(value) => write!(f, "integer.new {}", value),
             IntegerAdd => write!(f, "integer.add"),
             IntegerSubtract => write!(f, "integer.subtract"),
@@ -305,6 +623,34 @@ impl Display for RealInstruction {
             IntegerBitwiseNot => write!(f, "integer.bitwise_not"),
             IntegerShiftLeft => write!(f, "integer.shift_left"),
             IntegerShiftRight => write!(f, "integer.shift_right"),
+            IntegerWrappingAdd => write!(f, "integer.wrapping_add"),
+            IntegerWrappingSubtract => write!(f, "integer.wrapping_subtract"),
+            IntegerWrappingMultiply => write!(f, "integer.wrapping_multiply"),
+            IntegerWrappingPower => write!(f, "integer.wrapping_power"),
+            IntegerWrappingShiftLeft => write!(f, "integer.wrapping_shift_left"),
+            IntegerSaturatingAdd => write!(f, "integer.saturating_add"),
+            IntegerSaturatingSubtract => write!(f, "integer.saturating_subtract"),
+            IntegerSaturatingMultiply => write!(f, "integer.saturating_multiply"),
+            IntegerSaturatingPower => write!(f, "integer.saturating_power"),
+            IntegerSaturatingShiftLeft => write!(f, "integer.saturating_shift_left"),
+            IntegerPopCount => write!(f, "integer.pop_count"),
+            IntegerBitLength => write!(f, "integer.bit_length"),
+            IntegerTestBit => write!(f, "integer.test_bit"),
+            IntegerSetBit => write!(f, "integer.set_bit"),
+            IntegerClearBit => write!(f, "integer.clear_bit"),
+            IntegerFloorDivide => write!(f, "integer.floor_divide"),
+            IntegerFloorModulo => write!(f, "integer.floor_modulo"),
+            IntegerEuclideanDivide => write!(f, "integer.euclidean_divide"),
+            IntegerEuclideanModulo => write!(f, "integer.euclidean_modulo"),
+            IntegerDivMod(mode) => write!(f, "integer.divmod {}", mode),
+            IntegerGcd => write!(f, "integer.gcd"),
+            IntegerLcm => write!(f, "integer.lcm"),
+            IntegerFactorial => write!(f, "integer.factorial"),
+            IntegerBinomial => write!(f, "integer.binomial"),
+            IntegerSquareRoot => write!(f, "integer.square_root"),
+            IntegerPowMod => write!(f, "integer.pow_mod"),
+            IntegerModInverse => write!(f, "integer.mod_inverse"),
+            IntegerIsPrime => write!(f, "integer.is_prime"),
             DecimalNew(value) => write!(f, "decimal.new {}", value),
             DecimalAdd => write!(f, "decimal.add"),
             DecimalSubtract => write!(f, "decimal.subtract"),
@@ -313,6 +659,10 @@ impl Display for RealInstruction {
             DecimalModulo => write!(f, "decimal.modulo"),
             DecimalPower => write!(f, "decimal.power"),
             DecimalNegate => write!(f, "decimal.negate"),
+            DecimalRound(rounding) => write!(f, "decimal.round {}", rounding),
+            DecimalRoundTo(rounding) => write!(f, "decimal.round_to {}", rounding),
+            DecimalConvert(typ) => write!(f, "decimal.convert {}", typ),
+            DecimalDivideTo(rounding) => write!(f, "decimal.divide_to {}", rounding),
             StringNew(value) => write!(f, "string.new {}", value),
             StringConcat => write!(f, "string.concat"),
             StringLength => write!(f, "string.length"),
@@ -333,6 +683,10 @@ impl Display for RealInstruction {
             Read => write!(f, "read"),
             GetStringRef(path, index) => write!(f, "get_string_ref {} {}", path, index),
             Cast(value_type) => write!(f, "cast {}", value_type),
+            IncrementLocal(name, value) => write!(f, "increment_local {} {}", name, value),
+            DecrementLocal(name, value) => write!(f, "decrement_local {} {}", name, value),
+            CompareGoto(comparison, target, condition) => write!(f, "compare_goto {} {} {}", comparison, target, condition),
+            LookupPairOperation(left, right, operation) => write!(f, "lookup_pair {} {} {}", left, right, operation),
         }
     }
 }
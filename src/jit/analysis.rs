@@ -0,0 +1,326 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use crate::instruction::{BinaryOperation, Condition, FunctionSource, RealInstruction};
+use crate::jit::{FunctionKey, JitType};
+use crate::program::module::Module;
+use crate::value::decimal::Decimal;
+use crate::value::function::Function;
+
+/// The reason a function can't be compiled.
+/// The JIT never reports these, the function just stays in the interpreter.
+#[derive(Debug)]
+pub struct Unsupported;
+
+/// The abstract machine state right before an instruction runs.
+#[derive(Clone, PartialEq, Debug)]
+pub struct State {
+    /// The types of the values the function has pushed on the stack.
+    pub stack: Vec<JitType>,
+    /// The locals that are assigned on every path to the instruction.
+    pub defined: BTreeSet<Box<str>>,
+}
+
+/// Everything the code generator needs to know about a function specialized to its argument types.
+pub struct FunctionInfo<'m> {
+    pub function: &'m Function,
+    pub arguments: Box<[JitType]>,
+    pub results: Box<[JitType]>,
+    pub locals: BTreeMap<Box<str>, JitType>,
+    /// The state at each instruction, `None` if the instruction can't be reached.
+    pub states: Vec<Option<State>>,
+    /// The function each `FunctionCall` instruction calls.
+    pub calls: HashMap<usize, FunctionKey>,
+}
+
+/// The result of a single pass over a function.
+struct Pass<'m> {
+    info: FunctionInfo<'m>,
+    results: Option<Box<[JitType]>>,
+    /// A call to the function itself was skipped since its results weren't known yet.
+    blocked_on_self: bool,
+}
+
+/// Infers the stack shape and the types of locals for a function and everything it calls.
+///
+/// A function is only supported if every instruction sees the same stack shape and local types
+/// on all paths leading to it, it never pops values it didn't push and it only uses
+/// fixed width integers and floats.
+pub struct Analyzer<'m> {
+    module: &'m Module,
+    pub functions: HashMap<FunctionKey, FunctionInfo<'m>>,
+    in_progress: HashMap<FunctionKey, Option<Box<[JitType]>>>,
+}
+
+impl<'m> Analyzer<'m> {
+    pub fn new(module: &'m Module) -> Analyzer<'m> {
+        Analyzer {
+            module,
+            functions: HashMap::new(),
+            in_progress: HashMap::new(),
+        }
+    }
+
+    /// Analyzes the function (and its callees) specialized for the argument types.
+    pub fn analyze(&mut self, function: &'m Function, arguments: &[JitType]) -> Result<FunctionKey, Unsupported> {
+        let key = FunctionKey::new(function, arguments);
+        if self.functions.contains_key(&key) || self.in_progress.contains_key(&key) {
+            return Ok(key);
+        }
+        if !function.environment.is_empty() {
+            return Err(Unsupported);
+        }
+
+        self.in_progress.insert(key.clone(), None);
+        let mut pass = self.pass(&key, function, arguments)?;
+        if pass.blocked_on_self {
+            // Recursive calls are skipped until a non recursive path tells us what the function
+            // returns. One more pass with that knowledge covers the recursive paths.
+            if pass.results.is_none() {
+                return Err(Unsupported);
+            }
+            self.in_progress.insert(key.clone(), pass.results.clone());
+            pass = self.pass(&key, function, arguments)?;
+            if pass.results != self.in_progress[&key] {
+                return Err(Unsupported);
+            }
+        }
+        self.in_progress.remove(&key);
+
+        let mut info = pass.info;
+        info.results = pass.results.ok_or(Unsupported)?;
+        self.functions.insert(key.clone(), info);
+        Ok(key)
+    }
+
+    fn pass(&mut self, key: &FunctionKey, function: &'m Function, arguments: &[JitType]) -> Result<Pass<'m>, Unsupported> {
+        let code = &function.code;
+        if arguments.len() != function.argument_names.len() {
+            return Err(Unsupported);
+        }
+
+        let mut locals = BTreeMap::new();
+        for (name, typ) in function.argument_names.iter().zip(arguments.iter()) {
+            if locals.insert(name.clone(), *typ).is_some() {
+                return Err(Unsupported);
+            }
+        }
+
+        let mut pass = Pass {
+            info: FunctionInfo {
+                function,
+                arguments: arguments.into(),
+                results: Box::new([]),
+                locals,
+                states: vec![None; code.len()],
+                calls: HashMap::new(),
+            },
+            results: None,
+            blocked_on_self: false,
+        };
+        if code.is_empty() {
+            return Err(Unsupported);
+        }
+
+        pass.info.states[0] = Some(State {
+            stack: Vec::new(),
+            defined: function.argument_names.iter().cloned().collect(),
+        });
+        let mut worklist = vec![0];
+        while let Some(address) = worklist.pop() {
+            let state = pass.info.states[address].clone().unwrap();
+            for (successor, state) in self.transfer(key, &mut pass, address, state)? {
+                if successor >= code.len() {
+                    return Err(Unsupported);
+                }
+                match &mut pass.info.states[successor] {
+                    None => {
+                        pass.info.states[successor] = Some(state);
+                        worklist.push(successor);
+                    }
+                    Some(existing) => {
+                        if existing.stack != state.stack {
+                            return Err(Unsupported);
+                        }
+                        let defined = existing.defined.intersection(&state.defined).cloned().collect::<BTreeSet<_>>();
+                        if defined != existing.defined {
+                            existing.defined = defined;
+                            worklist.push(successor);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(pass)
+    }
+
+    /// Runs one instruction on the abstract state and returns the states of its successors.
+    fn transfer(&mut self, key: &FunctionKey, pass: &mut Pass<'m>, address: usize, mut state: State) -> Result<Vec<(usize, State)>, Unsupported> {
+        use RealInstruction::*;
+        let next = address + 1;
+        let instruction = &pass.info.function.code[address].instruction;
+        match instruction {
+            NoOp => {}
+            Pop => {
+                pop(&mut state)?;
+            }
+            Duplicate => {
+                let typ = pop(&mut state)?;
+                state.stack.push(typ);
+                state.stack.push(typ);
+            }
+            IntegerNew(integer) => state.stack.push(JitType::of_integer(integer).ok_or(Unsupported)?),
+            DecimalNew(Decimal::F32(_)) => state.stack.push(JitType::F32),
+            DecimalNew(Decimal::F64(_)) => state.stack.push(JitType::F64),
+            Lookup(name) => lookup(&pass.info.locals, &mut state, name)?,
+            Store(name) => {
+                let typ = pop(&mut state)?;
+                match pass.info.locals.get(name) {
+                    Some(existing) if *existing != typ => return Err(Unsupported),
+                    _ => {
+                        pass.info.locals.insert(name.clone(), typ);
+                    }
+                }
+                state.defined.insert(name.clone());
+            }
+            IntegerAdd | IntegerSubtract | IntegerMultiply | IntegerDivide | IntegerModulo |
+            IntegerBitwiseAnd | IntegerBitwiseOr | IntegerBitwiseXor | IntegerShiftLeft | IntegerShiftRight => {
+                binary(&mut state, JitType::is_integer)?;
+            }
+            DecimalAdd | DecimalSubtract | DecimalMultiply | DecimalDivide => {
+                binary(&mut state, JitType::is_float)?;
+            }
+            IntegerNegate => unary(&mut state, JitType::is_signed)?,
+            IntegerBitwiseNot => unary(&mut state, JitType::is_integer)?,
+            DecimalNegate => unary(&mut state, JitType::is_float)?,
+            Compare(_) => compare(&mut state)?,
+            IncrementLocal(name, amount) | DecrementLocal(name, amount) => {
+                let typ = JitType::of_integer(amount).ok_or(Unsupported)?;
+                if !state.defined.contains(name) || pass.info.locals.get(name) != Some(&typ) {
+                    return Err(Unsupported);
+                }
+            }
+            LookupPairOperation(left, right, operation) => {
+                lookup(&pass.info.locals, &mut state, left)?;
+                lookup(&pass.info.locals, &mut state, right)?;
+                match operation {
+                    BinaryOperation::DecimalAdd | BinaryOperation::DecimalSubtract |
+                    BinaryOperation::DecimalMultiply | BinaryOperation::DecimalDivide => binary(&mut state, JitType::is_float)?,
+                    _ => binary(&mut state, JitType::is_integer)?,
+                }
+            }
+            Goto(target, condition) | CompareGoto(_, target, condition) => {
+                if let CompareGoto(_, _, _) = instruction {
+                    compare(&mut state)?;
+                }
+                let target = target.resolve(address).ok_or(Unsupported)?;
+                if let Condition::Always = condition {
+                    return Ok(vec![(target, state)]);
+                }
+                return Ok(vec![(target, state.clone()), (next, state)]);
+            }
+            Return(condition) => {
+                let results = state.stack.clone().into_boxed_slice();
+                match &pass.results {
+                    Some(existing) if *existing != results => return Err(Unsupported),
+                    _ => pass.results = Some(results),
+                }
+                if let Condition::Always = condition {
+                    return Ok(vec![]);
+                }
+            }
+            FunctionCall(FunctionSource::Name(path), condition) => {
+                let callee = self.module.get_bytecode_function(path).ok_or(Unsupported)?;
+                let mut arguments = Vec::with_capacity(callee.argument_names.len());
+                let mut called = state.clone();
+                for _ in callee.argument_names.iter() {
+                    arguments.push(pop(&mut called)?);
+                }
+
+                let callee_key = self.analyze(callee, &arguments)?;
+                let results = match self.functions.get(&callee_key) {
+                    Some(info) => Some(info.results.clone()),
+                    None if callee_key == *key => {
+                        let results = self.in_progress[&callee_key].clone();
+                        if results.is_none() {
+                            pass.blocked_on_self = true;
+                        }
+                        results
+                    }
+                    // Mutual recursion is left to the interpreter.
+                    None => return Err(Unsupported),
+                };
+                pass.info.calls.insert(address, callee_key);
+
+                let mut successors = Vec::new();
+                if let Some(results) = results {
+                    called.stack.extend(results.iter());
+                    successors.push((next, called));
+                }
+                if !matches!(condition, Condition::Always) {
+                    successors.push((next, state));
+                }
+                return Ok(successors);
+            }
+            _ => return Err(Unsupported),
+        }
+        Ok(vec![(next, state)])
+    }
+}
+
+fn pop(state: &mut State) -> Result<JitType, Unsupported> {
+    state.stack.pop().ok_or(Unsupported)
+}
+
+fn lookup(locals: &BTreeMap<Box<str>, JitType>, state: &mut State, name: &str) -> Result<(), Unsupported> {
+    if !state.defined.contains(name) {
+        return Err(Unsupported);
+    }
+    state.stack.push(*locals.get(name).ok_or(Unsupported)?);
+    Ok(())
+}
+
+fn unary(state: &mut State, allowed: fn(&JitType) -> bool) -> Result<(), Unsupported> {
+    let typ = pop(state)?;
+    if !allowed(&typ) {
+        return Err(Unsupported);
+    }
+    state.stack.push(typ);
+    Ok(())
+}
+
+fn binary(state: &mut State, allowed: fn(&JitType) -> bool) -> Result<(), Unsupported> {
+    let right = pop(state)?;
+    let left = pop(state)?;
+    if left != right || !allowed(&left) {
+        return Err(Unsupported);
+    }
+    state.stack.push(left);
+    Ok(())
+}
+
+fn compare(state: &mut State) -> Result<(), Unsupported> {
+    let right = pop(state)?;
+    let left = pop(state)?;
+    if left != right {
+        return Err(Unsupported);
+    }
+    state.stack.push(left);
+    state.stack.push(right);
+    Ok(())
+}
+
+/// Returns every function reachable from `root` through calls, `root` first.
+pub fn call_tree(functions: &HashMap<FunctionKey, FunctionInfo>, root: &FunctionKey) -> Vec<FunctionKey> {
+    let mut seen = HashSet::new();
+    let mut order = Vec::new();
+    let mut worklist = vec![root.clone()];
+    while let Some(key) = worklist.pop() {
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        if let Some(info) = functions.get(&key) {
+            worklist.extend(info.calls.values().cloned());
+        }
+        order.push(key);
+    }
+    order
+}
@@ -0,0 +1,805 @@
+use std::collections::HashMap;
+use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::{types, AbiParam, Block, FuncRef, InstBuilder, MemFlags, Signature, StackSlotData, StackSlotKind, Type, UserFuncName, Value};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_codegen::Context;
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, FuncId, Linkage, Module as _};
+use fxhash::FxHashMap;
+use crate::analysis::cfg::ControlFlowGraph;
+use crate::instruction::{BinaryOperation, ComparisonType, Condition, RealInstruction};
+use crate::jit::analysis::{call_tree, Analyzer, FunctionInfo};
+use crate::jit::{CompiledFunction, FunctionKey, JitType, NativeFunction, FLAG_BYTES, FLAG_CARRY, FLAG_COMPARISON, FLAG_NEGATIVE, FLAG_OVERFLOW, FLAG_ZERO};
+use crate::machine::core::Comparison;
+use crate::program::module::Module;
+use crate::value::decimal::Decimal;
+use crate::value::function::Function;
+use crate::value::integer::Integer;
+
+/// Integer operations the code generator knows how to emit.
+#[derive(Clone, Copy)]
+enum IntegerOperation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    And,
+    Or,
+    Xor,
+    ShiftLeft,
+    ShiftRight,
+}
+
+/// Decimal operations the code generator knows how to emit.
+#[derive(Clone, Copy)]
+enum DecimalOperation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+/// The operations behind a `LookupPairOperation` superinstruction.
+fn split_binary_operation(operation: &BinaryOperation) -> Result<IntegerOperation, DecimalOperation> {
+    match operation {
+        BinaryOperation::IntegerAdd => Ok(IntegerOperation::Add),
+        BinaryOperation::IntegerSubtract => Ok(IntegerOperation::Subtract),
+        BinaryOperation::IntegerMultiply => Ok(IntegerOperation::Multiply),
+        BinaryOperation::IntegerDivide => Ok(IntegerOperation::Divide),
+        BinaryOperation::IntegerModulo => Ok(IntegerOperation::Modulo),
+        BinaryOperation::IntegerBitwiseAnd => Ok(IntegerOperation::And),
+        BinaryOperation::IntegerBitwiseOr => Ok(IntegerOperation::Or),
+        BinaryOperation::IntegerBitwiseXor => Ok(IntegerOperation::Xor),
+        BinaryOperation::DecimalAdd => Err(DecimalOperation::Add),
+        BinaryOperation::DecimalSubtract => Err(DecimalOperation::Subtract),
+        BinaryOperation::DecimalMultiply => Err(DecimalOperation::Multiply),
+        BinaryOperation::DecimalDivide => Err(DecimalOperation::Divide),
+    }
+}
+
+fn cranelift_type(typ: JitType) -> Type {
+    match typ {
+        JitType::U8 | JitType::I8 => types::I8,
+        JitType::U16 | JitType::I16 => types::I16,
+        JitType::U32 | JitType::I32 => types::I32,
+        JitType::U64 | JitType::I64 => types::I64,
+        JitType::F32 => types::F32,
+        JitType::F64 => types::F64,
+    }
+}
+
+/// The raw bits of a fixed width integer constant.
+fn integer_bits(integer: &Integer) -> i64 {
+    match integer {
+        Integer::U8(value) => *value as i64,
+        Integer::U16(value) => *value as i64,
+        Integer::U32(value) => *value as i64,
+        Integer::U64(value) => *value as i64,
+        Integer::I8(value) => *value as i64,
+        Integer::I16(value) => *value as i64,
+        Integer::I32(value) => *value as i64,
+        Integer::I64(value) => *value,
+        x => panic!("Cannot compile bignum constant {:?}", x),
+    }
+}
+
+/// Owns the Cranelift JIT module all the functions of a `Core` are compiled into.
+pub struct Backend {
+    module: JITModule,
+    context: Context,
+    builder_context: FunctionBuilderContext,
+    functions: usize,
+}
+
+impl Backend {
+    pub fn new() -> Backend {
+        let mut flags = settings::builder();
+        flags.set("use_colocated_libcalls", "false").unwrap();
+        flags.set("is_pic", "false").unwrap();
+        flags.set("opt_level", "speed").unwrap();
+        let isa = cranelift_native::builder()
+            .unwrap_or_else(|message| panic!("JIT is not supported on this host: {}", message))
+            .finish(settings::Flags::new(flags))
+            .unwrap();
+        let module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+        Backend {
+            context: module.make_context(),
+            module,
+            builder_context: FunctionBuilderContext::new(),
+            functions: 0,
+        }
+    }
+
+    fn signature(&self) -> Signature {
+        let pointer = self.module.target_config().pointer_type();
+        let mut signature = self.module.make_signature();
+        signature.params.push(AbiParam::new(pointer));
+        signature.params.push(AbiParam::new(pointer));
+        signature.params.push(AbiParam::new(pointer));
+        signature.returns.push(AbiParam::new(types::I32));
+        signature
+    }
+
+    /// Compiles the function specialized for `arguments` together with everything it calls.
+    /// The outcome is recorded in `compiled`, including when the function can't be compiled.
+    pub fn compile(&mut self,
+                   module: &Module,
+                   function: &Function,
+                   arguments: &[JitType],
+                   compiled: &mut FxHashMap<FunctionKey, Option<CompiledFunction>>) {
+        let mut analyzer = Analyzer::new(module);
+        let root = match analyzer.analyze(function, arguments) {
+            Ok(root) => root,
+            Err(_) => {
+                compiled.insert(FunctionKey::new(function, arguments), None);
+                return;
+            }
+        };
+
+        let signature = self.signature();
+        let mut ids = HashMap::new();
+        let mut new_functions = Vec::new();
+        for key in call_tree(&analyzer.functions, &root) {
+            match compiled.get(&key) {
+                Some(Some(existing)) => {
+                    ids.insert(key, existing.id);
+                }
+                _ => {
+                    let name = format!("crayfish_jit_{}", self.functions);
+                    self.functions += 1;
+                    let id = self.module.declare_function(&name, Linkage::Local, &signature).unwrap();
+                    ids.insert(key.clone(), id);
+                    new_functions.push(key);
+                }
+            }
+        }
+
+        for key in new_functions.iter() {
+            let info = &analyzer.functions[key];
+            self.context.func.signature = signature.clone();
+            self.context.func.name = UserFuncName::user(0, ids[key].as_u32());
+            self.translate(info, &analyzer.functions, &ids);
+            if self.module.define_function(ids[key], &mut self.context).is_err() {
+                self.module.clear_context(&mut self.context);
+                compiled.insert(root, None);
+                return;
+            }
+            self.module.clear_context(&mut self.context);
+        }
+        self.module.finalize_definitions().unwrap();
+
+        for key in new_functions {
+            let id = ids[&key];
+            let pointer = self.module.get_finalized_function(id);
+            // Safety: every function in this module is defined with the `NativeFunction` signature.
+            let pointer = unsafe { std::mem::transmute::<*const u8, NativeFunction>(pointer) };
+            let results = analyzer.functions[&key].results.clone();
+            compiled.insert(key, Some(CompiledFunction { id, results, pointer }));
+        }
+    }
+
+    fn translate(&mut self, info: &FunctionInfo, functions: &HashMap<FunctionKey, FunctionInfo>, ids: &HashMap<FunctionKey, FuncId>) {
+        let pointer_type = self.module.target_config().pointer_type();
+        let mut builder = FunctionBuilder::new(&mut self.context.func, &mut self.builder_context);
+
+        let mut callees = HashMap::new();
+        for (address, key) in info.calls.iter() {
+            let callee = self.module.declare_func_in_func(ids[key], builder.func);
+            callees.insert(*address, (callee, key.arguments.len(), functions[key].results.clone()));
+        }
+
+        let entry = builder.create_block();
+        let deopt = builder.create_block();
+        builder.append_block_params_for_function_params(entry);
+        builder.switch_to_block(entry);
+        let parameters = builder.block_params(entry).to_vec();
+
+        let translator = Translator {
+            builder,
+            info,
+            callees,
+            stack: Vec::new(),
+            slots: HashMap::new(),
+            locals: HashMap::new(),
+            flags: [Variable::from_u32(0), Variable::from_u32(1), Variable::from_u32(2), Variable::from_u32(3), Variable::from_u32(4)],
+            variables: FLAG_BYTES as u32,
+            blocks: HashMap::new(),
+            cfg: ControlFlowGraph::new(info.function),
+            deopt,
+            results_pointer: parameters[1],
+            flags_pointer: parameters[2],
+            pointer_type,
+        };
+        translator.translate(parameters[0]);
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Backend::new()
+    }
+}
+
+/// Translates one function into Cranelift IR.
+///
+/// Every stack slot and local is a Cranelift `Variable`, so the SSA construction in
+/// `cranelift_frontend` takes care of merging values where control flow joins.
+/// The analysis guarantees that the stack has the same shape on every path into an
+/// instruction, so a slot is identified by its depth and type.
+struct Translator<'a, 'b> {
+    builder: FunctionBuilder<'a>,
+    info: &'b FunctionInfo<'b>,
+    /// The callee, argument count and result types of each call instruction.
+    callees: HashMap<usize, (FuncRef, usize, Box<[JitType]>)>,
+    stack: Vec<JitType>,
+    slots: HashMap<(usize, JitType), Variable>,
+    locals: HashMap<Box<str>, Variable>,
+    flags: [Variable; FLAG_BYTES],
+    variables: u32,
+    blocks: HashMap<usize, Block>,
+    cfg: ControlFlowGraph,
+    deopt: Block,
+    results_pointer: Value,
+    flags_pointer: Value,
+    pointer_type: Type,
+}
+
+impl Translator<'_, '_> {
+    fn new_variable(&mut self, typ: Type) -> Variable {
+        let variable = Variable::from_u32(self.variables);
+        self.variables += 1;
+        self.builder.declare_var(variable, typ);
+        variable
+    }
+
+    fn translate(mut self, arguments_pointer: Value) {
+        for flag in self.flags {
+            self.builder.declare_var(flag, types::I8);
+        }
+        for (name, typ) in self.info.locals.iter() {
+            let variable = self.new_variable(cranelift_type(*typ));
+            self.locals.insert(name.clone(), variable);
+        }
+
+        for (index, name) in self.info.function.argument_names.iter().enumerate() {
+            let typ = self.info.arguments[index];
+            let bits = self.builder.ins().load(types::I64, MemFlags::trusted(), arguments_pointer, (index * 8) as i32);
+            let value = self.unpack(typ, bits);
+            self.builder.def_var(self.locals[name], value);
+        }
+        for (offset, flag) in self.flags.into_iter().enumerate() {
+            let value = self.builder.ins().load(types::I8, MemFlags::trusted(), self.flags_pointer, offset as i32);
+            self.builder.def_var(flag, value);
+        }
+
+        for (index, block) in self.cfg.blocks.iter().enumerate() {
+            if self.info.states[block.start].is_some() {
+                let cranelift_block = self.builder.create_block();
+                self.blocks.insert(index, cranelift_block);
+            }
+        }
+        let first = self.block_for(0);
+        self.builder.ins().jump(first, &[]);
+
+        for index in 0..self.cfg.blocks.len() {
+            let Some(cranelift_block) = self.blocks.get(&index).copied() else {
+                continue;
+            };
+            let block = self.cfg.blocks[index].clone();
+            self.builder.switch_to_block(cranelift_block);
+            self.stack = self.info.states[block.start].as_ref().unwrap().stack.clone();
+
+            let mut terminated = false;
+            for address in block.addresses() {
+                terminated = self.instruction(address);
+            }
+            if !terminated {
+                let next = self.block_for(block.end);
+                self.builder.ins().jump(next, &[]);
+            }
+        }
+
+        self.builder.switch_to_block(self.deopt);
+        let status = self.builder.ins().iconst(types::I32, 1);
+        self.builder.ins().return_(&[status]);
+
+        self.builder.seal_all_blocks();
+        self.builder.finalize();
+    }
+
+    /// Returns the block starting at `address`, or the deoptimization block
+    /// if the analysis found the address unreachable.
+    fn block_for(&self, address: usize) -> Block {
+        if address >= self.info.states.len() || self.info.states[address].is_none() {
+            return self.deopt;
+        }
+        self.cfg.block_containing(address)
+            .and_then(|block| self.blocks.get(&block).copied())
+            .unwrap_or(self.deopt)
+    }
+
+    fn slot(&mut self, depth: usize, typ: JitType) -> Variable {
+        if let Some(variable) = self.slots.get(&(depth, typ)) {
+            return *variable;
+        }
+        let variable = self.new_variable(cranelift_type(typ));
+        self.slots.insert((depth, typ), variable);
+        variable
+    }
+
+    fn push(&mut self, typ: JitType, value: Value) {
+        let variable = self.slot(self.stack.len(), typ);
+        self.builder.def_var(variable, value);
+        self.stack.push(typ);
+    }
+
+    fn pop(&mut self) -> (JitType, Value) {
+        let typ = self.stack.pop().unwrap();
+        let variable = self.slot(self.stack.len(), typ);
+        (typ, self.builder.use_var(variable))
+    }
+
+    fn integer_constant(&mut self, typ: JitType, value: i64) -> Value {
+        let value = if typ.bits() == 64 {
+            value
+        } else {
+            value & ((1i64 << typ.bits()) - 1)
+        };
+        self.builder.ins().iconst(cranelift_type(typ), value)
+    }
+
+    fn pack(&mut self, typ: JitType, value: Value) -> Value {
+        match typ {
+            JitType::F32 => {
+                let bits = self.builder.ins().bitcast(types::I32, MemFlags::new(), value);
+                self.builder.ins().uextend(types::I64, bits)
+            }
+            JitType::F64 => self.builder.ins().bitcast(types::I64, MemFlags::new(), value),
+            typ if typ.bits() < 64 => self.builder.ins().uextend(types::I64, value),
+            _ => value,
+        }
+    }
+
+    fn unpack(&mut self, typ: JitType, bits: Value) -> Value {
+        match typ {
+            JitType::F32 => {
+                let bits = self.builder.ins().ireduce(types::I32, bits);
+                self.builder.ins().bitcast(types::F32, MemFlags::new(), bits)
+            }
+            JitType::F64 => self.builder.ins().bitcast(types::F64, MemFlags::new(), bits),
+            typ if typ.bits() < 64 => self.builder.ins().ireduce(cranelift_type(typ), bits),
+            _ => bits,
+        }
+    }
+
+    /// Leaves the compiled code and lets the interpreter rerun the call if `condition` is set.
+    fn deopt_if(&mut self, condition: Value) {
+        let next = self.builder.create_block();
+        self.builder.ins().brif(condition, self.deopt, &[], next, &[]);
+        self.builder.switch_to_block(next);
+    }
+
+    /// Sets the zero and negative flags like the interpreter does after an arithmetic instruction.
+    fn set_result_flags(&mut self, typ: JitType, value: Value) {
+        let (zero, negative) = if typ.is_float() {
+            let constant = match typ {
+                JitType::F32 => self.builder.ins().f32const(0.0),
+                _ => self.builder.ins().f64const(0.0),
+            };
+            let zero = self.builder.ins().fcmp(FloatCC::Equal, value, constant);
+            let negative = self.builder.ins().fcmp(FloatCC::LessThan, value, constant);
+            (zero, negative)
+        } else {
+            let zero = self.builder.ins().icmp_imm(IntCC::Equal, value, 0);
+            let negative = if typ.is_signed() {
+                self.builder.ins().icmp_imm(IntCC::SignedLessThan, value, 0)
+            } else {
+                self.builder.ins().iconst(types::I8, 0)
+            };
+            (zero, negative)
+        };
+        self.builder.def_var(self.flags[FLAG_ZERO], zero);
+        self.builder.def_var(self.flags[FLAG_NEGATIVE], negative);
+    }
+
+    /// Compiled code deoptimizes instead of overflowing, so integer operations only ever set the carry flag.
+    fn set_carry(&mut self, carry: Value) {
+        let zero = self.builder.ins().iconst(types::I8, 0);
+        self.builder.def_var(self.flags[FLAG_OVERFLOW], zero);
+        self.builder.def_var(self.flags[FLAG_CARRY], carry);
+    }
+
+    fn clear_carry(&mut self) {
+        let zero = self.builder.ins().iconst(types::I8, 0);
+        self.set_carry(zero);
+    }
+
+    /// Returns a nonzero value if the condition holds.
+    fn condition(&mut self, condition: &Condition) -> Value {
+        let comparison = self.builder.use_var(self.flags[FLAG_COMPARISON]);
+        let negative = self.builder.use_var(self.flags[FLAG_NEGATIVE]);
+        let zero = self.builder.use_var(self.flags[FLAG_ZERO]);
+        let overflow = self.builder.use_var(self.flags[FLAG_OVERFLOW]);
+        let carry = self.builder.use_var(self.flags[FLAG_CARRY]);
+        let equals = |builder: &mut FunctionBuilder, expected: Comparison| {
+            builder.ins().icmp_imm(IntCC::Equal, comparison, expected as u8 as i64)
+        };
+        match condition {
+            Condition::Always => self.builder.ins().iconst(types::I8, 1),
+            Condition::Equal => equals(&mut self.builder, Comparison::Equal),
+            Condition::NotEqual => equals(&mut self.builder, Comparison::NotEqual),
+            Condition::LessThan => equals(&mut self.builder, Comparison::LessThan),
+            Condition::LessThanOrEqual => equals(&mut self.builder, Comparison::LessThanOrEqual),
+            Condition::GreaterThan => equals(&mut self.builder, Comparison::GreaterThan),
+            Condition::GreaterThanOrEqual => equals(&mut self.builder, Comparison::GreaterThanOrEqual),
+            Condition::Zero => zero,
+            Condition::NotZero => self.builder.ins().icmp_imm(IntCC::Equal, zero, 0),
+            Condition::Negative => negative,
+            Condition::NotNegative => self.builder.ins().icmp_imm(IntCC::Equal, negative, 0),
+            Condition::Carry => carry,
+            Condition::NoCarry => self.builder.ins().icmp_imm(IntCC::Equal, carry, 0),
+            Condition::Overflow => overflow,
+            Condition::NoOverflow => self.builder.ins().icmp_imm(IntCC::Equal, overflow, 0),
+        }
+    }
+
+    fn integer_operation(&mut self, operation: IntegerOperation) {
+        let (typ, right) = self.pop();
+        let (_, left) = self.pop();
+        let result = self.integer_operation_values(operation, typ, left, right);
+        self.push(typ, result);
+        self.set_result_flags(typ, result);
+    }
+
+    fn integer_operation_values(&mut self, operation: IntegerOperation, typ: JitType, left: Value, right: Value) -> Value {
+        let signed = typ.is_signed();
+        match operation {
+            IntegerOperation::Add | IntegerOperation::Subtract | IntegerOperation::Multiply => {
+                let (result, overflow) = match (operation, signed) {
+                    (IntegerOperation::Add, true) => self.builder.ins().sadd_overflow(left, right),
+                    (IntegerOperation::Add, false) => self.builder.ins().uadd_overflow(left, right),
+                    (IntegerOperation::Subtract, true) => self.builder.ins().ssub_overflow(left, right),
+                    (IntegerOperation::Subtract, false) => self.builder.ins().usub_overflow(left, right),
+                    (_, true) => self.builder.ins().smul_overflow(left, right),
+                    (_, false) => self.builder.ins().umul_overflow(left, right),
+                };
+                self.deopt_if(overflow);
+                // Unsigned operations that got this far didn't carry, signed ones can still carry out of the bit pattern.
+                let carry = match (operation, signed) {
+                    (IntegerOperation::Add, true) => self.builder.ins().uadd_overflow(left, right).1,
+                    (IntegerOperation::Subtract, true) => self.builder.ins().icmp(IntCC::UnsignedLessThan, left, right),
+                    _ => self.builder.ins().iconst(types::I8, 0),
+                };
+                self.set_carry(carry);
+                result
+            }
+            IntegerOperation::Divide | IntegerOperation::Modulo => {
+                let by_zero = self.builder.ins().icmp_imm(IntCC::Equal, right, 0);
+                self.deopt_if(by_zero);
+                if signed {
+                    let minimum = self.integer_constant(typ, i64::MIN >> (64 - typ.bits()));
+                    let minus_one = self.integer_constant(typ, -1);
+                    let is_minimum = self.builder.ins().icmp(IntCC::Equal, left, minimum);
+                    let is_minus_one = self.builder.ins().icmp(IntCC::Equal, right, minus_one);
+                    let overflow = self.builder.ins().band(is_minimum, is_minus_one);
+                    self.deopt_if(overflow);
+                }
+                self.clear_carry();
+                match (operation, signed) {
+                    (IntegerOperation::Divide, true) => self.builder.ins().sdiv(left, right),
+                    (IntegerOperation::Divide, false) => self.builder.ins().udiv(left, right),
+                    (_, true) => self.builder.ins().srem(left, right),
+                    (_, false) => self.builder.ins().urem(left, right),
+                }
+            }
+            IntegerOperation::ShiftLeft | IntegerOperation::ShiftRight => {
+                let too_far = self.builder.ins().icmp_imm(IntCC::UnsignedGreaterThanOrEqual, right, typ.bits() as i64);
+                self.deopt_if(too_far);
+                match (operation, signed) {
+                    (IntegerOperation::ShiftLeft, _) => {
+                        // Shifting back must give the original value, or bits were lost.
+                        let result = self.builder.ins().ishl(left, right);
+                        let restored = if signed {
+                            self.builder.ins().sshr(result, right)
+                        } else {
+                            self.builder.ins().ushr(result, right)
+                        };
+                        let lost = self.builder.ins().icmp(IntCC::NotEqual, restored, left);
+                        self.deopt_if(lost);
+                        // Nothing was lost, so the bits shifted out were all copies of the sign bit.
+                        let carry = if signed {
+                            let shifted = self.builder.ins().icmp_imm(IntCC::NotEqual, right, 0);
+                            let negative = self.builder.ins().icmp_imm(IntCC::SignedLessThan, left, 0);
+                            self.builder.ins().band(shifted, negative)
+                        } else {
+                            self.builder.ins().iconst(types::I8, 0)
+                        };
+                        self.set_carry(carry);
+                        result
+                    }
+                    (_, signed) => {
+                        let one_less = self.builder.ins().iadd_imm(right, -1);
+                        let last = self.builder.ins().ushr(left, one_less);
+                        let last = self.builder.ins().band_imm(last, 1);
+                        let last = self.builder.ins().icmp_imm(IntCC::NotEqual, last, 0);
+                        let shifted = self.builder.ins().icmp_imm(IntCC::NotEqual, right, 0);
+                        let carry = self.builder.ins().band(last, shifted);
+                        self.set_carry(carry);
+                        if signed {
+                            self.builder.ins().sshr(left, right)
+                        } else {
+                            self.builder.ins().ushr(left, right)
+                        }
+                    }
+                }
+            }
+            IntegerOperation::And | IntegerOperation::Or | IntegerOperation::Xor => {
+                self.clear_carry();
+                match operation {
+                    IntegerOperation::And => self.builder.ins().band(left, right),
+                    IntegerOperation::Or => self.builder.ins().bor(left, right),
+                    _ => self.builder.ins().bxor(left, right),
+                }
+            }
+        }
+    }
+
+    fn decimal_operation(&mut self, operation: DecimalOperation) {
+        let (typ, right) = self.pop();
+        let (_, left) = self.pop();
+        let result = match operation {
+            DecimalOperation::Add => self.builder.ins().fadd(left, right),
+            DecimalOperation::Subtract => self.builder.ins().fsub(left, right),
+            DecimalOperation::Multiply => self.builder.ins().fmul(left, right),
+            DecimalOperation::Divide => {
+                let constant = match typ {
+                    JitType::F32 => self.builder.ins().f32const(0.0),
+                    _ => self.builder.ins().f64const(0.0),
+                };
+                let by_zero = self.builder.ins().fcmp(FloatCC::Equal, right, constant);
+                self.deopt_if(by_zero);
+                self.builder.ins().fdiv(left, right)
+            }
+        };
+        self.push(typ, result);
+        self.set_result_flags(typ, result);
+    }
+
+    fn compare(&mut self, comparison_type: &ComparisonType) {
+        let (typ, right) = self.pop();
+        let (_, left) = self.pop();
+        let signed = typ.is_signed();
+        let (integer, float, holds, fails) = match comparison_type {
+            ComparisonType::Equal => (IntCC::Equal, FloatCC::Equal, Comparison::Equal, Comparison::NotEqual),
+            ComparisonType::NotEqual => (IntCC::NotEqual, FloatCC::NotEqual, Comparison::NotEqual, Comparison::Equal),
+            ComparisonType::LessThan => (
+                if signed { IntCC::SignedLessThan } else { IntCC::UnsignedLessThan },
+                FloatCC::LessThan, Comparison::LessThan, Comparison::GreaterThanOrEqual),
+            ComparisonType::LessThanOrEqual => (
+                if signed { IntCC::SignedLessThanOrEqual } else { IntCC::UnsignedLessThanOrEqual },
+                FloatCC::LessThanOrEqual, Comparison::LessThanOrEqual, Comparison::GreaterThan),
+            ComparisonType::GreaterThan => (
+                if signed { IntCC::SignedGreaterThan } else { IntCC::UnsignedGreaterThan },
+                FloatCC::GreaterThan, Comparison::GreaterThan, Comparison::LessThanOrEqual),
+            ComparisonType::GreaterThanOrEqual => (
+                if signed { IntCC::SignedGreaterThanOrEqual } else { IntCC::UnsignedGreaterThanOrEqual },
+                FloatCC::GreaterThanOrEqual, Comparison::GreaterThanOrEqual, Comparison::LessThan),
+        };
+        let result = if typ.is_float() {
+            self.builder.ins().fcmp(float, left, right)
+        } else {
+            self.builder.ins().icmp(integer, left, right)
+        };
+        let holds = self.builder.ins().iconst(types::I8, holds as u8 as i64);
+        let fails = self.builder.ins().iconst(types::I8, fails as u8 as i64);
+        let comparison = self.builder.ins().select(result, holds, fails);
+        self.builder.def_var(self.flags[FLAG_COMPARISON], comparison);
+        self.push(typ, left);
+        self.push(typ, right);
+    }
+
+    fn lookup(&mut self, name: &str) {
+        let typ = self.info.locals[name];
+        let value = self.builder.use_var(self.locals[name]);
+        self.push(typ, value);
+    }
+
+    fn store_flags(&mut self, pointer: Value) {
+        for (offset, flag) in self.flags.into_iter().enumerate() {
+            let value = self.builder.use_var(flag);
+            self.builder.ins().store(MemFlags::trusted(), value, pointer, offset as i32);
+        }
+    }
+
+    fn load_flags(&mut self, pointer: Value) {
+        for (offset, flag) in self.flags.into_iter().enumerate() {
+            let value = self.builder.ins().load(types::I8, MemFlags::trusted(), pointer, offset as i32);
+            self.builder.def_var(flag, value);
+        }
+    }
+
+    /// Writes the stack and the flags back to the caller and returns successfully.
+    fn emit_return(&mut self) {
+        for (depth, typ) in self.stack.clone().into_iter().enumerate() {
+            let variable = self.slot(depth, typ);
+            let value = self.builder.use_var(variable);
+            let bits = self.pack(typ, value);
+            self.builder.ins().store(MemFlags::trusted(), bits, self.results_pointer, (depth * 8) as i32);
+        }
+        self.store_flags(self.flags_pointer);
+        let status = self.builder.ins().iconst(types::I32, 0);
+        self.builder.ins().return_(&[status]);
+    }
+
+    fn emit_call(&mut self, address: usize) {
+        let (callee, arguments, results) = self.callees[&address].clone();
+        let arguments_slot = self.builder.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, (arguments.max(1) * 8) as u32, 3));
+        let results_slot = self.builder.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, (results.len().max(1) * 8) as u32, 3));
+        let flags_slot = self.builder.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, FLAG_BYTES as u32, 0));
+
+        for index in 0..arguments {
+            let (typ, value) = self.pop();
+            let bits = self.pack(typ, value);
+            self.builder.ins().stack_store(bits, arguments_slot, (index * 8) as i32);
+        }
+        let arguments_pointer = self.builder.ins().stack_addr(self.pointer_type, arguments_slot, 0);
+        let results_pointer = self.builder.ins().stack_addr(self.pointer_type, results_slot, 0);
+        let flags_pointer = self.builder.ins().stack_addr(self.pointer_type, flags_slot, 0);
+        self.store_flags(flags_pointer);
+
+        let call = self.builder.ins().call(callee, &[arguments_pointer, results_pointer, flags_pointer]);
+        let status = self.builder.inst_results(call)[0];
+        self.deopt_if(status);
+
+        self.load_flags(flags_pointer);
+        for (index, typ) in results.iter().enumerate() {
+            let bits = self.builder.ins().stack_load(types::I64, results_slot, (index * 8) as i32);
+            let value = self.unpack(*typ, bits);
+            self.push(*typ, value);
+        }
+    }
+
+    /// Emits one instruction. Returns true if the instruction ended the current block.
+    fn instruction(&mut self, address: usize) -> bool {
+        use RealInstruction::*;
+        let next = address + 1;
+        match &self.info.function.code[address].instruction {
+            NoOp => {}
+            Pop => {
+                self.pop();
+            }
+            Duplicate => {
+                let (typ, value) = self.pop();
+                self.push(typ, value);
+                self.push(typ, value);
+            }
+            IntegerNew(integer) => {
+                let typ = JitType::of_integer(integer).unwrap();
+                let value = self.integer_constant(typ, integer_bits(integer));
+                self.push(typ, value);
+            }
+            DecimalNew(Decimal::F32(value)) => {
+                let value = self.builder.ins().f32const(*value);
+                self.push(JitType::F32, value);
+            }
+            DecimalNew(Decimal::F64(value)) => {
+                let value = self.builder.ins().f64const(*value);
+                self.push(JitType::F64, value);
+            }
+            Lookup(name) => self.lookup(name),
+            Store(name) => {
+                let (_, value) = self.pop();
+                self.builder.def_var(self.locals[name], value);
+            }
+            IntegerAdd => self.integer_operation(IntegerOperation::Add),
+            IntegerSubtract => self.integer_operation(IntegerOperation::Subtract),
+            IntegerMultiply => self.integer_operation(IntegerOperation::Multiply),
+            IntegerDivide => self.integer_operation(IntegerOperation::Divide),
+            IntegerModulo => self.integer_operation(IntegerOperation::Modulo),
+            IntegerBitwiseAnd => self.integer_operation(IntegerOperation::And),
+            IntegerBitwiseOr => self.integer_operation(IntegerOperation::Or),
+            IntegerBitwiseXor => self.integer_operation(IntegerOperation::Xor),
+            IntegerShiftLeft => self.integer_operation(IntegerOperation::ShiftLeft),
+            IntegerShiftRight => self.integer_operation(IntegerOperation::ShiftRight),
+            IntegerNegate => {
+                let (typ, value) = self.pop();
+                let minimum = self.integer_constant(typ, i64::MIN >> (64 - typ.bits()));
+                let overflow = self.builder.ins().icmp(IntCC::Equal, value, minimum);
+                self.deopt_if(overflow);
+                let result = self.builder.ins().ineg(value);
+                self.clear_carry();
+                self.push(typ, result);
+                self.set_result_flags(typ, result);
+            }
+            IntegerBitwiseNot => {
+                let (typ, value) = self.pop();
+                let result = self.builder.ins().bnot(value);
+                self.clear_carry();
+                self.push(typ, result);
+                self.set_result_flags(typ, result);
+            }
+            DecimalAdd => self.decimal_operation(DecimalOperation::Add),
+            DecimalSubtract => self.decimal_operation(DecimalOperation::Subtract),
+            DecimalMultiply => self.decimal_operation(DecimalOperation::Multiply),
+            DecimalDivide => self.decimal_operation(DecimalOperation::Divide),
+            DecimalNegate => {
+                let (typ, value) = self.pop();
+                let result = self.builder.ins().fneg(value);
+                self.push(typ, result);
+                self.set_result_flags(typ, result);
+            }
+            Compare(comparison_type) => self.compare(comparison_type),
+            IncrementLocal(name, amount) | DecrementLocal(name, amount) => {
+                let operation = match &self.info.function.code[address].instruction {
+                    IncrementLocal(_, _) => IntegerOperation::Add,
+                    _ => IntegerOperation::Subtract,
+                };
+                let typ = self.info.locals[name];
+                let variable = self.locals[name];
+                let value = self.builder.use_var(variable);
+                let amount = self.integer_constant(typ, integer_bits(amount));
+                let result = self.integer_operation_values(operation, typ, value, amount);
+                self.builder.def_var(variable, result);
+                self.set_result_flags(typ, result);
+            }
+            LookupPairOperation(left, right, operation) => {
+                self.lookup(left);
+                self.lookup(right);
+                match split_binary_operation(operation) {
+                    Ok(operation) => self.integer_operation(operation),
+                    Err(operation) => self.decimal_operation(operation),
+                }
+            }
+            Goto(target, condition) | CompareGoto(_, target, condition) => {
+                if let CompareGoto(comparison_type, _, _) = &self.info.function.code[address].instruction {
+                    self.compare(comparison_type);
+                }
+                let target = self.block_for(target.resolve(address).unwrap());
+                match condition {
+                    Condition::Always => {
+                        self.builder.ins().jump(target, &[]);
+                    }
+                    condition => {
+                        let holds = self.condition(condition);
+                        let next = self.block_for(next);
+                        self.builder.ins().brif(holds, target, &[], next, &[]);
+                    }
+                }
+                return true;
+            }
+            Return(condition) => {
+                match condition {
+                    Condition::Always => self.emit_return(),
+                    condition => {
+                        let holds = self.condition(condition);
+                        let returning = self.builder.create_block();
+                        let next = self.block_for(next);
+                        self.builder.ins().brif(holds, returning, &[], next, &[]);
+                        self.builder.switch_to_block(returning);
+                        self.emit_return();
+                    }
+                }
+                return true;
+            }
+            FunctionCall(_, condition) => {
+                match condition {
+                    Condition::Always => self.emit_call(address),
+                    condition => {
+                        let holds = self.condition(condition);
+                        let calling = self.builder.create_block();
+                        let next = self.block_for(next);
+                        self.builder.ins().brif(holds, calling, &[], next, &[]);
+                        self.builder.switch_to_block(calling);
+                        self.emit_call(address);
+                        self.builder.ins().jump(next, &[]);
+                        return true;
+                    }
+                }
+            }
+            x => panic!("Cannot compile {:?}", x),
+        }
+        false
+    }
+}
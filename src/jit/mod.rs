@@ -0,0 +1,251 @@
+//! An optional tier that compiles hot functions to native code with Cranelift.
+//!
+//! Functions are interpreted until they have been called `threshold` times through
+//! `FunctionCall(FunctionSource::Name(..))`. They are then specialized for the types of
+//! their arguments and compiled together with everything they call. Only functions that
+//! use fixed width integers, `F32`/`F64` decimals, locals, `Compare`/`Goto` and calls are
+//! compiled, anything else keeps running in the interpreter.
+//!
+//! Compiled code never touches the `Core` while it runs: the operand stack and the locals
+//! live in native registers and only the results and the flags are written back when the
+//! function returns. That makes deoptimization simple: whenever the native code hits
+//! something it can't handle (an overflow, a division by zero, a bad shift) it bails out
+//! and the call is rerun from the start by the interpreter, which then produces the exact
+//! same result or fault it always would have.
+
+pub mod analysis;
+pub mod codegen;
+
+use std::fmt::{Debug, Display};
+use fxhash::FxHashMap;
+use crate::program::module::Module;
+use crate::value::decimal::Decimal;
+use crate::value::function::Function;
+use crate::value::integer::Integer;
+use crate::value::Value;
+
+/// The number of calls after which a function gets compiled.
+pub const DEFAULT_THRESHOLD: u32 = 1000;
+
+/// The number of bytes used to pass the core flags in and out of compiled code.
+pub const FLAG_BYTES: usize = 5;
+pub const FLAG_COMPARISON: usize = 0;
+pub const FLAG_NEGATIVE: usize = 1;
+pub const FLAG_ZERO: usize = 2;
+pub const FLAG_OVERFLOW: usize = 3;
+pub const FLAG_CARRY: usize = 4;
+
+/// The types a compiled function can work with.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum JitType {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+impl JitType {
+    pub fn of_integer(integer: &Integer) -> Option<JitType> {
+        match integer {
+            Integer::U8(_) => Some(JitType::U8),
+            Integer::U16(_) => Some(JitType::U16),
+            Integer::U32(_) => Some(JitType::U32),
+            Integer::U64(_) => Some(JitType::U64),
+            Integer::I8(_) => Some(JitType::I8),
+            Integer::I16(_) => Some(JitType::I16),
+            Integer::I32(_) => Some(JitType::I32),
+            Integer::I64(_) => Some(JitType::I64),
+            Integer::Natural(_) | Integer::Integer(_) => None,
+        }
+    }
+
+    pub fn of_value(value: &Value) -> Option<JitType> {
+        match value {
+            Value::Integer(integer) => JitType::of_integer(integer),
+            Value::Decimal(Decimal::F32(_)) => Some(JitType::F32),
+            Value::Decimal(Decimal::F64(_)) => Some(JitType::F64),
+            _ => None,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        !self.is_float()
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, JitType::F32 | JitType::F64)
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, JitType::I8 | JitType::I16 | JitType::I32 | JitType::I64)
+    }
+
+    pub fn bits(&self) -> u32 {
+        match self {
+            JitType::U8 | JitType::I8 => 8,
+            JitType::U16 | JitType::I16 => 16,
+            JitType::U32 | JitType::I32 | JitType::F32 => 32,
+            JitType::U64 | JitType::I64 | JitType::F64 => 64,
+        }
+    }
+
+    /// Packs a value into the 64 bit slot used to pass values to and from compiled code.
+    pub fn to_bits(value: &Value) -> u64 {
+        match value {
+            Value::Integer(Integer::U8(value)) => *value as u64,
+            Value::Integer(Integer::U16(value)) => *value as u64,
+            Value::Integer(Integer::U32(value)) => *value as u64,
+            Value::Integer(Integer::U64(value)) => *value,
+            Value::Integer(Integer::I8(value)) => *value as u64,
+            Value::Integer(Integer::I16(value)) => *value as u64,
+            Value::Integer(Integer::I32(value)) => *value as u64,
+            Value::Integer(Integer::I64(value)) => *value as u64,
+            Value::Decimal(Decimal::F32(value)) => value.to_bits() as u64,
+            Value::Decimal(Decimal::F64(value)) => value.to_bits(),
+            x => panic!("Cannot pass {:?} to compiled code", x),
+        }
+    }
+
+    /// Unpacks a value from a 64 bit slot.
+    pub fn from_bits(&self, bits: u64) -> Value {
+        match self {
+            JitType::U8 => Value::Integer(Integer::U8(bits as u8)),
+            JitType::U16 => Value::Integer(Integer::U16(bits as u16)),
+            JitType::U32 => Value::Integer(Integer::U32(bits as u32)),
+            JitType::U64 => Value::Integer(Integer::U64(bits)),
+            JitType::I8 => Value::Integer(Integer::I8(bits as i8)),
+            JitType::I16 => Value::Integer(Integer::I16(bits as i16)),
+            JitType::I32 => Value::Integer(Integer::I32(bits as i32)),
+            JitType::I64 => Value::Integer(Integer::I64(bits as i64)),
+            JitType::F32 => Value::Decimal(Decimal::F32(f32::from_bits(bits as u32))),
+            JitType::F64 => Value::Decimal(Decimal::F64(f64::from_bits(bits))),
+        }
+    }
+}
+
+impl Display for JitType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JitType::U8 => write!(f, "u8"),
+            JitType::U16 => write!(f, "u16"),
+            JitType::U32 => write!(f, "u32"),
+            JitType::U64 => write!(f, "u64"),
+            JitType::I8 => write!(f, "i8"),
+            JitType::I16 => write!(f, "i16"),
+            JitType::I32 => write!(f, "i32"),
+            JitType::I64 => write!(f, "i64"),
+            JitType::F32 => write!(f, "f32"),
+            JitType::F64 => write!(f, "f64"),
+        }
+    }
+}
+
+impl Debug for JitType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+/// Identifies a function specialized for a list of argument types.
+/// Functions are identified by their address inside the `Module`, which never moves
+/// while the module is shared with the cores.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FunctionKey {
+    address: usize,
+    arguments: Box<[JitType]>,
+}
+
+impl FunctionKey {
+    pub fn new(function: &Function, arguments: &[JitType]) -> FunctionKey {
+        FunctionKey {
+            address: function as *const Function as usize,
+            arguments: arguments.into(),
+        }
+    }
+}
+
+/// The signature of every compiled function.
+/// Takes the arguments, a buffer for the results and the flags (read and written).
+/// Returns 0 on success and anything else when the call has to be rerun in the interpreter.
+pub type NativeFunction = unsafe extern "C" fn(*const u64, *mut u64, *mut u8) -> u32;
+
+pub struct CompiledFunction {
+    pub id: cranelift_module::FuncId,
+    pub results: Box<[JitType]>,
+    pointer: NativeFunction,
+}
+
+pub struct Jit {
+    threshold: u32,
+    /// The module the counters and compiled code belong to.
+    module_address: usize,
+    counters: FxHashMap<usize, u32>,
+    /// `None` marks functions that can't be compiled so we don't try again.
+    compiled: FxHashMap<FunctionKey, Option<CompiledFunction>>,
+    backend: codegen::Backend,
+}
+
+impl Jit {
+    pub fn new(threshold: u32) -> Jit {
+        Jit {
+            threshold,
+            module_address: 0,
+            counters: FxHashMap::default(),
+            compiled: FxHashMap::default(),
+            backend: codegen::Backend::new(),
+        }
+    }
+
+    pub fn set_threshold(&mut self, threshold: u32) {
+        self.threshold = threshold;
+    }
+
+    /// Counts a call to the function and runs it natively if it is hot and can be compiled.
+    /// Returns `None` if the interpreter has to run the call instead.
+    pub fn call(&mut self, module: &Module, function: &Function, arguments: &[Value], flags: &mut [u8; FLAG_BYTES]) -> Option<Vec<Value>> {
+        let module_address = module as *const Module as usize;
+        if self.module_address != module_address {
+            self.module_address = module_address;
+            self.counters.clear();
+            self.compiled.clear();
+        }
+
+        let counter = self.counters.entry(function as *const Function as usize).or_insert(0);
+        if *counter < self.threshold {
+            *counter += 1;
+            return None;
+        }
+
+        let types = arguments.iter().map(JitType::of_value).collect::<Option<Vec<_>>>()?;
+        let key = FunctionKey::new(function, &types);
+        if !self.compiled.contains_key(&key) {
+            self.backend.compile(module, function, &types, &mut self.compiled);
+        }
+        let compiled = self.compiled.get(&key)?.as_ref()?;
+        let pointer = compiled.pointer;
+
+        let arguments = arguments.iter().map(JitType::to_bits).collect::<Vec<_>>();
+        let mut results = vec![0u64; compiled.results.len()];
+        let mut new_flags = *flags;
+        // Safety: the pointer was produced by Cranelift for a function with the `NativeFunction`
+        // signature and the buffers have the sizes the function was compiled for.
+        let status = unsafe { pointer(arguments.as_ptr(), results.as_mut_ptr(), new_flags.as_mut_ptr()) };
+        if status != 0 {
+            return None;
+        }
+        *flags = new_flags;
+        Some(compiled.results.iter().zip(results).map(|(typ, bits)| typ.from_bits(bits)).collect())
+    }
+}
+
+impl Default for Jit {
+    fn default() -> Self {
+        Jit::new(DEFAULT_THRESHOLD)
+    }
+}
@@ -0,0 +1,14 @@
+pub mod value;
+pub mod instruction;
+pub mod stack;
+pub mod program;
+pub mod machine;
+pub mod optimizer;
+pub mod analysis;
+pub mod stdlib;
+#[cfg(feature = "jit")]
+pub mod jit;
+
+pub use machine::vm::Vm;
+pub use machine::Fault;
+pub use value::Value;
@@ -1,17 +1,25 @@
 use std::alloc::Layout;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::sync::Arc;
-use malachite::num::arithmetic::traits::Pow;
-use crate::instruction::{ComparisonType, Condition, FunctionSource, Instruction, JumpTarget, RealInstruction};
-use crate::machine::{Fault, InstructionResult};
+use malachite::Natural;
+#[cfg(feature = "jit")]
+use crate::jit::{Jit, FLAG_BYTES, FLAG_CARRY, FLAG_COMPARISON, FLAG_NEGATIVE, FLAG_OVERFLOW, FLAG_ZERO};
+use crate::instruction::{BinaryOperation, ComparisonType, Condition, FunctionSource, Instruction, JumpTarget, RealInstruction};
+use crate::machine::{call_closure, Fault, InstructionResult};
 use crate::machine::environment::Environment;
+use crate::program::FunctionPath;
 use crate::program::module::Module;
 use crate::stack::{Stack, StackChunk};
 use crate::value::{Value, ValueType};
-use crate::value::decimal::DecimalType;
-use crate::value::integer::IntegerType;
+use crate::value::function::Function;
+use crate::value::decimal::{Decimal, DecimalType, Rounding};
+use crate::value::integer::{DivisionMode, Integer, IntegerType, OverflowMode};
+use crate::value::map::Map;
 use crate::value::tuple::Tuple;
 use crate::value::vector::{Vector, VectorType};
+use crate::value::vector_math::{self, VectorOperation, VectorReduction};
+use crate::value::array::{self, Array};
 
 
 macro_rules! basic_alu_op_int {
@@ -21,7 +29,8 @@ macro_rules! basic_alu_op_int {
             let left = self.stack.pop().get_boxed_value();
             match (left, right) {
                 (Value::Integer(left), Value::Integer(right)) => {
-                    let result = left $op right;
+                    let (left, right) = promote_integers(left, right, self.integer_promotion)?;
+                    let result = self.narrow(left $op right);
 
                     if result.is_zero() {
                         self.flags.zero = true;
@@ -35,6 +44,9 @@ macro_rules! basic_alu_op_int {
                         self.flags.negative = false;
                     }
 
+                    self.flags.carry = false;
+                    self.flags.overflow = false;
+
                     let chunk = result.into_chunk();
 
                     self.stack.push(chunk);
@@ -48,6 +60,92 @@ macro_rules! basic_alu_op_int {
     };
 }
 
+/// Like `basic_alu_op_int!` for the operations that can overflow.
+/// `$mode` decides what happens when they do, and the overflow flag records whether they did.
+/// The carry flag is set from `$carry`, or mirrors the overflow flag when there is none.
+/// `$operands` prepares the operands, by default converting them to a common type.
+macro_rules! overflow_alu_op_int {
+    ($fun_name:ident, $method:ident, $mode:ident) => {
+        overflow_alu_op_int!($fun_name, $method, $mode, |_, _| None);
+    };
+    ($fun_name:ident, $method:ident, $mode:ident, $carry:expr) => {
+        overflow_alu_op_int!($fun_name, $method, $mode, $carry, promote_integers);
+    };
+    ($fun_name:ident, $method:ident, $mode:ident, $carry:expr, $operands:ident) => {
+        fn $fun_name(&mut self) -> Result<(),Fault> {
+            let right = self.stack.pop().get_boxed_value();
+            let left = self.stack.pop().get_boxed_value();
+            match (left, right) {
+                (Value::Integer(left), Value::Integer(right)) => {
+                    let (left, right) = $operands(left, right, self.integer_promotion)?;
+                    let carry: Option<bool> = $carry(&left, &right);
+                    let promoting = self.arithmetic_mode == ArithmeticMode::Promoting;
+                    let (result, overflowed) = if promoting && matches!(OverflowMode::$mode, OverflowMode::Checked) {
+                        promoting_operation(left, right, Integer::$method)?
+                    } else {
+                        left.$method(right, OverflowMode::$mode)?
+                    };
+
+                    self.flags.zero = result.is_zero();
+                    self.flags.negative = result.is_negative();
+                    self.flags.carry = carry.unwrap_or(overflowed);
+                    self.flags.overflow = overflowed;
+
+                    self.stack.push(result.into_chunk());
+                }
+                _ => return Err(Fault::NotAnInteger),
+            }
+            Ok(())
+        }
+    };
+}
+
+/// Converts the operands of a binary integer operation to their common type, see [`IntegerType::promote`].
+/// Faults with `TypeMismatch` instead if their types differ and promotion is off for the running function's module.
+fn promote_integers(left: Integer, right: Integer, integer_promotion: bool) -> Result<(Integer, Integer), Fault> {
+    if integer_promotion {
+        Ok(left.promote(right))
+    } else if left.get_type() == right.get_type() {
+        Ok((left, right))
+    } else {
+        Err(Fault::TypeMismatch)
+    }
+}
+
+/// Runs a checked integer operation in [`ArithmeticMode::Promoting`].
+///
+/// Fixed width operands get the fast path first. If the result doesn't fit, or an operand is already
+/// arbitrary precision, the operation is redone on `Integer`s. The exact result becomes a `Natural` if
+/// both operands were unsigned and it isn't negative, and is narrowed back to a machine word if it fits.
+fn promoting_operation<F>(left: Integer, right: Integer, operation: F) -> Result<(Integer, bool), Fault>
+where
+    F: Fn(Integer, Integer, OverflowMode) -> Result<(Integer, bool), Fault>,
+{
+    let (left_type, right_type) = (left.get_type(), right.get_type());
+    if left_type.bits().is_some() && right_type.bits().is_some() {
+        match operation(left.clone(), right.clone(), OverflowMode::Checked) {
+            Err(Fault::Overflow | Fault::Underflow) => {}
+            result => return result,
+        }
+    }
+    let (result, _) = operation(Integer::Integer(left.to_big_integer()), Integer::Integer(right.to_big_integer()), OverflowMode::Checked)?;
+    Ok((widened(result, !left_type.is_signed() && !right_type.is_signed()), false))
+}
+
+/// Narrows a result computed on `Integer`s, which is a `Natural` if it came from unsigned operands.
+fn widened(result: Integer, unsigned: bool) -> Integer {
+    let result = match result {
+        Integer::Integer(value) if value >= 0 && unsigned => Integer::Natural(Natural::try_from(value).unwrap()),
+        result => result,
+    };
+    result.narrow()
+}
+
+/// Exponents and shift amounts don't need to have the type of the value they apply to.
+fn independent_integers(left: Integer, right: Integer, _: bool) -> Result<(Integer, Integer), Fault> {
+    Ok((left, right))
+}
+
 macro_rules! basic_alu_op_decimal {
     ($fun_name:ident, $op:tt) => {
         fn $fun_name(&mut self) -> Result<(),Fault> {
@@ -94,18 +192,78 @@ pub enum Comparison {
     GreaterThanOrEqual,
 }
 
+impl Comparison {
+    /// The inverse of `comparison as u8`, used to pass the flag through native code.
+    pub fn from_code(code: u8) -> Comparison {
+        match code {
+            1 => Comparison::Equal,
+            2 => Comparison::NotEqual,
+            3 => Comparison::LessThan,
+            4 => Comparison::LessThanOrEqual,
+            5 => Comparison::GreaterThan,
+            6 => Comparison::GreaterThanOrEqual,
+            _ => Comparison::None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct CoreFlags {
     comparison: Comparison,
     negative: bool,
     zero: bool,
+    /// Set when an integer operation carried out of (or borrowed into) the top bit of a fixed width type,
+    /// or shifted out a one bit.
+    carry: bool,
+    /// Set when an integer add, subtract, multiply, power or shift left wrapped or saturated.
+    overflow: bool,
 }
 
+#[cfg(feature = "jit")]
+impl CoreFlags {
+    fn to_bytes(self) -> [u8; FLAG_BYTES] {
+        let mut bytes = [0; FLAG_BYTES];
+        bytes[FLAG_COMPARISON] = self.comparison as u8;
+        bytes[FLAG_NEGATIVE] = self.negative as u8;
+        bytes[FLAG_ZERO] = self.zero as u8;
+        bytes[FLAG_OVERFLOW] = self.overflow as u8;
+        bytes[FLAG_CARRY] = self.carry as u8;
+        bytes
+    }
+
+    fn from_bytes(bytes: [u8; FLAG_BYTES]) -> CoreFlags {
+        CoreFlags {
+            comparison: Comparison::from_code(bytes[FLAG_COMPARISON]),
+            negative: bytes[FLAG_NEGATIVE] != 0,
+            zero: bytes[FLAG_ZERO] != 0,
+            overflow: bytes[FLAG_OVERFLOW] != 0,
+            carry: bytes[FLAG_CARRY] != 0,
+        }
+    }
+}
 
 
+
+/// How the checked integer instructions deal with results that don't fit in their type.
+/// The explicit wrapping and saturating instructions behave the same in every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArithmeticMode {
+    /// Fault with `Overflow` or `Underflow`.
+    #[default]
+    Fixed,
+    /// Promote the result to a `Natural` or `Integer`, like integers in Python.
+    /// Arbitrary precision results that fit in a `U64` or `I64` are narrowed back to that type.
+    Promoting,
+}
+
 pub struct Core {
     flags: CoreFlags,
     stack: Stack,
+    arithmetic_mode: ArithmeticMode,
+    /// The integer promotion setting of the module the running function belongs to.
+    integer_promotion: bool,
+    #[cfg(feature = "jit")]
+    jit: Jit,
 }
 
 
@@ -116,8 +274,89 @@ impl Core {
                 comparison: Comparison::None,
                 negative: false,
                 zero: false,
+                carry: false,
+                overflow: false,
             },
             stack: Stack::new(),
+            arithmetic_mode: ArithmeticMode::Fixed,
+            integer_promotion: true,
+            #[cfg(feature = "jit")]
+            jit: Jit::default(),
+        }
+    }
+
+    pub fn arithmetic_mode(&self) -> ArithmeticMode {
+        self.arithmetic_mode
+    }
+
+    pub fn set_arithmetic_mode(&mut self, mode: ArithmeticMode) {
+        self.arithmetic_mode = mode;
+    }
+
+    pub(crate) fn integer_promotion(&self) -> bool {
+        self.integer_promotion
+    }
+
+    /// Switches to the integer promotion setting of the module whose function is about to run.
+    pub(crate) fn set_integer_promotion(&mut self, enabled: bool) {
+        self.integer_promotion = enabled;
+    }
+
+    /// Narrows arbitrary precision results back to machine words in [`ArithmeticMode::Promoting`].
+    fn narrow(&self, result: Integer) -> Integer {
+        match self.arithmetic_mode {
+            ArithmeticMode::Fixed => result,
+            ArithmeticMode::Promoting => result.narrow(),
+        }
+    }
+
+    /// Pushes a value on top of the stack.
+    pub fn push_value(&mut self, value: Value) {
+        self.stack.push(value.into_chunk());
+    }
+
+    /// Pops the value on top of the stack, or returns `None` if the stack is empty.
+    pub fn pop_value(&mut self) -> Option<Value> {
+        if self.stack.is_empty() {
+            return None;
+        }
+        Some(self.stack.pop().get_boxed_value())
+    }
+
+    /// The number of values on the stack.
+    pub fn stack_len(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Sets the number of calls after which a function is compiled to native code.
+    #[cfg(feature = "jit")]
+    pub fn set_jit_threshold(&mut self, threshold: u32) {
+        self.jit.set_threshold(threshold);
+    }
+
+    /// Runs a call to a named function with native code if the function is hot and can be compiled.
+    /// Returns false if the interpreter has to run the call instead.
+    #[cfg(feature = "jit")]
+    pub fn call_compiled(&mut self, function: &Function, environment: &Environment, module: &Module) -> bool {
+        if !function.get_environment().is_empty() {
+            return false;
+        }
+        let arguments = function.argument_names.iter()
+            .map(|name| environment.get(name).cloned())
+            .collect::<Option<Vec<_>>>();
+        let Some(arguments) = arguments else {
+            return false;
+        };
+        let mut flags = self.flags.to_bytes();
+        match self.jit.call(module, function, &arguments, &mut flags) {
+            None => false,
+            Some(results) => {
+                for result in results {
+                    self.stack.push(result.into_chunk());
+                }
+                self.flags = CoreFlags::from_bytes(flags);
+                true
+            }
         }
     }
 
@@ -162,8 +401,8 @@ impl Core {
             IntegerAdd => self.integer_add()?,
             IntegerSubtract => self.integer_subtract()?,
             IntegerMultiply => self.integer_multiply()?,
-            IntegerDivide => self.integer_divide()?,
-            IntegerModulo => self.integer_modulo()?,
+            IntegerDivide => self.integer_divide(DivisionMode::Truncate)?,
+            IntegerModulo => self.integer_modulo(DivisionMode::Truncate)?,
             IntegerPower => self.integer_power()?,
             IntegerNegate => self.integer_negate()?,
             IntegerBitwiseAnd => self.integer_bitwise_and()?,
@@ -172,6 +411,34 @@ impl Core {
             IntegerBitwiseNot => self.integer_bitwise_not()?,
             IntegerShiftLeft => self.integer_shift_left()?,
             IntegerShiftRight => self.integer_shift_right()?,
+            IntegerWrappingAdd => self.integer_wrapping_add()?,
+            IntegerWrappingSubtract => self.integer_wrapping_subtract()?,
+            IntegerWrappingMultiply => self.integer_wrapping_multiply()?,
+            IntegerWrappingPower => self.integer_wrapping_power()?,
+            IntegerWrappingShiftLeft => self.integer_wrapping_shift_left()?,
+            IntegerSaturatingAdd => self.integer_saturating_add()?,
+            IntegerSaturatingSubtract => self.integer_saturating_subtract()?,
+            IntegerSaturatingMultiply => self.integer_saturating_multiply()?,
+            IntegerSaturatingPower => self.integer_saturating_power()?,
+            IntegerSaturatingShiftLeft => self.integer_saturating_shift_left()?,
+            IntegerPopCount => self.integer_count(Integer::pop_count)?,
+            IntegerBitLength => self.integer_count(|integer| Ok(integer.bit_length()))?,
+            IntegerTestBit => self.integer_test_bit()?,
+            IntegerSetBit => self.integer_assign_bit(true)?,
+            IntegerClearBit => self.integer_assign_bit(false)?,
+            IntegerFloorDivide => self.integer_divide(DivisionMode::Floor)?,
+            IntegerFloorModulo => self.integer_modulo(DivisionMode::Floor)?,
+            IntegerEuclideanDivide => self.integer_divide(DivisionMode::Euclidean)?,
+            IntegerEuclideanModulo => self.integer_modulo(DivisionMode::Euclidean)?,
+            IntegerDivMod(mode) => self.integer_divmod(*mode)?,
+            IntegerGcd => self.integer_binary_function(Integer::gcd)?,
+            IntegerLcm => self.integer_binary_function(Integer::lcm)?,
+            IntegerFactorial => self.integer_unary_function(Integer::factorial)?,
+            IntegerBinomial => self.integer_binary_function(Integer::binomial)?,
+            IntegerSquareRoot => self.integer_unary_function(Integer::isqrt)?,
+            IntegerPowMod => self.integer_pow_mod()?,
+            IntegerModInverse => self.integer_mod_inverse()?,
+            IntegerIsPrime => self.integer_is_prime()?,
             Compare(comparison_type) => self.compare(comparison_type)?,
             Goto(target, condition) => return self.goto(target, condition, program_counter),
             Return(condition) => return self.return_instruction(condition, program_counter),
@@ -187,6 +454,10 @@ impl Core {
             DecimalModulo => self.decimal_modulo()?,
             DecimalPower => self.decimal_power()?,
             DecimalNegate => self.decimal_negate()?,
+            DecimalRound(rounding) => self.decimal_round(*rounding)?,
+            DecimalRoundTo(rounding) => self.decimal_round_to(*rounding)?,
+            DecimalConvert(typ) => self.decimal_convert(typ)?,
+            DecimalDivideTo(rounding) => self.decimal_divide_to(*rounding)?,
             BooleanNew(boolean) => {
                 let chunk = boolean.into_chunk();
                 self.stack.push(chunk);
@@ -198,9 +469,76 @@ impl Core {
             VectorGet => self.vector_get()?,
             VectorSet => self.vector_set()?,
             VectorLength => self.vector_length()?,
+            VectorPush => self.vector_push()?,
+            VectorPop => self.vector_pop()?,
+            VectorInsert => self.vector_insert()?,
+            VectorRemove => self.vector_remove()?,
+            VectorTruncate => self.vector_truncate()?,
+            VectorResize => self.vector_resize()?,
+            VectorSlice => self.vector_slice()?,
+            VectorConcat => self.vector_concat()?,
+            VectorCopy => self.vector_copy()?,
+            VectorArithmetic(operation) => self.vector_arithmetic(*operation)?,
+            VectorDot => self.vector_dot()?,
+            VectorReduce(reduction) => self.vector_reduce(*reduction)?,
+            VectorSort => self.vector_sort()?,
+            VectorSortBy => self.vector_sort_by(module)?,
+            VectorBinarySearch => self.vector_binary_search()?,
+            VectorReverse => self.vector_update(Vector::reverse)?,
+            VectorDedup => self.vector_update(Vector::dedup)?,
+            VectorMap => self.vector_map(module)?,
+            VectorFilter => self.vector_filter(module)?,
+            VectorFold => self.vector_fold(module)?,
+            ArrayNew(typ) => self.array_new(typ)?,
+            ArrayFromVector => self.array_from_vector()?,
+            ArrayGet => self.array_get()?,
+            ArraySet => self.array_set()?,
+            ArrayShape => self.array_shape()?,
+            ArrayReshape => self.array_reshape()?,
+            ArrayTranspose => self.array_transpose()?,
+            ArraySlice => self.array_slice()?,
+            ArrayMatrixMultiply => self.array_matrix_multiply()?,
+            ArrayToVector => self.array_to_vector()?,
             TupleNew => self.tuple_new()?,
             TupleGet => self.tuple_get()?,
+            TupleSet => self.tuple_set()?,
+            TupleLength => self.tuple_length()?,
+            TupleUnpack => self.tuple_unpack()?,
+            TupleFromVector => {
+                let tuple = Tuple::from_vector(&self.pop_vector()?)?;
+                self.stack.push(tuple.into_chunk());
+            }
+            TupleToVector => {
+                let vector = self.pop_tuple()?.to_vector();
+                self.stack.push(vector.into_chunk());
+            }
+            MapNew => self.stack.push(Map::new().into_chunk()),
+            MapInsert => self.map_insert()?,
+            MapGet => self.map_get()?,
+            MapRemove => self.map_remove()?,
+            MapContains => self.map_query(|map, key| Ok(Value::Boolean(map.contains(&key))))?,
+            MapLength => self.map_inspect(|map| map.length().get_value())?,
+            MapKeys => self.map_inspect(|map| Value::Tuple(map.keys()))?,
+            MapValues => self.map_inspect(|map| Value::Tuple(map.values()))?,
+            Cast(typ) => {
+                let value = self.stack.pop().get_boxed_value().cast(typ.clone())?;
+                self.stack.push(value.into_chunk());
+            }
             ClosureNew(source) => self.closure_new(source, module, environment)?,
+            HostCall(path) => self.host_call(path, module, environment)?,
+            IncrementLocal(name, amount) => self.increment_local(name, amount, environment, false)?,
+            DecrementLocal(name, amount) => self.increment_local(name, amount, environment, true)?,
+            CompareGoto(comparison_type, target, condition) => {
+                self.compare(comparison_type)?;
+                return self.goto(target, condition, program_counter);
+            }
+            LookupPairOperation(left, right, operation) => {
+                let left = environment.get(left).unwrap().clone();
+                self.stack.push(left.into_chunk());
+                let right = environment.get(right).unwrap().clone();
+                self.stack.push(right.into_chunk());
+                self.binary_operation(operation)?;
+            }
 
             x => panic!("Unimplemented instruction: {:?}", x),
         }
@@ -210,20 +548,86 @@ impl Core {
         Ok(InstructionResult::Continue)
     }
 
-    basic_alu_op_int!(integer_add, +);
-    basic_alu_op_int!(integer_subtract, -);
-    basic_alu_op_int!(integer_multiply, *);
+    fn binary_operation(&mut self, operation: &BinaryOperation) -> Result<(),Fault> {
+        match operation {
+            BinaryOperation::IntegerAdd => self.integer_add(),
+            BinaryOperation::IntegerSubtract => self.integer_subtract(),
+            BinaryOperation::IntegerMultiply => self.integer_multiply(),
+            BinaryOperation::IntegerDivide => self.integer_divide(DivisionMode::Truncate),
+            BinaryOperation::IntegerModulo => self.integer_modulo(DivisionMode::Truncate),
+            BinaryOperation::IntegerBitwiseAnd => self.integer_bitwise_and(),
+            BinaryOperation::IntegerBitwiseOr => self.integer_bitwise_or(),
+            BinaryOperation::IntegerBitwiseXor => self.integer_bitwise_xor(),
+            BinaryOperation::DecimalAdd => self.decimal_add(),
+            BinaryOperation::DecimalSubtract => self.decimal_subtract(),
+            BinaryOperation::DecimalMultiply => self.decimal_multiply(),
+            BinaryOperation::DecimalDivide => self.decimal_divide(),
+        }
+    }
+
+    /// Adds (or subtracts) `amount` to the integer stored under `name` without touching the stack.
+    fn increment_local(&mut self, name: &str, amount: &Integer, environment: &mut Environment, subtract: bool) -> Result<(),Fault> {
+        let value = environment.get_mut(name).unwrap();
+        match value {
+            Value::Integer(integer) => {
+                let (left, amount) = promote_integers(integer.clone(), amount.clone(), self.integer_promotion)?;
+                let (carry, operation): (bool, fn(Integer, Integer, OverflowMode) -> _) = if subtract {
+                    (left.subtract_borrow(&amount), Integer::overflowing_sub)
+                } else {
+                    (left.add_carry(&amount), Integer::overflowing_add)
+                };
+                let (result, overflowed) = match self.arithmetic_mode {
+                    ArithmeticMode::Fixed => operation(left, amount, OverflowMode::Checked)?,
+                    ArithmeticMode::Promoting => promoting_operation(left, amount, operation)?,
+                };
+
+                self.flags.zero = result.is_zero();
+                self.flags.negative = result.is_negative();
+                self.flags.carry = carry;
+                self.flags.overflow = overflowed;
+
+                *integer = result;
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
+    overflow_alu_op_int!(integer_add, overflowing_add, Checked, |left: &Integer, right| Some(left.add_carry(right)));
+    overflow_alu_op_int!(integer_subtract, overflowing_sub, Checked, |left: &Integer, right| Some(left.subtract_borrow(right)));
+    overflow_alu_op_int!(integer_multiply, overflowing_mul, Checked);
+    overflow_alu_op_int!(integer_power, overflowing_pow, Checked, |_, _| None, independent_integers);
+    overflow_alu_op_int!(integer_shift_left, overflowing_shl, Checked, |left: &Integer, right| Some(left.shift_left_carry(right)), independent_integers);
+    overflow_alu_op_int!(integer_wrapping_add, overflowing_add, Wrapping, |left: &Integer, right| Some(left.add_carry(right)));
+    overflow_alu_op_int!(integer_wrapping_subtract, overflowing_sub, Wrapping, |left: &Integer, right| Some(left.subtract_borrow(right)));
+    overflow_alu_op_int!(integer_wrapping_multiply, overflowing_mul, Wrapping);
+    overflow_alu_op_int!(integer_wrapping_power, overflowing_pow, Wrapping, |_, _| None, independent_integers);
+    overflow_alu_op_int!(integer_wrapping_shift_left, overflowing_shl, Wrapping, |left: &Integer, right| Some(left.shift_left_carry(right)), independent_integers);
+    overflow_alu_op_int!(integer_saturating_add, overflowing_add, Saturating, |left: &Integer, right| Some(left.add_carry(right)));
+    overflow_alu_op_int!(integer_saturating_subtract, overflowing_sub, Saturating, |left: &Integer, right| Some(left.subtract_borrow(right)));
+    overflow_alu_op_int!(integer_saturating_multiply, overflowing_mul, Saturating);
+    overflow_alu_op_int!(integer_saturating_power, overflowing_pow, Saturating, |_, _| None, independent_integers);
+    overflow_alu_op_int!(integer_saturating_shift_left, overflowing_shl, Saturating, |left: &Integer, right| Some(left.shift_left_carry(right)), independent_integers);
+
+    /// Divides two integers of the promoted type. In promoting mode a quotient that doesn't fit
+    /// in a fixed width type is computed again as a bignum.
+    fn divide_integers(&self, left: Integer, right: Integer, mode: DivisionMode) -> Result<(Integer, Integer), Fault> {
+        let (left, right) = promote_integers(left, right, self.integer_promotion)?;
+        let (quotient, remainder) = match left.clone().divide_remainder(right.clone(), mode) {
+            Err(Fault::Overflow) if self.arithmetic_mode == ArithmeticMode::Promoting => {
+                Integer::Integer(left.to_big_integer()).divide_remainder(Integer::Integer(right.to_big_integer()), mode)?
+            }
+            result => result?,
+        };
+        Ok((self.narrow(quotient), self.narrow(remainder)))
+    }
 
-    fn integer_divide(&mut self) -> Result<(),Fault> {
+    fn integer_divide(&mut self, mode: DivisionMode) -> Result<(),Fault> {
         let right = self.stack.pop().get_boxed_value();
         let left = self.stack.pop().get_boxed_value();
         match (left, right) {
             (Value::Integer(left), Value::Integer(right)) => {
-                if right.is_zero() {
-                    return Err(Fault::DivisionByZero);
-                }
-
-                let result = left / right;
+                let (result, _) = self.divide_integers(left, right, mode)?;
 
                 if result.is_zero() {
                     self.flags.zero = true;
@@ -237,6 +641,9 @@ impl Core {
                     self.flags.negative = false;
                 }
 
+                self.flags.carry = false;
+                self.flags.overflow = false;
+
                 let chunk = result.into_chunk();
 
                 self.stack.push(chunk);
@@ -246,16 +653,13 @@ impl Core {
         Ok(())
     }
 
-    fn integer_modulo(&mut self) -> Result<(),Fault> {
+    fn integer_modulo(&mut self, mode: DivisionMode) -> Result<(),Fault> {
         let right = self.stack.pop().get_boxed_value();
         let left = self.stack.pop().get_boxed_value();
         match (left, right) {
             (Value::Integer(left), Value::Integer(right)) => {
-                if right.is_zero() {
-                    return Err(Fault::DivisionByZero);
-                }
-
-                let result = left % right;
+                let (left, right) = promote_integers(left, right, self.integer_promotion)?;
+                let result = self.narrow(left.remainder(right, mode)?);
 
                 if result.is_zero() {
                     self.flags.zero = true;
@@ -269,6 +673,9 @@ impl Core {
                     self.flags.negative = false;
                 }
 
+                self.flags.carry = false;
+                self.flags.overflow = false;
+
                 let chunk = result.into_chunk();
 
                 self.stack.push(chunk);
@@ -278,35 +685,142 @@ impl Core {
         Ok(())
     }
 
+    /// The flags describe the remainder, which ends up on top, so `zero` tells whether the division was exact.
+    fn integer_divmod(&mut self, mode: DivisionMode) -> Result<(),Fault> {
+        let right = self.stack.pop().get_boxed_value();
+        let left = self.stack.pop().get_boxed_value();
+        match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => {
+                let (quotient, remainder) = self.divide_integers(left, right, mode)?;
+
+                self.flags.zero = remainder.is_zero();
+                self.flags.negative = remainder.is_negative();
+                self.flags.carry = false;
+                self.flags.overflow = false;
+
+                self.stack.push(quotient.into_chunk());
+                self.stack.push(remainder.into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
+
+    fn integer_binary_function(&mut self, function: fn(Integer, &Integer) -> Result<Integer, Fault>) -> Result<(),Fault> {
+        let right = self.stack.pop().get_boxed_value();
+        let left = self.stack.pop().get_boxed_value();
+        match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => {
+                let (left, right) = promote_integers(left, right, self.integer_promotion)?;
+                let result = if self.arithmetic_mode == ArithmeticMode::Promoting {
+                    promoting_operation(left, right, |left, right, _| Ok((function(left, &right)?, false)))?.0
+                } else {
+                    function(left, &right)?
+                };
+
+                self.flags.zero = result.is_zero();
+                self.flags.negative = result.is_negative();
+                self.flags.carry = false;
+                self.flags.overflow = false;
+
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
+    fn integer_unary_function(&mut self, function: fn(Integer) -> Result<Integer, Fault>) -> Result<(),Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        match value {
+            Value::Integer(value) => {
+                let typ = value.get_type();
+                let result = match function(value.clone()) {
+                    Err(Fault::Overflow) if self.arithmetic_mode == ArithmeticMode::Promoting && typ.bits().is_some() => {
+                        widened(function(Integer::Integer(value.to_big_integer()))?, !typ.is_signed())
+                    }
+                    result => self.narrow(result?),
+                };
+
+                self.flags.zero = result.is_zero();
+                self.flags.negative = result.is_negative();
+                self.flags.carry = false;
+                self.flags.overflow = false;
+
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
+    /// The result has the type of the modulus and always fits, so no promotion is needed.
+    fn integer_pow_mod(&mut self) -> Result<(),Fault> {
+        let modulus = self.stack.pop().get_boxed_value();
+        let exponent = self.stack.pop().get_boxed_value();
+        let base = self.stack.pop().get_boxed_value();
+        match (base, exponent, modulus) {
+            (Value::Integer(base), Value::Integer(exponent), Value::Integer(modulus)) => {
+                let result = base.pow_mod(&exponent, &modulus)?;
+
+                self.flags.zero = result.is_zero();
+                self.flags.negative = false;
+                self.flags.carry = false;
+                self.flags.overflow = false;
+
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
+    fn integer_mod_inverse(&mut self) -> Result<(),Fault> {
+        let modulus = self.stack.pop().get_boxed_value();
+        let value = self.stack.pop().get_boxed_value();
+        match (value, modulus) {
+            (Value::Integer(value), Value::Integer(modulus)) => {
+                let result = value.mod_inverse(&modulus)?;
+
+                self.flags.zero = result.is_zero();
+                self.flags.negative = false;
+                self.flags.carry = false;
+                self.flags.overflow = false;
+
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
+    fn integer_is_prime(&mut self) -> Result<(),Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        match value {
+            Value::Integer(value) => self.stack.push(value.is_prime().into_chunk()),
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
 
     basic_alu_op_int!(integer_bitwise_and, &);
     basic_alu_op_int!(integer_bitwise_or, |);
     basic_alu_op_int!(integer_bitwise_xor, ^);
-    basic_alu_op_int!(integer_shift_left, <<);
-    basic_alu_op_int!(integer_shift_right, >>);
 
-    fn integer_power(&mut self) -> Result<(),Fault> {
+    fn integer_shift_right(&mut self) -> Result<(),Fault> {
         let right = self.stack.pop().get_boxed_value();
         let left = self.stack.pop().get_boxed_value();
         match (left, right) {
             (Value::Integer(left), Value::Integer(right)) => {
-                let result = left.pow(right);
+                self.flags.carry = left.shift_right_carry(&right);
+                let result = self.narrow(left.shift_right(right)?);
 
-                if result.is_zero() {
-                    self.flags.zero = true;
-                } else {
-                    self.flags.zero = false;
-                }
-
-                if result.is_negative() {
-                    self.flags.negative = true;
-                } else {
-                    self.flags.negative = false;
-                }
+                self.flags.zero = result.is_zero();
+                self.flags.negative = result.is_negative();
+                self.flags.overflow = false;
 
-                let chunk = result.into_chunk();
-
-                self.stack.push(chunk);
+                self.stack.push(result.into_chunk());
             }
             _ => return Err(Fault::NotAnInteger),
         }
@@ -317,7 +831,13 @@ impl Core {
         let value = self.stack.pop().get_boxed_value();
         match value {
             Value::Integer(value) => {
-                let result = -value;
+                let result = if !value.negation_overflows() {
+                    self.narrow(-value)
+                } else if self.arithmetic_mode == ArithmeticMode::Promoting {
+                    Integer::Integer(-value.to_big_integer()).narrow()
+                } else {
+                    return Err(Fault::Overflow);
+                };
 
                 if result.is_zero() {
                     self.flags.zero = true;
@@ -331,6 +851,9 @@ impl Core {
                     self.flags.negative = false;
                 }
 
+                self.flags.carry = false;
+                self.flags.overflow = false;
+
                 let chunk = result.into_chunk();
 
                 self.stack.push(chunk);
@@ -358,6 +881,9 @@ impl Core {
                     self.flags.negative = false;
                 }
 
+                self.flags.carry = false;
+                self.flags.overflow = false;
+
                 let chunk = result.into_chunk();
 
                 self.stack.push(chunk);
@@ -367,6 +893,52 @@ impl Core {
         Ok(())
     }
 
+    fn integer_count(&mut self, count: fn(&Integer) -> Result<u64, Fault>) -> Result<(),Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        match value {
+            Value::Integer(value) => {
+                let result = count(&value)?;
+                self.flags.zero = result == 0;
+                self.flags.negative = false;
+                self.flags.carry = false;
+                self.flags.overflow = false;
+                self.stack.push(Integer::U64(result).into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
+    fn integer_test_bit(&mut self) -> Result<(),Fault> {
+        let index = self.stack.pop().get_boxed_value();
+        let value = self.stack.pop().get_boxed_value();
+        match (value, index) {
+            (Value::Integer(value), Value::Integer(index)) => {
+                let result = value.test_bit(&index)?;
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
+    fn integer_assign_bit(&mut self, bit: bool) -> Result<(),Fault> {
+        let index = self.stack.pop().get_boxed_value();
+        let value = self.stack.pop().get_boxed_value();
+        match (value, index) {
+            (Value::Integer(value), Value::Integer(index)) => {
+                let result = value.with_bit(&index, bit)?;
+                self.flags.zero = result.is_zero();
+                self.flags.negative = result.is_negative();
+                self.flags.carry = false;
+                self.flags.overflow = false;
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
     basic_alu_op_decimal!(decimal_add, +);
     basic_alu_op_decimal!(decimal_subtract, -);
     basic_alu_op_decimal!(decimal_multiply, *);
@@ -380,7 +952,7 @@ impl Core {
                     return Err(Fault::DivisionByZero);
                 }
 
-                let result = left / right;
+                let result = left.divide(right)?;
 
                 if result.is_zero() {
                     self.flags.zero = true;
@@ -412,7 +984,7 @@ impl Core {
                     return Err(Fault::DivisionByZero);
                 }
 
-                let result = left % right;
+                let result = left.remainder(right)?;
 
                 if result.is_zero() {
                     self.flags.zero = true;
@@ -440,7 +1012,7 @@ impl Core {
         let left = self.stack.pop().get_boxed_value();
         match (left, right) {
             (Value::Decimal(left), Value::Integer(right)) => {
-                let result = left.powi(right);
+                let result = left.powi(right)?;
 
                 if result.is_zero() {
                     self.flags.zero = true;
@@ -458,8 +1030,8 @@ impl Core {
 
                 self.stack.push(chunk);
             }
-            (Value::Decimal(left), Value::Decimal(right)) if !left.is_rational() && !right.is_rational() => {
-                let result = left.powd(right);
+            (Value::Decimal(left), Value::Decimal(right)) => {
+                let result = left.powd(right)?;
 
                 if result.is_zero() {
                     self.flags.zero = true;
@@ -482,6 +1054,67 @@ impl Core {
         Ok(())
     }
 
+    fn decimal_round(&mut self, rounding: Rounding) -> Result<(),Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        match value {
+            Value::Decimal(value) => self.push_rounded(value, 0, rounding),
+            _ => Err(Fault::NotAnInteger),
+        }
+    }
+
+    fn decimal_round_to(&mut self, rounding: Rounding) -> Result<(),Fault> {
+        let digits = self.stack.pop().get_boxed_value();
+        let value = self.stack.pop().get_boxed_value();
+        match (value, digits) {
+            (Value::Decimal(value), Value::Integer(digits)) => {
+                let digits = i64::try_from(&digits.to_big_integer()).map_err(|_| Fault::Overflow)?;
+                self.push_rounded(value, digits, rounding)
+            }
+            _ => Err(Fault::NotAnInteger),
+        }
+    }
+
+    fn push_rounded(&mut self, value: Decimal, digits: i64, rounding: Rounding) -> Result<(),Fault> {
+        let result = value.round(digits, rounding)?;
+
+        self.flags.zero = result.is_zero();
+        self.flags.negative = result.is_negative();
+
+        self.stack.push(result.into_chunk());
+        Ok(())
+    }
+
+    fn decimal_divide_to(&mut self, rounding: Rounding) -> Result<(),Fault> {
+        let digits = self.stack.pop().get_boxed_value();
+        let right = self.stack.pop().get_boxed_value();
+        let left = self.stack.pop().get_boxed_value();
+        match (left, right, digits) {
+            (Value::Decimal(left), Value::Decimal(right), Value::Integer(digits)) => {
+                let digits = i64::try_from(&digits.to_big_integer()).map_err(|_| Fault::Overflow)?;
+                let result = left.divide_to(right, digits, rounding)?;
+
+                self.flags.zero = result.is_zero();
+                self.flags.negative = result.is_negative();
+
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
+    fn decimal_convert(&mut self, typ: &DecimalType) -> Result<(),Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        match value {
+            Value::Decimal(value) => {
+                let result = value.convert(typ)?;
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAnInteger),
+        }
+        Ok(())
+    }
+
     fn decimal_negate(&mut self) -> Result<(),Fault> {
         let value = self.stack.pop().get_boxed_value();
         match value {
@@ -555,13 +1188,9 @@ impl Core {
 
     fn vector_new(&mut self, typ: &VectorType) -> Result<(), Fault> {
 
-        let size = self.stack.pop().get_boxed_value();
-        let size = match size {
-            Value::Integer(size) => size.to_usize().unwrap(),
-            _ => return Err(Fault::NotAnInteger),
-        };
+        let size = self.pop_index()?;
 
-        let mut vec = Vector::new(size, typ);
+        let vec = Vector::new(size, typ)?;
 
         self.stack.push(vec.into_chunk());
 
@@ -570,11 +1199,10 @@ impl Core {
 
 
     fn vector_get(&mut self) -> Result<(), Fault> {
-        let index = self.stack.pop().get_boxed_value();
+        let index = self.pop_index()?;
         let vector = self.stack.pop().get_boxed_value();
-        match (vector, index) {
-            (Value::Vector(vector), Value::Integer(index)) => {
-                let index = index.to_usize().unwrap();
+        match vector {
+            Value::Vector(vector) => {
                 let value = vector.get(index)?.clone();
                 self.stack.push(vector.into_chunk());
                 self.stack.push(value.into_chunk());
@@ -586,11 +1214,10 @@ impl Core {
 
     fn vector_set(&mut self) -> Result<(), Fault> {
         let value = self.stack.pop().get_boxed_value();
-        let index = self.stack.pop().get_boxed_value();
-        let mut vector = self.stack.pop().get_boxed_value();
-        match (vector, index) {
-            (Value::Vector(mut vector), Value::Integer(index)) => {
-                let index = index.to_usize().unwrap();
+        let index = self.pop_index()?;
+        let vector = self.stack.pop().get_boxed_value();
+        match vector {
+            Value::Vector(mut vector) => {
                 vector.set(index,value)?;
                 self.stack.push(vector.into_chunk());
             }
@@ -613,18 +1240,322 @@ impl Core {
         Ok(())
     }
 
-    fn tuple_new(&mut self) -> Result<(), Fault> {
-        let size = self.stack.pop().get_boxed_value();
-        let size = match size {
-            Value::Integer(size) => size.to_usize().unwrap(),
-            _ => return Err(Fault::NotAnInteger),
+    /// Pops an index or length. Negative integers are out of bounds.
+    fn pop_index(&mut self) -> Result<usize, Fault> {
+        match self.stack.pop().get_boxed_value() {
+            Value::Integer(index) => index.to_usize().ok_or(Fault::OutOfBounds),
+            _ => Err(Fault::NotAnInteger),
+        }
+    }
+
+    fn pop_vector(&mut self) -> Result<Vector, Fault> {
+        match self.stack.pop().get_boxed_value() {
+            Value::Vector(vector) => Ok(vector),
+            _ => Err(Fault::NotAVector),
+        }
+    }
+
+    fn vector_push(&mut self) -> Result<(), Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        let mut vector = self.pop_vector()?;
+        vector.push(value)?;
+        self.stack.push(vector.into_chunk());
+        Ok(())
+    }
+
+    fn vector_pop(&mut self) -> Result<(), Fault> {
+        let mut vector = self.pop_vector()?;
+        let value = vector.pop()?;
+        self.stack.push(vector.into_chunk());
+        self.stack.push(value.into_chunk());
+        Ok(())
+    }
+
+    fn vector_insert(&mut self) -> Result<(), Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        let index = self.pop_index()?;
+        let mut vector = self.pop_vector()?;
+        vector.insert(index, value)?;
+        self.stack.push(vector.into_chunk());
+        Ok(())
+    }
+
+    fn vector_remove(&mut self) -> Result<(), Fault> {
+        let index = self.pop_index()?;
+        let mut vector = self.pop_vector()?;
+        let value = vector.remove(index)?;
+        self.stack.push(vector.into_chunk());
+        self.stack.push(value.into_chunk());
+        Ok(())
+    }
+
+    fn vector_truncate(&mut self) -> Result<(), Fault> {
+        let length = self.pop_index()?;
+        let mut vector = self.pop_vector()?;
+        vector.truncate(length)?;
+        self.stack.push(vector.into_chunk());
+        Ok(())
+    }
+
+    fn vector_resize(&mut self) -> Result<(), Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        let length = self.pop_index()?;
+        let mut vector = self.pop_vector()?;
+        vector.resize(length, value)?;
+        self.stack.push(vector.into_chunk());
+        Ok(())
+    }
+
+    fn vector_slice(&mut self) -> Result<(), Fault> {
+        let end = self.pop_index()?;
+        let start = self.pop_index()?;
+        let vector = self.pop_vector()?;
+        let slice = vector.slice(start, end)?;
+        self.stack.push(vector.into_chunk());
+        self.stack.push(slice.into_chunk());
+        Ok(())
+    }
+
+    fn vector_concat(&mut self) -> Result<(), Fault> {
+        let right = self.pop_vector()?;
+        let left = self.pop_vector()?;
+        self.stack.push(left.concat(&right)?.into_chunk());
+        Ok(())
+    }
+
+    fn vector_copy(&mut self) -> Result<(), Fault> {
+        let end = self.pop_index()?;
+        let start = self.pop_index()?;
+        let source = self.pop_vector()?;
+        let offset = self.pop_index()?;
+        let mut destination = self.pop_vector()?;
+        destination.copy_from(offset, &source, start, end)?;
+        self.stack.push(destination.into_chunk());
+        Ok(())
+    }
+
+    fn vector_arithmetic(&mut self, operation: VectorOperation) -> Result<(), Fault> {
+        let right = self.stack.pop().get_boxed_value();
+        let left = self.stack.pop().get_boxed_value();
+        let result = vector_math::elementwise(&left, &right, operation)?;
+        self.stack.push(result.into_chunk());
+        Ok(())
+    }
+
+    fn vector_dot(&mut self) -> Result<(), Fault> {
+        let right = self.pop_vector()?;
+        let left = self.pop_vector()?;
+        self.stack.push(vector_math::dot(&left, &right)?.into_chunk());
+        Ok(())
+    }
+
+    fn vector_reduce(&mut self, reduction: VectorReduction) -> Result<(), Fault> {
+        let vector = self.pop_vector()?;
+        self.stack.push(vector_math::reduce(&vector, reduction)?.into_chunk());
+        Ok(())
+    }
+
+    fn pop_function(&mut self) -> Result<Function, Fault> {
+        match self.stack.pop().get_boxed_value() {
+            Value::Function(function) => Ok(function),
+            _ => Err(Fault::NotAFunction),
+        }
+    }
+
+    /// Calls a closure that has to return exactly one value.
+    fn call_callback(&mut self, function: &Function, arguments: Vec<Value>, module: &Module) -> Result<Value, Fault> {
+        let mut values = call_closure(self, module, function, arguments)?;
+        if values.len() != 1 {
+            return Err(Fault::InvalidOperation(format!("Expected a callback to return one value, found {}", values.len())));
+        }
+        Ok(values.pop().unwrap())
+    }
+
+    fn vector_sort(&mut self) -> Result<(), Fault> {
+        let mut vector = self.pop_vector()?;
+        vector.sort()?;
+        self.stack.push(vector.into_chunk());
+        Ok(())
+    }
+
+    fn vector_sort_by(&mut self, module: &Module) -> Result<(), Fault> {
+        let comparator = self.pop_function()?;
+        let mut vector = self.pop_vector()?;
+        vector.sort_by(|left, right| {
+            match self.call_callback(&comparator, vec![left, right], module)? {
+                Value::Integer(ordering) if ordering.is_zero() => Ok(Ordering::Equal),
+                Value::Integer(ordering) if ordering.is_negative() => Ok(Ordering::Less),
+                Value::Integer(_) => Ok(Ordering::Greater),
+                _ => Err(Fault::NotAnInteger),
+            }
+        })?;
+        self.stack.push(vector.into_chunk());
+        Ok(())
+    }
+
+    fn vector_binary_search(&mut self) -> Result<(), Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        let vector = self.pop_vector()?;
+        let position = match vector.binary_search(&value)? {
+            Ok(position) => position as i64,
+            Err(position) => -(position as i64) - 1,
         };
+        self.stack.push(vector.into_chunk());
+        self.stack.push(Value::Integer(Integer::I64(position)).into_chunk());
+        Ok(())
+    }
+
+    /// Pops a vector, changes it in place and pushes it back.
+    fn vector_update(&mut self, update: fn(&mut Vector)) -> Result<(), Fault> {
+        let mut vector = self.pop_vector()?;
+        update(&mut vector);
+        self.stack.push(vector.into_chunk());
+        Ok(())
+    }
+
+    fn vector_map(&mut self, module: &Module) -> Result<(), Fault> {
+        let function = self.pop_function()?;
+        let vector = self.pop_vector()?;
+        let result = vector.map(|value| self.call_callback(&function, vec![value], module))?;
+        self.stack.push(result.into_chunk());
+        Ok(())
+    }
+
+    fn vector_filter(&mut self, module: &Module) -> Result<(), Fault> {
+        let function = self.pop_function()?;
+        let vector = self.pop_vector()?;
+        let result = vector.filter(|value| {
+            match self.call_callback(&function, vec![value], module)? {
+                Value::Boolean(keep) => Ok(keep),
+                _ => Err(Fault::NotABoolean),
+            }
+        })?;
+        self.stack.push(result.into_chunk());
+        Ok(())
+    }
 
-        let mut data = Vec::new();
+    fn vector_fold(&mut self, module: &Module) -> Result<(), Fault> {
+        let function = self.pop_function()?;
+        let mut accumulator = self.stack.pop().get_boxed_value();
+        let vector = self.pop_vector()?;
+        for index in 0..vector.length() {
+            accumulator = self.call_callback(&function, vec![accumulator, vector.get(index)?], module)?;
+        }
+        self.stack.push(accumulator.into_chunk());
+        Ok(())
+    }
+
+    fn pop_array(&mut self) -> Result<Array, Fault> {
+        match self.stack.pop().get_boxed_value() {
+            Value::Array(array) => Ok(array),
+            _ => Err(Fault::NotAnArray),
+        }
+    }
+
+    /// Pops a tuple of indices or sizes.
+    fn pop_indices(&mut self) -> Result<Vec<usize>, Fault> {
+        match self.stack.pop().get_boxed_value() {
+            Value::Tuple(tuple) => array::indices(&tuple),
+            _ => Err(Fault::NotATuple),
+        }
+    }
+
+    fn array_new(&mut self, typ: &VectorType) -> Result<(), Fault> {
+        let shape = self.pop_indices()?;
+        self.stack.push(Array::new(shape, typ)?.into_chunk());
+        Ok(())
+    }
+
+    fn array_from_vector(&mut self) -> Result<(), Fault> {
+        let shape = self.pop_indices()?;
+        let vector = self.pop_vector()?;
+        self.stack.push(Array::from_vector(vector, shape)?.into_chunk());
+        Ok(())
+    }
+
+    fn array_get(&mut self) -> Result<(), Fault> {
+        let indices = self.pop_indices()?;
+        let array = self.pop_array()?;
+        let value = array.get(&indices)?;
+        self.stack.push(array.into_chunk());
+        self.stack.push(value.into_chunk());
+        Ok(())
+    }
+
+    fn array_set(&mut self) -> Result<(), Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        let indices = self.pop_indices()?;
+        let mut array = self.pop_array()?;
+        array.set(&indices, value)?;
+        self.stack.push(array.into_chunk());
+        Ok(())
+    }
+
+    fn array_shape(&mut self) -> Result<(), Fault> {
+        let array = self.pop_array()?;
+        let shape = array::shape_tuple(array.shape());
+        self.stack.push(array.into_chunk());
+        self.stack.push(shape.into_chunk());
+        Ok(())
+    }
+
+    fn array_reshape(&mut self) -> Result<(), Fault> {
+        let shape = self.pop_indices()?;
+        let array = self.pop_array()?;
+        self.stack.push(array.reshape(shape)?.into_chunk());
+        Ok(())
+    }
+
+    fn array_transpose(&mut self) -> Result<(), Fault> {
+        let array = self.pop_array()?;
+        self.stack.push(array.transpose().into_chunk());
+        Ok(())
+    }
+
+    fn array_slice(&mut self) -> Result<(), Fault> {
+        let end = self.pop_index()?;
+        let start = self.pop_index()?;
+        let axis = self.pop_index()?;
+        let array = self.pop_array()?;
+        let slice = array.slice(axis, start, end)?;
+        self.stack.push(array.into_chunk());
+        self.stack.push(slice.into_chunk());
+        Ok(())
+    }
+
+    fn array_matrix_multiply(&mut self) -> Result<(), Fault> {
+        let right = self.pop_array()?;
+        let left = self.pop_array()?;
+        self.stack.push(left.matrix_multiply(&right)?.into_chunk());
+        Ok(())
+    }
+
+    fn array_to_vector(&mut self) -> Result<(), Fault> {
+        let array = self.pop_array()?;
+        self.stack.push(array.to_vector().into_chunk());
+        Ok(())
+    }
+
+    fn pop_tuple(&mut self) -> Result<Tuple, Fault> {
+        match self.stack.pop().get_boxed_value() {
+            Value::Tuple(tuple) => Ok(tuple),
+            _ => Err(Fault::NotATuple),
+        }
+    }
+
+    fn tuple_new(&mut self) -> Result<(), Fault> {
+        let size = self.pop_index()?;
+        if size > self.stack.len() {
+            return Err(Fault::OutOfBounds);
+        }
+
+        let mut data = Vec::with_capacity(size);
         for _ in 0..size {
             let value = self.stack.pop().get_boxed_value();
             data.push(value);
         }
+        // The last value pushed was popped first.
+        data.reverse();
 
         let tuple = Value::Tuple(Tuple::new(data.into_boxed_slice()));
         self.stack.push(tuple.into_chunk());
@@ -633,16 +1564,95 @@ impl Core {
     }
 
     fn tuple_get(&mut self) -> Result<(), Fault> {
-        let index = self.stack.pop().get_boxed_value();
-        let tuple = self.stack.pop().get_boxed_value();
-        match (tuple, index) {
-            (Value::Tuple(tuple), Value::Integer(index)) => {
-                let index = index.to_usize().unwrap();
-                let value = tuple.get(index).clone();
-                self.stack.push(tuple.into_chunk());
+        let index = self.pop_index()?;
+        let tuple = self.pop_tuple()?;
+        let value = tuple.get(index)?.clone();
+        self.stack.push(tuple.into_chunk());
+        self.stack.push(value.into_chunk());
+        Ok(())
+    }
+
+    fn tuple_set(&mut self) -> Result<(), Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        let index = self.pop_index()?;
+        let tuple = self.pop_tuple()?.set(index, value)?;
+        self.stack.push(tuple.into_chunk());
+        Ok(())
+    }
+
+    fn tuple_length(&mut self) -> Result<(), Fault> {
+        let tuple = self.pop_tuple()?;
+        let length = tuple.length();
+        self.stack.push(tuple.into_chunk());
+        self.stack.push(length.into_chunk());
+        Ok(())
+    }
+
+    fn tuple_unpack(&mut self) -> Result<(), Fault> {
+        let tuple = self.pop_tuple()?;
+        for value in tuple.data.iter() {
+            self.stack.push(value.clone().into_chunk());
+        }
+        Ok(())
+    }
+
+    fn map_insert(&mut self) -> Result<(), Fault> {
+        let value = self.stack.pop().get_boxed_value();
+        let key = self.stack.pop().get_boxed_value();
+        let map = self.stack.pop().get_boxed_value();
+        match map {
+            Value::Map(mut map) => {
+                map.insert(key, value)?;
+                self.stack.push(map.into_chunk());
+            }
+            _ => return Err(Fault::NotAMap),
+        }
+        Ok(())
+    }
+
+    fn map_get(&mut self) -> Result<(), Fault> {
+        self.map_query(|map, key| map.get(&key).cloned())
+    }
+
+    fn map_remove(&mut self) -> Result<(), Fault> {
+        let key = self.stack.pop().get_boxed_value();
+        let map = self.stack.pop().get_boxed_value();
+        match map {
+            Value::Map(mut map) => {
+                let value = map.remove(&key)?;
+                self.stack.push(map.into_chunk());
                 self.stack.push(value.into_chunk());
             }
-            _ => return Err(Fault::NotATuple),
+            _ => return Err(Fault::NotAMap),
+        }
+        Ok(())
+    }
+
+    /// Pops a key and a map, and pushes the map back followed by `query`'s result.
+    fn map_query(&mut self, query: impl FnOnce(&Map, Value) -> Result<Value, Fault>) -> Result<(), Fault> {
+        let key = self.stack.pop().get_boxed_value();
+        let map = self.stack.pop().get_boxed_value();
+        match map {
+            Value::Map(map) => {
+                let result = query(&map, key)?;
+                self.stack.push(map.into_chunk());
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAMap),
+        }
+        Ok(())
+    }
+
+    /// Pops a map, and pushes it back followed by `inspect`'s result.
+    fn map_inspect(&mut self, inspect: impl FnOnce(&Map) -> Value) -> Result<(), Fault> {
+        let map = self.stack.pop().get_boxed_value();
+        match map {
+            Value::Map(map) => {
+                let result = inspect(&map);
+                self.stack.push(map.into_chunk());
+                self.stack.push(result.into_chunk());
+            }
+            _ => return Err(Fault::NotAMap),
         }
         Ok(())
     }
@@ -650,6 +1660,12 @@ impl Core {
     fn closure_new(&mut self, function_source: &FunctionSource, module: &Module, env: &mut Environment) -> Result<(), Fault> {
         let mut function = match function_source {
             FunctionSource::Name(name) => {
+                if let Some(host_function) = module.get_host_function(name) {
+                    // Host functions don't see the environment, so the closure captures nothing.
+                    let closure = Value::Function(host_function.shim(name));
+                    self.stack.push(closure.into_chunk());
+                    return Ok(());
+                }
                 let function = module.get_function(name)
                     .ok_or(Fault::FunctionNotFound(name.clone()))?;
                 function.clone()
@@ -665,9 +1681,53 @@ impl Core {
         Ok(())
     }
 
+    fn host_call(&mut self, path: &FunctionPath, module: &Module, environment: &Environment) -> Result<(), Fault> {
+        let host_function = module.get_host_function(path)
+            .ok_or(Fault::FunctionNotFound(path.clone()))?;
+        let arguments = host_function.argument_names.iter()
+            .filter_map(|name| environment.get(name).cloned())
+            .collect::<Vec<_>>();
+        if arguments.len() != host_function.arity() {
+            return Err(Fault::WrongArgumentCount {
+                expected: host_function.arity(),
+                found: arguments.len(),
+            });
+        }
+        for value in host_function.call(self, &arguments)? {
+            self.stack.push(value.into_chunk());
+        }
+        Ok(())
+    }
+
     fn compare(&mut self, comparison_type: &ComparisonType) -> Result<(),Fault> {
         let right = self.stack.pop().get_boxed_value();
         let left = self.stack.pop().get_boxed_value();
+        self.compare_values(comparison_type, &left, &right)?;
+        self.stack.push(left.into_chunk());
+        self.stack.push(right.into_chunk());
+
+        Ok(())
+    }
+
+    /// Sets the comparison flag. Integers of different types are compared in their common type,
+    /// but the operands are left as they were.
+    /// Compound values compare structurally, and anything holding a function faults with `NotComparable`.
+    fn compare_values(&mut self, comparison_type: &ComparisonType, left: &Value, right: &Value) -> Result<(),Fault> {
+        if left.contains_function() || right.contains_function() {
+            return Err(Fault::NotComparable);
+        }
+        if let (Value::Integer(left), Value::Integer(right)) = (left, right) {
+            if left.get_type() != right.get_type() {
+                let (left, right) = promote_integers(left.clone(), right.clone(), self.integer_promotion)?;
+                return self.compare_values(comparison_type, &Value::Integer(left), &Value::Integer(right));
+            }
+        }
+        if let (Value::Decimal(left), Value::Decimal(right)) = (left, right) {
+            if left.get_type() != right.get_type() {
+                let (left, right) = left.clone().promote(right.clone());
+                return self.compare_values(comparison_type, &Value::Decimal(left), &Value::Decimal(right));
+            }
+        }
         match comparison_type {
             ComparisonType::Equal => {
                 if left == right {
@@ -712,9 +1772,6 @@ impl Core {
                 }
             }
         }
-        self.stack.push(left.into_chunk());
-        self.stack.push(right.into_chunk());
-
         Ok(())
     }
 
@@ -729,6 +1786,10 @@ impl Core {
             Condition::GreaterThanOrEqual => self.flags.comparison == Comparison::GreaterThanOrEqual,
             Condition::Negative => self.flags.negative,
             Condition::NotNegative => !self.flags.negative,
+            Condition::Carry => self.flags.carry,
+            Condition::NoCarry => !self.flags.carry,
+            Condition::Overflow => self.flags.overflow,
+            Condition::NoOverflow => !self.flags.overflow,
             Condition::Zero => self.flags.zero,
             Condition::NotZero => !self.flags.zero,
         }
@@ -768,6 +1829,16 @@ impl Core {
         if self.can_jump(condition) {
             match source {
                 FunctionSource::Name(name) => {
+                    if let Some(host_function) = module.get_host_function(name) {
+                        let mut arguments = Vec::with_capacity(host_function.arity());
+                        for _ in host_function.argument_names.iter() {
+                            arguments.push(self.stack.pop().get_boxed_value());
+                        }
+                        for value in host_function.call(self, &arguments)? {
+                            self.stack.push(value.into_chunk());
+                        }
+                        return Ok(InstructionResult::Continue);
+                    }
                     let function = module.get_function(name)
                         .ok_or(Fault::FunctionNotFound(name.clone()))?;
                     let mut environment = Environment::new();
@@ -775,6 +1846,7 @@ impl Core {
                         let value = self.stack.pop();
                         environment.insert(parameter.clone(), value.get_boxed_value());
                     }
+                    self.integer_promotion = module.integer_promotion_of(name);
                     return Ok(InstructionResult::CallRef(function, environment));
                 }
                 FunctionSource::Address => {
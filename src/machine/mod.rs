@@ -9,6 +9,7 @@ use crate::value::Value;
 
 pub mod core;
 pub mod environment;
+pub mod vm;
 
 
 #[derive(Debug)]
@@ -31,9 +32,14 @@ pub enum Fault {
     NotABoolean,
     OutOfMemory,
     NotAVector,
+    NotAnArray,
     OutOfBounds,
     TypeMismatch,
+    NotComparable,
     NotATuple,
+    NotAMap,
+    KeyNotFound,
+    WrongArgumentCount { expected: usize, found: usize },
 }
 
 #[derive(Debug)]
@@ -54,7 +60,8 @@ pub fn call_main(core: &mut Core, module: Arc<Module>) -> Result<(), Fault> {
         }
         Some(function) => {
             let environment = Environment::new();
-            match call_function(core, module.clone(), function, environment)? {
+            core.set_integer_promotion(module.integer_promotion());
+            match call_function(core, &module, function, environment)? {
                 InstructionResult::Stop => {
                     Ok(())
                 }
@@ -76,22 +83,24 @@ pub fn call_main(core: &mut Core, module: Arc<Module>) -> Result<(), Fault> {
 
 
 fn call_function<'a>(core: &mut Core,
-                 module: Arc<Module>,
+                 module: &Module,
                  function: &Function,
                  mut environment: Environment) -> Result<InstructionResult<'a>,Fault> {
     let mut program_counter = 0;
-    let mut instruction = function.get_instruction(program_counter);
+    let mut instruction = function.get_instruction(program_counter)?;
+    // Calls by name switch to the callee's module setting, so it's put back after each call.
+    let integer_promotion = core.integer_promotion();
 
     loop {
 
-        let mut result = core.execute_instruction(instruction, &mut program_counter, &mut environment, &module)?;
+        let mut result = core.execute_instruction(instruction, &mut program_counter, &mut environment, module)?;
         'check_result: loop {
             match result {
                 InstructionResult::Stop => {
                     return Ok(InstructionResult::Stop)
                 }
                 InstructionResult::Continue => {
-                    instruction = function.get_instruction(program_counter);
+                    instruction = function.get_instruction(program_counter)?;
                     break 'check_result;
                 }
                 InstructionResult::Return => {
@@ -103,16 +112,53 @@ fn call_function<'a>(core: &mut Core,
                 InstructionResult::Call(function, mut environment) => {
                     let function_env = (*function.get_environment()).clone();
                     environment.extend(function_env);
-                    result = call_function(core, module.clone(), &function, environment)?;
+                    result = call_function(core, module, &function, environment)?;
+                    core.set_integer_promotion(integer_promotion);
                 }
                 InstructionResult::CallRef(function, mut environment) => {
                     let function_env = (*function.get_environment()).clone();
                     environment.extend(function_env);
-                    result = call_function(core, module.clone(), function, environment)?;
+                    #[cfg(feature = "jit")]
+                    if core.call_compiled(function, &environment, module) {
+                        core.set_integer_promotion(integer_promotion);
+                        result = InstructionResult::Continue;
+                        continue 'check_result;
+                    }
+                    result = call_function(core, module, function, environment)?;
+                    core.set_integer_promotion(integer_promotion);
                 }
             }
 
         }
 
     }
-}
\ No newline at end of file
+}
+
+/// Calls a closure and returns the values it left on the stack, bottom first.
+///
+/// The arguments are bound to the argument names in order and the closure's captured environment is
+/// added, like `FunctionCall` does. If the closure faults, the stack is reset to how it was before the call.
+pub(crate) fn call_closure(core: &mut Core, module: &Module, function: &Function, arguments: Vec<Value>) -> Result<Vec<Value>, Fault> {
+    if function.argument_names.len() != arguments.len() {
+        return Err(Fault::WrongArgumentCount {
+            expected: function.argument_names.len(),
+            found: arguments.len(),
+        });
+    }
+
+    let mut environment = Environment::new();
+    for (name, argument) in function.argument_names.iter().zip(arguments) {
+        environment.insert(name.clone(), argument);
+    }
+    environment.extend(function.get_environment().clone());
+
+    let height = core.stack_len();
+    let result = call_function(core, module, function, environment);
+    let mut values = Vec::with_capacity(core.stack_len().saturating_sub(height));
+    while core.stack_len() > height {
+        values.push(core.pop_value().unwrap());
+    }
+    result?;
+    values.reverse();
+    Ok(values)
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use crate::machine::core::Core;
+use crate::machine::{call_closure, Fault};
+use crate::program::FunctionPath;
+use crate::program::module::Module;
+use crate::value::Value;
+
+/// A virtual machine that runs the functions of a module on behalf of a host program.
+///
+/// ```ignore
+/// let mut vm = Vm::new(module);
+/// let results = vm.call("math::add", vec![1i64.into(), 2i64.into()])?;
+/// let sum = i64::try_from(results[0].clone())?;
+/// ```
+pub struct Vm {
+    core: Core,
+    module: Arc<Module>,
+}
+
+impl Vm {
+    pub fn new(module: Module) -> Vm {
+        Vm::from_arc(Arc::new(module))
+    }
+
+    /// Creates a machine for a module that is already shared with other machines.
+    pub fn from_arc(module: Arc<Module>) -> Vm {
+        Vm {
+            core: Core::new(),
+            module,
+        }
+    }
+
+    pub fn module(&self) -> &Arc<Module> {
+        &self.module
+    }
+
+    pub fn core(&self) -> &Core {
+        &self.core
+    }
+
+    pub fn core_mut(&mut self) -> &mut Core {
+        &mut self.core
+    }
+
+    /// Calls the function at `path` and returns the values it left on the stack, bottom first.
+    /// Host functions are called directly and return whatever their callback returns.
+    ///
+    /// The arguments are bound to the argument names of the function in order.
+    /// If the function faults, the stack is reset to how it was before the call.
+    pub fn call(&mut self, path: impl Into<FunctionPath>, arguments: Vec<Value>) -> Result<Vec<Value>, Fault> {
+        let path = path.into();
+        let module = self.module.clone();
+        if let Some(host_function) = module.get_host_function(&path) {
+            return host_function.call(&mut self.core, &arguments);
+        }
+        self.core.set_integer_promotion(module.integer_promotion_of(&path));
+        let function = module.get_function(&path).ok_or(Fault::FunctionNotFound(path))?;
+        call_closure(&mut self.core, &module, function, arguments)
+    }
+}
@@ -1,18 +1,13 @@
 use std::sync::Arc;
 use malachite::Natural;
-use crate::instruction::{ComparisonType, Condition, FunctionSource, Instruction, JumpTarget};
-use crate::machine::call_main;
-use crate::machine::core::Core;
-use crate::program::FunctionPath;
-use crate::program::module::Module;
-use crate::value::function::Function;
-use crate::value::integer::Integer;
-
-pub mod value;
-pub mod instruction;
-pub mod stack;
-pub mod program;
-pub mod machine;
+use crayfish_vm3::{instruction, optimizer};
+use crayfish_vm3::instruction::{ComparisonType, Condition, FunctionSource, Instruction, JumpTarget};
+use crayfish_vm3::machine::call_main;
+use crayfish_vm3::machine::core::Core;
+use crayfish_vm3::program::FunctionPath;
+use crayfish_vm3::program::module::Module;
+use crayfish_vm3::value::function::Function;
+use crayfish_vm3::value::integer::Integer;
 
 
 fn dp_fib() -> Function {
@@ -152,6 +147,8 @@ fn main() {
     module.add_function("fib", rec_fib());
     module.add_function("main", rec_fib_main());
 
+    optimizer::optimize_module(&mut module);
+
     let mut core = Core::new();
 
     let module = Arc::new(module);
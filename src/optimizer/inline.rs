@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+use crate::instruction::{Condition, FunctionSource, Instruction, JumpTarget, RealInstruction};
+use crate::optimizer::peephole::all_jumps_in_bounds;
+use crate::program::module::Module;
+use crate::value::function::Function;
+
+/// Callees with more instructions than this are never inlined.
+pub const MAX_CALLEE_SIZE: usize = 32;
+/// The number of instructions inlining may add to a single function.
+pub const MAX_GROWTH: usize = 256;
+
+/// Inlines small functions into their callers across the module and all of its sub modules.
+/// Callee bodies are taken from the module as it was before this pass, so every call is
+/// expanded at most one level deep.
+pub fn inline_module(module: &mut Module) {
+    let original = module.clone();
+    module.for_each_function_with_promotion_mut(&mut |function, integer_promotion| {
+        *function = inline(&original, function, integer_promotion);
+    });
+}
+
+/// Splices the bodies of small, non recursive functions into `function` at their call sites.
+///
+/// Only unconditional calls by name are inlined. The arguments are stored into renamed locals
+/// the same way `FunctionCall` would bind them, the locals of the callee get a suffix so they
+/// don't clash with the caller and every `Return` becomes a jump to the end of the inlined body.
+/// `integer_promotion` is the setting of the module `function` lives in. Callees from modules
+/// with a different setting are left as calls, since an inlined body runs under the caller's setting.
+/// Functions with a jump that leaves the function are returned unchanged, like in the peephole pass.
+pub fn inline(module: &Module, function: &Function, integer_promotion: bool) -> Function {
+    if !all_jumps_in_bounds(function) {
+        return function.clone();
+    }
+    let len = function.code.len();
+    let mut names = local_names(function);
+
+    let mut callees = Vec::with_capacity(len);
+    let mut growth = 0;
+    for instruction in function.code.iter() {
+        let callee = inline_candidate(module, &instruction.instruction, integer_promotion)
+            .filter(|callee| growth + callee.argument_names.len() + callee.code.len() - 1 <= MAX_GROWTH);
+        if let Some(callee) = callee {
+            growth += callee.argument_names.len() + callee.code.len() - 1;
+        }
+        callees.push(callee);
+    }
+    if callees.iter().all(Option::is_none) {
+        return function.clone();
+    }
+
+    let mut map = vec![0; len + 1];
+    let mut new_address = 0;
+    for (address, callee) in callees.iter().enumerate() {
+        map[address] = new_address;
+        new_address += match callee {
+            Some(callee) => callee.argument_names.len() + callee.code.len(),
+            None => 1,
+        };
+    }
+    map[len] = new_address;
+
+    let mut code = Vec::with_capacity(new_address);
+    let mut site = 0;
+    for (address, instruction) in function.code.iter().enumerate() {
+        let Some(callee) = callees[address] else {
+            let mut instruction = instruction.clone();
+            let new_address = code.len();
+            if let Some((target, _)) = instruction.instruction.jump_target_mut() {
+                let old_target = target.resolve(address).unwrap();
+                target.retarget(new_address, map[old_target]);
+            }
+            code.push(instruction);
+            continue;
+        };
+
+        let suffix = loop {
+            let suffix = format!("#{}", site);
+            site += 1;
+            let callee_names = local_names(callee);
+            if callee_names.iter().all(|name| !names.contains(&renamed(name, &suffix))) {
+                names.extend(callee_names.iter().map(|name| renamed(name, &suffix)));
+                break suffix;
+            }
+        };
+
+        for name in callee.argument_names.iter() {
+            code.push(Instruction {
+                row: instruction.row,
+                column: instruction.column,
+                instruction: RealInstruction::Store(renamed(name, &suffix)),
+            });
+        }
+        let base = code.len();
+        let end = base + callee.code.len();
+        for (callee_address, callee_instruction) in callee.code.iter().enumerate() {
+            let new_address = code.len();
+            let mut instruction = Instruction {
+                row: callee_instruction.row,
+                column: callee_instruction.column,
+                instruction: rename_locals(&callee_instruction.instruction, &suffix),
+            };
+            if let RealInstruction::Return(condition) = &instruction.instruction {
+                let mut target = JumpTarget::Relative(0);
+                target.retarget(new_address, end);
+                instruction.instruction = RealInstruction::Goto(target, *condition);
+            } else if let Some((target, _)) = instruction.instruction.jump_target_mut() {
+                let old_target = target.resolve(callee_address).unwrap();
+                target.retarget(new_address, base + old_target);
+            }
+            code.push(instruction);
+        }
+    }
+
+    let mut function = function.clone();
+    function.code = code.into_boxed_slice();
+    function
+}
+
+/// Returns the function called by `instruction` if the call can be inlined.
+fn inline_candidate<'m>(module: &'m Module, instruction: &RealInstruction, integer_promotion: bool) -> Option<&'m Function> {
+    let RealInstruction::FunctionCall(FunctionSource::Name(path), Condition::Always) = instruction else {
+        return None;
+    };
+    if module.integer_promotion_of(path) != integer_promotion {
+        return None;
+    }
+    let callee = module.get_bytecode_function(path)?;
+    if callee.code.is_empty() || callee.code.len() > MAX_CALLEE_SIZE || !callee.environment.is_empty() {
+        return None;
+    }
+    // Closures capture the whole environment, which would now include the caller's locals.
+    let has_closure = callee.code.iter()
+        .any(|instruction| matches!(instruction.instruction, RealInstruction::ClosureNew(_)));
+    if has_closure || !all_jumps_in_bounds(callee) {
+        return None;
+    }
+    if is_recursive(module, callee) {
+        return None;
+    }
+    Some(callee)
+}
+
+/// Checks if `function` can end up calling itself through calls by name.
+fn is_recursive(module: &Module, function: &Function) -> bool {
+    let mut seen = HashSet::new();
+    let mut worklist = vec![function];
+    while let Some(current) = worklist.pop() {
+        for instruction in current.code.iter() {
+            let RealInstruction::FunctionCall(FunctionSource::Name(path), _) = &instruction.instruction else {
+                continue;
+            };
+            let Some(callee) = module.get_bytecode_function(path) else {
+                continue;
+            };
+            if std::ptr::eq(callee, function) {
+                return true;
+            }
+            if seen.insert(callee as *const Function) {
+                worklist.push(callee);
+            }
+        }
+    }
+    false
+}
+
+/// Every local name a function binds or reads.
+fn local_names(function: &Function) -> HashSet<Box<str>> {
+    use RealInstruction::*;
+    let mut names = function.argument_names.iter().cloned().collect::<HashSet<_>>();
+    for instruction in function.code.iter() {
+        match &instruction.instruction {
+            Store(name) | Lookup(name) | IncrementLocal(name, _) | DecrementLocal(name, _) => {
+                names.insert(name.clone());
+            }
+            LookupPairOperation(left, right, _) => {
+                names.insert(left.clone());
+                names.insert(right.clone());
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn renamed(name: &str, suffix: &str) -> Box<str> {
+    format!("{}{}", name, suffix).into_boxed_str()
+}
+
+fn rename_locals(instruction: &RealInstruction, suffix: &str) -> RealInstruction {
+    use RealInstruction::*;
+    match instruction {
+        Store(name) => Store(renamed(name, suffix)),
+        Lookup(name) => Lookup(renamed(name, suffix)),
+        IncrementLocal(name, amount) => IncrementLocal(renamed(name, suffix), amount.clone()),
+        DecrementLocal(name, amount) => DecrementLocal(renamed(name, suffix), amount.clone()),
+        LookupPairOperation(left, right, operation) => {
+            LookupPairOperation(renamed(left, suffix), renamed(right, suffix), *operation)
+        }
+        instruction => instruction.clone(),
+    }
+}
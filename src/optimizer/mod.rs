@@ -0,0 +1,13 @@
+pub mod inline;
+pub mod peephole;
+
+use crate::program::module::Module;
+
+/// Runs every optimization pass over all the functions in the module and its sub modules.
+/// Inlining runs first so the peephole pass can clean up the jumps it leaves behind.
+pub fn optimize_module(module: &mut Module) {
+    inline::inline_module(module);
+    module.for_each_function_mut(&mut |function| {
+        *function = peephole::optimize(function);
+    });
+}
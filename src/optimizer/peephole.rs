@@ -0,0 +1,199 @@
+use crate::instruction::{BinaryOperation, Condition, Instruction, RealInstruction};
+use crate::value::function::Function;
+
+/// An instruction paired with the absolute address it can jump to (if any).
+/// Keeping the address absolute while instructions move around lets us fix up
+/// both relative and absolute jump targets once at the end.
+struct Slot {
+    instruction: Instruction,
+    target: Option<usize>,
+}
+
+/// Runs the peephole optimizer over a function.
+///
+/// The pass threads jumps that land on unconditional jumps, removes `NoOp`s and jumps to the
+/// next instruction and then fuses common instruction sequences into superinstructions.
+/// The optimized function behaves exactly like the original one. Functions with a jump that leaves
+/// the function are returned unchanged, since moving instructions would change where it lands.
+pub fn optimize(function: &Function) -> Function {
+    if !all_jumps_in_bounds(function) {
+        return function.clone();
+    }
+    let slots = function.code.iter()
+        .enumerate()
+        .map(|(address, instruction)| {
+            let target = instruction.instruction.jump_target()
+                .and_then(|(target, _)| target.resolve(address))
+                .map(|target| thread_jump(&function.code, target));
+            Slot {
+                instruction: instruction.clone(),
+                target,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let slots = remove_dead_instructions(slots);
+    let slots = fuse_instructions(slots);
+
+    let code = slots.into_iter()
+        .enumerate()
+        .map(|(address, mut slot)| {
+            if let (Some(new_target), Some((target, _))) = (slot.target, slot.instruction.instruction.jump_target_mut()) {
+                target.retarget(address, new_target);
+            }
+            slot.instruction
+        })
+        .collect::<Vec<_>>();
+
+    let mut function = function.clone();
+    function.code = code.into_boxed_slice();
+    function
+}
+
+/// Checks that every jump lands inside the function or right at its end.
+pub(crate) fn all_jumps_in_bounds(function: &Function) -> bool {
+    function.code.iter().enumerate().all(|(address, instruction)| {
+        instruction.instruction.jump_target()
+            .map(|(target, _)| target.resolve(address).is_some_and(|target| target <= function.code.len()))
+            .unwrap_or(true)
+    })
+}
+
+/// Follows a chain of unconditional jumps (and `NoOp`s) starting at `target` to its final destination.
+fn thread_jump(code: &[Instruction], mut target: usize) -> usize {
+    // Bounding the number of steps keeps us from spinning on jump cycles.
+    for _ in 0..=code.len() {
+        if target >= code.len() {
+            break;
+        }
+        match &code[target].instruction {
+            RealInstruction::NoOp => target += 1,
+            RealInstruction::Goto(next, Condition::Always) => match next.resolve(target) {
+                Some(next) if next <= code.len() => target = next,
+                _ => break,
+            },
+            _ => break,
+        }
+    }
+    target
+}
+
+/// Rewrites the jump targets of the slots after instructions have been moved.
+/// `map` holds the new address for each old address, with one extra entry for the end of the function.
+fn remap_targets(slots: &mut [Slot], map: &[usize]) {
+    for slot in slots.iter_mut() {
+        if let Some(target) = slot.target {
+            slot.target = Some(map[target]);
+        }
+    }
+}
+
+/// Removes `NoOp`s and jumps whose target is the instruction that would run next anyway.
+fn remove_dead_instructions(slots: Vec<Slot>) -> Vec<Slot> {
+    let len = slots.len();
+    let is_noop = |slot: &Slot| matches!(slot.instruction.instruction, RealInstruction::NoOp);
+
+    let mut keep = vec![true; len];
+    for (address, slot) in slots.iter().enumerate() {
+        if is_noop(slot) {
+            keep[address] = false;
+            continue;
+        }
+        if let (RealInstruction::Goto(_, _), Some(target)) = (&slot.instruction.instruction, slot.target) {
+            if target > address && slots[address + 1..target].iter().all(is_noop) {
+                keep[address] = false;
+            }
+        }
+    }
+
+    let mut map = vec![0; len + 1];
+    let mut new_address = 0;
+    for address in 0..len {
+        map[address] = new_address;
+        if keep[address] {
+            new_address += 1;
+        }
+    }
+    map[len] = new_address;
+
+    let mut slots = slots.into_iter()
+        .zip(keep)
+        .filter_map(|(slot, keep)| if keep { Some(slot) } else { None })
+        .collect::<Vec<_>>();
+    remap_targets(&mut slots, &map);
+    slots
+}
+
+/// Tries to fuse the instructions starting at the beginning of `slots` into a superinstruction.
+/// Returns the superinstruction and the number of instructions it replaces.
+fn fuse(slots: &[Slot]) -> Option<(RealInstruction, Option<usize>, usize)> {
+    use RealInstruction::*;
+    let instructions = slots.iter()
+        .take(4)
+        .map(|slot| &slot.instruction.instruction)
+        .collect::<Vec<_>>();
+
+    match instructions.as_slice() {
+        [Lookup(load), IntegerNew(amount), IntegerAdd, Store(store), ..] if load == store => {
+            Some((IncrementLocal(load.clone(), amount.clone()), None, 4))
+        }
+        [Lookup(load), IntegerNew(amount), IntegerSubtract, Store(store), ..] if load == store => {
+            Some((DecrementLocal(load.clone(), amount.clone()), None, 4))
+        }
+        [Compare(comparison), Goto(target, condition), ..] => {
+            Some((CompareGoto(*comparison, target.clone(), *condition), slots[1].target, 2))
+        }
+        [Lookup(left), Lookup(right), operation, ..] => {
+            let operation = BinaryOperation::from_instruction(operation)?;
+            Some((LookupPairOperation(left.clone(), right.clone(), operation), None, 3))
+        }
+        _ => None,
+    }
+}
+
+/// Replaces common instruction sequences with superinstructions.
+/// A sequence is only fused if nothing jumps into the middle of it.
+fn fuse_instructions(slots: Vec<Slot>) -> Vec<Slot> {
+    let len = slots.len();
+    let mut is_target = vec![false; len + 1];
+    for slot in slots.iter() {
+        if let Some(target) = slot.target {
+            is_target[target] = true;
+        }
+    }
+
+    let mut map = vec![0; len + 1];
+    let mut fused = Vec::with_capacity(len);
+    let mut address = 0;
+    while address < len {
+        let new_address = fused.len();
+        match fuse(&slots[address..]) {
+            Some((instruction, target, count)) if !is_target[address + 1..address + count].contains(&true) => {
+                let first = &slots[address].instruction;
+                fused.push(Slot {
+                    instruction: Instruction {
+                        row: first.row,
+                        column: first.column,
+                        instruction,
+                    },
+                    target,
+                });
+                map[address..address + count].fill(new_address);
+                address += count;
+            }
+            _ => {
+                let slot = &slots[address];
+                fused.push(Slot {
+                    instruction: slot.instruction.clone(),
+                    target: slot.target,
+                });
+                map[address] = new_address;
+                address += 1;
+            }
+        }
+    }
+    map[len] = fused.len();
+
+    remap_targets(&mut fused, &map);
+    fused
+}
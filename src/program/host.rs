@@ -0,0 +1,67 @@
+use std::fmt::{Debug, Display};
+use std::sync::Arc;
+use crate::instruction::{Condition, Instruction, RealInstruction};
+use crate::machine::core::Core;
+use crate::machine::Fault;
+use crate::program::FunctionPath;
+use crate::value::function::Function;
+use crate::value::Value;
+
+/// The Rust side of a host function.
+/// Gets the arguments in the order of the argument names and returns the values to push, bottom first.
+pub type HostCallback = dyn Fn(&mut Core, &[Value]) -> Result<Vec<Value>, Fault> + Send + Sync;
+
+/// A function implemented in Rust that bytecode can call like any other function.
+#[derive(Clone)]
+pub struct HostFunction {
+    pub argument_names: Box<[Box<str>]>,
+    callback: Arc<HostCallback>,
+}
+
+impl HostFunction {
+    pub fn new<F>(argument_names: &[&str], callback: F) -> Self
+    where
+        F: Fn(&mut Core, &[Value]) -> Result<Vec<Value>, Fault> + Send + Sync + 'static,
+    {
+        HostFunction {
+            argument_names: argument_names.iter().map(|name| (*name).into()).collect(),
+            callback: Arc::new(callback),
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.argument_names.len()
+    }
+
+    pub fn call(&self, core: &mut Core, arguments: &[Value]) -> Result<Vec<Value>, Fault> {
+        if arguments.len() != self.arity() {
+            return Err(Fault::WrongArgumentCount {
+                expected: self.arity(),
+                found: arguments.len(),
+            });
+        }
+        (self.callback)(core, arguments)
+    }
+
+    /// Creates a bytecode function that forwards its arguments to the host function at `path`.
+    /// This is what `ClosureNew` puts on the stack for host functions.
+    pub fn shim(&self, path: &FunctionPath) -> Function {
+        let code = Box::new([
+            Instruction::new(RealInstruction::HostCall(path.clone())),
+            Instruction::new(RealInstruction::Return(Condition::Always)),
+        ]);
+        Function::new(code, self.argument_names.clone())
+    }
+}
+
+impl Display for HostFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "host function ({})", self.argument_names.join(", "))
+    }
+}
+
+impl Debug for HostFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
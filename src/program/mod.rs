@@ -1,4 +1,5 @@
 pub mod module;
+pub mod host;
 
 use std::fmt::{Debug, Display};
 
@@ -1,12 +1,16 @@
 use std::collections::HashMap;
 use crate::program::FunctionPath;
+use crate::program::host::HostFunction;
 use crate::value::function::Function;
 
+#[derive(Clone)]
 pub struct Module {
     module_name: Box<str>,
     functions: HashMap<Box<str>, Function>,
+    host_functions: HashMap<Box<str>, HostFunction>,
     string_table: Vec<Box<str>>,
     sub_modules: HashMap<Box<str>, Module>,
+    integer_promotion: bool,
 }
 
 
@@ -16,11 +20,35 @@ impl Module {
         Module {
             module_name: module_name.to_string().into(),
             functions,
+            host_functions: HashMap::new(),
             string_table,
             sub_modules,
+            integer_promotion: true,
         }
     }
 
+    /// Whether integer operations on two different integer types convert both sides to a common type.
+    /// When it is off, mixing types faults with `TypeMismatch`. It is on by default.
+    pub fn integer_promotion(&self) -> bool {
+        self.integer_promotion
+    }
+
+    pub fn set_integer_promotion(&mut self, enabled: bool) {
+        self.integer_promotion = enabled;
+    }
+
+    /// The integer promotion setting of the module that `path` names a function in.
+    pub fn integer_promotion_of(&self, path: &FunctionPath) -> bool {
+        let mut module = self;
+        for part in path.path.iter().take(path.path.len().saturating_sub(1)) {
+            match module.sub_modules.get(part) {
+                Some(sub_module) => module = sub_module,
+                None => break,
+            }
+        }
+        module.integer_promotion
+    }
+
     pub fn get_function(&self, path: &FunctionPath) -> Option<&Function> {
         let mut module = self;
         for part in path.path.iter().take(path.path.len() - 1) {
@@ -33,9 +61,56 @@ impl Module {
         }
     }
 
+    /// Looks up a host function the same way [`Module::get_function`] looks up bytecode functions.
+    pub fn get_host_function(&self, path: &FunctionPath) -> Option<&HostFunction> {
+        let mut module = self;
+        for part in path.path.iter().take(path.path.len() - 1) {
+            module = module.sub_modules.get(part)?;
+        }
+        module.host_functions.get(path.path.last()?)
+    }
+
+    /// The bytecode function a call to `path` runs. Calls prefer a host function registered at
+    /// the same path, so this is `None` when there is one.
+    pub fn get_bytecode_function(&self, path: &FunctionPath) -> Option<&Function> {
+        if self.get_host_function(path).is_some() {
+            return None;
+        }
+        self.get_function(path)
+    }
+
     pub fn add_function(&mut self, path: &str, function: Function) {
         self.functions.insert(path.to_string().into(), function);
     }
+
+    pub fn add_module(&mut self, name: &str, module: Module) {
+        self.sub_modules.insert(name.to_string().into(), module);
+    }
+
+    pub fn add_host_function(&mut self, path: &str, function: HostFunction) {
+        self.host_functions.insert(path.to_string().into(), function);
+    }
+
+    /// Calls `f` on every function in this module and all of its sub modules.
+    pub fn for_each_function_mut(&mut self, f: &mut impl FnMut(&mut Function)) {
+        for function in self.functions.values_mut() {
+            f(function);
+        }
+        for module in self.sub_modules.values_mut() {
+            module.for_each_function_mut(f);
+        }
+    }
+
+    /// Like `for_each_function_mut`, but also passes the integer promotion setting of the module
+    /// each function lives in.
+    pub fn for_each_function_with_promotion_mut(&mut self, f: &mut impl FnMut(&mut Function, bool)) {
+        for function in self.functions.values_mut() {
+            f(function, self.integer_promotion);
+        }
+        for module in self.sub_modules.values_mut() {
+            module.for_each_function_with_promotion_mut(f);
+        }
+    }
 }
 
 impl Default for Module {
@@ -43,8 +118,10 @@ impl Default for Module {
         Module {
             module_name: "".to_string().into(),
             functions: HashMap::new(),
+            host_functions: HashMap::new(),
             string_table: Vec::new(),
             sub_modules: HashMap::new(),
+            integer_promotion: true,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,19 @@
+use crate::stack::StackChunk;
+use crate::value::array::Array;
+use crate::value::Value;
+
+
+
+impl StackChunk for Array {
+    fn get_value(self) -> Value {
+        Value::Array(self)
+    }
+
+    fn get_boxed_value(self: Box<Self>) -> Value {
+        Value::Array(*self)
+    }
+
+    fn into_chunk(self) -> Box<dyn StackChunk> {
+        Box::new(self)
+    }
+}
\ No newline at end of file
@@ -1,5 +1,6 @@
 use crate::value::Value;
 use crate::stack::StackChunk;
+use crate::value::big_decimal::BigDecimal;
 use crate::value::decimal::Decimal;
 
 macro_rules! decimal_chunk {
@@ -23,4 +24,5 @@ macro_rules! decimal_chunk {
 
 decimal_chunk!(F32, f32);
 decimal_chunk!(F64, f64);
-decimal_chunk!(Rational, malachite::Rational);
\ No newline at end of file
+decimal_chunk!(Rational, malachite::Rational);
+decimal_chunk!(BigDecimal, BigDecimal);
\ No newline at end of file
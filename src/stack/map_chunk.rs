@@ -0,0 +1,19 @@
+use crate::stack::StackChunk;
+use crate::value::map::Map;
+use crate::value::Value;
+
+
+
+impl StackChunk for Map {
+    fn get_value(self) -> Value {
+        Value::Map(self)
+    }
+
+    fn get_boxed_value(self: Box<Self>) -> Value {
+        Value::Map(*self)
+    }
+
+    fn into_chunk(self) -> Box<dyn StackChunk> {
+        Box::new(self)
+    }
+}
\ No newline at end of file
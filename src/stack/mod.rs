@@ -1,9 +1,11 @@
 pub mod integer_chunks;
 pub mod decimal_chunks;
 pub mod vector_chunk;
+pub mod array_chunk;
 pub mod product_chunk;
 pub mod sum_chunks;
 pub mod tuple_chunk;
+pub mod map_chunk;
 pub mod function_chunk;
 pub mod reference_chunk;
 pub mod character_chunk;
@@ -1,7 +1,8 @@
+use std::sync::Arc;
 use crate::stack::StackChunk;
 use crate::value::Value;
 
-impl StackChunk for String {
+impl StackChunk for Arc<str> {
     fn get_value(self) -> Value {
         Value::String(self)
     }
@@ -13,4 +14,4 @@ impl StackChunk for String {
     fn into_chunk(self) -> Box<dyn StackChunk> {
         Box::new(self)
     }
-}
\ No newline at end of file
+}
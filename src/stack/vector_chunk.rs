@@ -1,8 +1,12 @@
 use std::cell::RefCell;
 use std::fmt::Display;
+use std::sync::Arc;
 use malachite::Natural;
 use crate::stack::StackChunk;
+use crate::value::big_decimal::BigDecimal;
 use crate::value::function::Function;
+use crate::value::product::ProductType;
+use crate::value::sum::SumType;
 use crate::value::vector::Vector;
 use crate::value::Value;
 use crate::value::Reference;
@@ -44,23 +48,30 @@ macro_rules! vector_chunk {
     };
 }
 
-pub struct U8Vector(pub Vec<u8>);
-pub struct U16Vector(pub Vec<u16>);
-pub struct U32Vector(pub Vec<u32>);
-pub struct U64Vector(pub Vec<u64>);
-pub struct I8Vector(pub Vec<i8>);
-pub struct I16Vector(pub Vec<i16>);
-pub struct I32Vector(pub Vec<i32>);
-pub struct I64Vector(pub Vec<i64>);
-pub struct F32Vector(pub Vec<f32>);
-pub struct F64Vector(pub Vec<f64>);
-pub struct NaturalVector(pub Vec<Natural>);
-pub struct IntegerVector(pub Vec<malachite::Integer>);
-pub struct RationalVector(pub Vec<malachite::Rational>);
-pub struct ReferenceVector(pub Vec<Reference>);
-pub struct VectorVector(pub Vec<Vector>);
-pub struct TupleVector(pub Vec<Tuple>);
-pub struct FunctionVector(pub Vec<Function>);
+pub struct U8Vector(pub Arc<Vec<u8>>);
+pub struct U16Vector(pub Arc<Vec<u16>>);
+pub struct U32Vector(pub Arc<Vec<u32>>);
+pub struct U64Vector(pub Arc<Vec<u64>>);
+pub struct I8Vector(pub Arc<Vec<i8>>);
+pub struct I16Vector(pub Arc<Vec<i16>>);
+pub struct I32Vector(pub Arc<Vec<i32>>);
+pub struct I64Vector(pub Arc<Vec<i64>>);
+pub struct F32Vector(pub Arc<Vec<f32>>);
+pub struct F64Vector(pub Arc<Vec<f64>>);
+pub struct NaturalVector(pub Arc<Vec<Natural>>);
+pub struct IntegerVector(pub Arc<Vec<malachite::Integer>>);
+pub struct RationalVector(pub Arc<Vec<malachite::Rational>>);
+pub struct BigDecimalVector(pub Arc<Vec<BigDecimal>>);
+pub struct ReferenceVector(pub Arc<Vec<Reference>>);
+pub struct VectorVector(pub Arc<Vec<Vector>>);
+pub struct TupleVector(pub Arc<Vec<Tuple>>);
+pub struct FunctionVector(pub Arc<Vec<Function>>);
+pub struct StringVector(pub Arc<Vec<Arc<str>>>);
+pub struct BooleanVector(pub Arc<Vec<bool>>);
+pub struct CharacterVector(pub Arc<Vec<char>>);
+pub struct ProductVector(pub Arc<Vec<ProductType>>);
+pub struct SumVector(pub Arc<Vec<SumType>>);
+pub struct AnyVector(pub Arc<Vec<Value>>);
 
 
 vector_chunk!(U8, U8Vector);
@@ -76,8 +87,15 @@ vector_chunk!(F64, F64Vector);
 vector_chunk!(Natural, NaturalVector);
 vector_chunk!(Integer, IntegerVector);
 vector_chunk!(Rational, RationalVector);
+vector_chunk!(BigDecimal, BigDecimalVector);
 vector_chunk!(Reference, ReferenceVector);
 vector_chunk!(Vector, VectorVector);
 vector_chunk!(Tuple, TupleVector);
 vector_chunk!(Function, FunctionVector);
+vector_chunk!(String, StringVector);
+vector_chunk!(Boolean, BooleanVector);
+vector_chunk!(Character, CharacterVector);
+vector_chunk!(Product, ProductVector);
+vector_chunk!(Sum, SumVector);
+vector_chunk!(Any, AnyVector);
 
@@ -0,0 +1,92 @@
+use std::str::FromStr;
+use malachite::num::arithmetic::traits::{Ceiling, Floor};
+use malachite::num::conversion::traits::RoundingFrom;
+use malachite::rounding_modes::RoundingMode;
+use malachite::Rational;
+use crate::machine::Fault;
+use crate::program::module::Module;
+use crate::stdlib::{add, empty_module, integer, string};
+use crate::value::big_decimal::BigDecimal;
+use crate::value::decimal::{Decimal, DecimalType};
+use crate::value::integer::Integer;
+use crate::value::Value;
+
+/// Builds `std::convert`, conversions between strings, characters and numbers of any type.
+pub fn module() -> Module {
+    let mut module = empty_module("convert");
+
+    add(&mut module, "to_string", &["value"], |arguments| {
+        Ok(vec![Value::String(arguments[0].to_string().into())])
+    });
+    add(&mut module, "parse_integer", &["string"], |arguments| {
+        let integer = malachite::Integer::from_str(string(&arguments[0])?.trim()).map_err(|_| Fault::InvalidString)?;
+        Ok(vec![Value::Integer(Integer::Integer(integer))])
+    });
+    add(&mut module, "parse_decimal", &["string"], |arguments| {
+        let decimal = f64::from_str(string(&arguments[0])?.trim()).map_err(|_| Fault::InvalidString)?;
+        Ok(vec![Value::Decimal(Decimal::F64(decimal))])
+    });
+    add(&mut module, "parse_big_decimal", &["string"], |arguments| {
+        Ok(vec![Value::Decimal(Decimal::BigDecimal(BigDecimal::from_str(string(&arguments[0])?.trim())?))])
+    });
+    add(&mut module, "to_big_decimal", &["number"], |arguments| {
+        let result = match &arguments[0] {
+            Value::Integer(integer) => Decimal::BigDecimal(BigDecimal::from(integer.to_big_integer())),
+            Value::Decimal(decimal) => decimal.clone().convert(&DecimalType::BigDecimal)?,
+            _ => return Err(Fault::TypeMismatch),
+        };
+        Ok(vec![Value::Decimal(result)])
+    });
+    add(&mut module, "to_f64", &["number"], |arguments| {
+        let result = match &arguments[0] {
+            Value::Integer(integer) => f64::rounding_from(&integer.to_big_integer(), RoundingMode::Nearest).0,
+            Value::Decimal(Decimal::F32(value)) => *value as f64,
+            Value::Decimal(Decimal::F64(value)) => *value,
+            Value::Decimal(Decimal::Rational(value)) => f64::rounding_from(value, RoundingMode::Nearest).0,
+            Value::Decimal(Decimal::BigDecimal(value)) => f64::rounding_from(&value.to_rational(), RoundingMode::Nearest).0,
+            _ => return Err(Fault::TypeMismatch),
+        };
+        Ok(vec![Value::Decimal(Decimal::F64(result))])
+    });
+    add(&mut module, "to_rational", &["number"], |arguments| {
+        let result = match &arguments[0] {
+            Value::Integer(integer) => Rational::from(integer.to_big_integer()),
+            Value::Decimal(Decimal::F32(value)) => Rational::try_from(*value).map_err(|_| not_finite(*value as f64))?,
+            Value::Decimal(Decimal::F64(value)) => Rational::try_from(*value).map_err(|_| not_finite(*value))?,
+            Value::Decimal(Decimal::Rational(value)) => value.clone(),
+            Value::Decimal(Decimal::BigDecimal(value)) => value.to_rational(),
+            _ => return Err(Fault::TypeMismatch),
+        };
+        Ok(vec![Value::Decimal(Decimal::Rational(result))])
+    });
+    add(&mut module, "to_integer", &["number"], |arguments| {
+        let rational = match &arguments[0] {
+            Value::Integer(integer) => return Ok(vec![Value::Integer(Integer::Integer(integer.to_big_integer()))]),
+            Value::Decimal(Decimal::F32(value)) => Rational::try_from(*value).map_err(|_| not_finite(*value as f64))?,
+            Value::Decimal(Decimal::F64(value)) => Rational::try_from(*value).map_err(|_| not_finite(*value))?,
+            Value::Decimal(Decimal::Rational(value)) => value.clone(),
+            Value::Decimal(Decimal::BigDecimal(value)) => return Ok(vec![Value::Integer(Integer::Integer(value.to_integer()))]),
+            _ => return Err(Fault::TypeMismatch),
+        };
+        // Truncates towards zero like the casts between fixed width types.
+        let integer = if rational < 0 { rational.ceiling() } else { rational.floor() };
+        Ok(vec![Value::Integer(Integer::Integer(integer))])
+    });
+    add(&mut module, "char_code", &["character"], |arguments| {
+        match &arguments[0] {
+            Value::Character(character) => Ok(vec![Value::Integer(Integer::U32(*character as u32))]),
+            _ => Err(Fault::TypeMismatch),
+        }
+    });
+    add(&mut module, "from_char_code", &["code"], |arguments| {
+        let code = u32::try_from(&integer(&arguments[0])?.to_big_integer()).map_err(|_| Fault::OutOfBounds)?;
+        let character = char::from_u32(code).ok_or(Fault::OutOfBounds)?;
+        Ok(vec![Value::Character(character)])
+    });
+
+    module
+}
+
+fn not_finite(value: f64) -> Fault {
+    Fault::InvalidOperation(format!("Cannot convert {} to an exact number", value))
+}
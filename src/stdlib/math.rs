@@ -0,0 +1,142 @@
+use malachite::num::arithmetic::traits::{Abs, Ceiling, Floor};
+use malachite::num::conversion::traits::RoundingFrom;
+use malachite::rounding_modes::RoundingMode;
+use malachite::Rational;
+use crate::machine::Fault;
+use crate::program::module::Module;
+use crate::stdlib::{add, decimal, empty_module};
+use crate::value::big_decimal::BigDecimal;
+use crate::value::decimal::{Decimal, Rounding};
+use crate::value::Value;
+
+/// Builds `std::math`.
+///
+/// Transcendental functions keep `F32` and `F64` arguments in their type. `Rational` arguments
+/// are rounded to the nearest `F64` first since the results are irrational anyway.
+/// Rounding functions and `abs` are exact for every decimal type. Rounding a `BigDecimal` gives it a scale of 0.
+pub fn module() -> Module {
+    let mut module = empty_module("math");
+
+    macro_rules! unary {
+        ($name:expr, $method:ident) => {
+            add(&mut module, $name, &["x"], |arguments| {
+                let result = match decimal(&arguments[0])? {
+                    Decimal::F32(x) => Decimal::F32(x.$method()),
+                    Decimal::F64(x) => Decimal::F64(x.$method()),
+                    Decimal::Rational(x) => Decimal::F64(to_f64(x).$method()),
+                    Decimal::BigDecimal(x) => Decimal::F64(to_f64(&x.to_rational()).$method()),
+                };
+                Ok(vec![Value::Decimal(result)])
+            });
+        };
+    }
+
+    unary!("sqrt", sqrt);
+    unary!("cbrt", cbrt);
+    unary!("sin", sin);
+    unary!("cos", cos);
+    unary!("tan", tan);
+    unary!("asin", asin);
+    unary!("acos", acos);
+    unary!("atan", atan);
+    unary!("sinh", sinh);
+    unary!("cosh", cosh);
+    unary!("tanh", tanh);
+    unary!("exp", exp);
+    unary!("ln", ln);
+    unary!("log2", log2);
+    unary!("log10", log10);
+
+    macro_rules! binary {
+        ($name:expr, $arguments:expr, $method:ident) => {
+            add(&mut module, $name, $arguments, |arguments| {
+                let result = match (decimal(&arguments[0])?, decimal(&arguments[1])?) {
+                    (Decimal::F32(x), Decimal::F32(y)) => Decimal::F32(x.$method(*y)),
+                    (x, y) => Decimal::F64(decimal_to_f64(x).$method(decimal_to_f64(y))),
+                };
+                Ok(vec![Value::Decimal(result)])
+            });
+        };
+    }
+
+    binary!("atan2", &["y", "x"], atan2);
+    binary!("hypot", &["x", "y"], hypot);
+    binary!("log", &["x", "base"], log);
+
+    macro_rules! rounding {
+        ($name:expr, $float:ident, $rational:expr, $big_decimal:expr) => {
+            add(&mut module, $name, &["x"], |arguments| {
+                let result = match decimal(&arguments[0])? {
+                    Decimal::F32(x) => Decimal::F32(x.$float()),
+                    Decimal::F64(x) => Decimal::F64(x.$float()),
+                    Decimal::Rational(x) => Decimal::Rational($rational(x)),
+                    Decimal::BigDecimal(x) => Decimal::BigDecimal($big_decimal(x)?),
+                };
+                Ok(vec![Value::Decimal(result)])
+            });
+        };
+    }
+
+    rounding!("floor", floor, |x: &Rational| Rational::from(x.floor()), |x: &BigDecimal| x.with_scale(0, Rounding::Floor));
+    rounding!("ceil", ceil, |x: &Rational| Rational::from(x.ceiling()), |x: &BigDecimal| x.with_scale(0, Rounding::Ceiling));
+    rounding!("trunc", trunc, truncate, |x: &BigDecimal| x.with_scale(0, Rounding::Truncate));
+    rounding!("round", round, round, |x: &BigDecimal| x.with_scale(0, Rounding::HalfUp));
+    rounding!("abs", abs, |x: &Rational| x.abs(), |x: &BigDecimal| Ok::<_, Fault>(x.abs()));
+
+    add(&mut module, "pi", &[], |_| Ok(vec![Value::Decimal(Decimal::F64(std::f64::consts::PI))]));
+    add(&mut module, "e", &[], |_| Ok(vec![Value::Decimal(Decimal::F64(std::f64::consts::E))]));
+    add(&mut module, "is_nan", &["x"], |arguments| {
+        let result = match decimal(&arguments[0])? {
+            Decimal::F32(x) => x.is_nan(),
+            Decimal::F64(x) => x.is_nan(),
+            Decimal::Rational(_) | Decimal::BigDecimal(_) => false,
+        };
+        Ok(vec![Value::Boolean(result)])
+    });
+    add(&mut module, "min", &["x", "y"], |arguments| min_max(arguments, true));
+    add(&mut module, "max", &["x", "y"], |arguments| min_max(arguments, false));
+
+    module
+}
+
+fn to_f64(value: &Rational) -> f64 {
+    f64::rounding_from(value, RoundingMode::Nearest).0
+}
+
+fn decimal_to_f64(value: &Decimal) -> f64 {
+    match value {
+        Decimal::F32(value) => *value as f64,
+        Decimal::F64(value) => *value,
+        Decimal::Rational(value) => to_f64(value),
+        Decimal::BigDecimal(value) => to_f64(&value.to_rational()),
+    }
+}
+
+fn truncate(value: &Rational) -> Rational {
+    if *value < 0 {
+        Rational::from(value.ceiling())
+    } else {
+        Rational::from(value.floor())
+    }
+}
+
+/// Rounds half way cases away from zero, like `f64::round`.
+fn round(value: &Rational) -> Rational {
+    let half = Rational::from_signeds(1, 2);
+    if *value < 0 {
+        -Rational::from((-value + half).floor())
+    } else {
+        Rational::from((value + half).floor())
+    }
+}
+
+fn min_max(arguments: &[Value], min: bool) -> Result<Vec<Value>, Fault> {
+    let x = decimal(&arguments[0])?;
+    let y = decimal(&arguments[1])?;
+    if std::mem::discriminant(x) != std::mem::discriminant(y) {
+        return Err(Fault::TypeMismatch);
+    }
+    let ordering = x.partial_cmp(y).ok_or(Fault::TypeMismatch)?;
+    let take_x = if min { ordering.is_le() } else { ordering.is_ge() };
+    Ok(vec![Value::Decimal(if take_x { x.clone() } else { y.clone() })])
+}
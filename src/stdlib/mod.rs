@@ -0,0 +1,80 @@
+//! The standard library: host functions that ship with the VM.
+//!
+//! Everything lives in the `std` sub module, so once it is loaded programs call
+//! `std::math::sqrt` or `std::string::upper` like any other function.
+
+pub mod math;
+pub mod number;
+pub mod string;
+pub mod vector;
+pub mod convert;
+
+use std::collections::HashMap;
+use crate::machine::Fault;
+use crate::program::host::HostFunction;
+use crate::program::module::Module;
+use crate::value::decimal::Decimal;
+use crate::value::integer::Integer;
+use crate::value::vector::Vector;
+use crate::value::Value;
+
+/// Builds the `std` module with all of its sub modules.
+pub fn module() -> Module {
+    let mut std = empty_module("std");
+    std.add_module("math", math::module());
+    std.add_module("number", number::module());
+    std.add_module("string", string::module());
+    std.add_module("vector", vector::module());
+    std.add_module("convert", convert::module());
+    std
+}
+
+/// Mounts the standard library as the `std` sub module of `module`.
+pub fn load(module: &mut Module) {
+    module.add_module("std", self::module());
+}
+
+fn empty_module(name: &str) -> Module {
+    Module::new(name, HashMap::new(), Vec::new(), HashMap::new())
+}
+
+/// Registers a host function that doesn't need access to the core.
+fn add<F>(module: &mut Module, name: &str, argument_names: &[&str], function: F)
+where
+    F: Fn(&[Value]) -> Result<Vec<Value>, Fault> + Send + Sync + 'static,
+{
+    module.add_host_function(name, HostFunction::new(argument_names, move |_, arguments| function(arguments)));
+}
+
+fn integer(value: &Value) -> Result<&Integer, Fault> {
+    match value {
+        Value::Integer(integer) => Ok(integer),
+        _ => Err(Fault::NotAnInteger),
+    }
+}
+
+fn decimal(value: &Value) -> Result<&Decimal, Fault> {
+    match value {
+        Value::Decimal(decimal) => Ok(decimal),
+        _ => Err(Fault::TypeMismatch),
+    }
+}
+
+fn string(value: &Value) -> Result<&str, Fault> {
+    match value {
+        Value::String(string) => Ok(string),
+        _ => Err(Fault::InvalidString),
+    }
+}
+
+fn vector(value: &Value) -> Result<&Vector, Fault> {
+    match value {
+        Value::Vector(vector) => Ok(vector),
+        _ => Err(Fault::NotAVector),
+    }
+}
+
+/// Reads a non negative integer of any type as an index or a count.
+fn index(value: &Value) -> Result<usize, Fault> {
+    usize::try_from(&integer(value)?.to_big_integer()).map_err(|_| Fault::OutOfBounds)
+}
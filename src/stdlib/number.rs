@@ -0,0 +1,39 @@
+use crate::program::module::Module;
+use crate::stdlib::{add, empty_module, integer};
+use crate::value::Value;
+
+/// Builds `std::number`, integer number theory for integers of every type.
+///
+/// The work is done on arbitrary precision values and the result has the type of the first
+/// argument, or of the modulus for `pow_mod` and `mod_inverse`, faulting with `Overflow` if it doesn't fit.
+pub fn module() -> Module {
+    let mut module = empty_module("number");
+
+    add(&mut module, "gcd", &["a", "b"], |arguments| {
+        Ok(vec![Value::Integer(integer(&arguments[0])?.clone().gcd(integer(&arguments[1])?)?)])
+    });
+    add(&mut module, "lcm", &["a", "b"], |arguments| {
+        Ok(vec![Value::Integer(integer(&arguments[0])?.clone().lcm(integer(&arguments[1])?)?)])
+    });
+    add(&mut module, "factorial", &["n"], |arguments| {
+        Ok(vec![Value::Integer(integer(&arguments[0])?.clone().factorial()?)])
+    });
+    add(&mut module, "binomial", &["n", "k"], |arguments| {
+        Ok(vec![Value::Integer(integer(&arguments[0])?.clone().binomial(integer(&arguments[1])?)?)])
+    });
+    add(&mut module, "isqrt", &["n"], |arguments| {
+        Ok(vec![Value::Integer(integer(&arguments[0])?.clone().isqrt()?)])
+    });
+    add(&mut module, "pow_mod", &["base", "exponent", "modulus"], |arguments| {
+        let result = integer(&arguments[0])?.pow_mod(integer(&arguments[1])?, integer(&arguments[2])?)?;
+        Ok(vec![Value::Integer(result)])
+    });
+    add(&mut module, "mod_inverse", &["value", "modulus"], |arguments| {
+        Ok(vec![Value::Integer(integer(&arguments[0])?.mod_inverse(integer(&arguments[1])?)?)])
+    });
+    add(&mut module, "is_prime", &["n"], |arguments| {
+        Ok(vec![Value::Boolean(integer(&arguments[0])?.is_prime())])
+    });
+
+    module
+}
@@ -0,0 +1,93 @@
+use crate::machine::Fault;
+use crate::program::module::Module;
+use crate::stdlib::{add, empty_module, index, string};
+use crate::value::integer::Integer;
+use crate::value::tuple::Tuple;
+use crate::value::Value;
+
+/// Builds `std::string`. Lengths and positions count characters, not bytes.
+pub fn module() -> Module {
+    let mut module = empty_module("string");
+
+    add(&mut module, "length", &["string"], |arguments| {
+        let length = string(&arguments[0])?.chars().count();
+        Ok(vec![Value::Integer(Integer::U64(length as u64))])
+    });
+
+    macro_rules! map {
+        ($name:expr, $method:ident) => {
+            add(&mut module, $name, &["string"], |arguments| {
+                Ok(vec![Value::String(string(&arguments[0])?.$method().into())])
+            });
+        };
+    }
+
+    map!("upper", to_uppercase);
+    map!("lower", to_lowercase);
+    map!("trim", trim);
+    map!("trim_start", trim_start);
+    map!("trim_end", trim_end);
+
+    macro_rules! test {
+        ($name:expr, $method:ident) => {
+            add(&mut module, $name, &["string", "pattern"], |arguments| {
+                let result = string(&arguments[0])?.$method(string(&arguments[1])?);
+                Ok(vec![Value::Boolean(result)])
+            });
+        };
+    }
+
+    test!("contains", contains);
+    test!("starts_with", starts_with);
+    test!("ends_with", ends_with);
+
+    add(&mut module, "reverse", &["string"], |arguments| {
+        Ok(vec![Value::String(string(&arguments[0])?.chars().rev().collect::<String>().into())])
+    });
+    add(&mut module, "repeat", &["string", "count"], |arguments| {
+        let (string, count) = (string(&arguments[0])?, index(&arguments[1])?);
+        let length = string.len().checked_mul(count).ok_or(Fault::OutOfMemory)?;
+        let mut result = String::new();
+        result.try_reserve_exact(length).map_err(|_| Fault::OutOfMemory)?;
+        while result.len() < length {
+            result.push_str(string);
+        }
+        Ok(vec![Value::String(result.into())])
+    });
+    add(&mut module, "replace", &["string", "from", "to"], |arguments| {
+        let result = string(&arguments[0])?.replace(string(&arguments[1])?, string(&arguments[2])?);
+        Ok(vec![Value::String(result.into())])
+    });
+    add(&mut module, "substring", &["string", "start", "end"], |arguments| {
+        let string = string(&arguments[0])?;
+        let (start, end) = (index(&arguments[1])?, index(&arguments[2])?);
+        if start > end || end > string.chars().count() {
+            return Err(Fault::OutOfBounds);
+        }
+        Ok(vec![Value::String(string.chars().skip(start).take(end - start).collect::<String>().into())])
+    });
+    add(&mut module, "char_at", &["string", "index"], |arguments| {
+        let character = string(&arguments[0])?.chars().nth(index(&arguments[1])?).ok_or(Fault::OutOfBounds)?;
+        Ok(vec![Value::Character(character)])
+    });
+    add(&mut module, "index_of", &["string", "pattern"], |arguments| {
+        let string = string(&arguments[0])?;
+        let position = match string.find(self::string(&arguments[1])?) {
+            Some(byte) => string[..byte].chars().count() as i64,
+            None => -1,
+        };
+        Ok(vec![Value::Integer(Integer::I64(position))])
+    });
+    add(&mut module, "split", &["string", "separator"], |arguments| {
+        let parts = string(&arguments[0])?
+            .split(string(&arguments[1])?)
+            .map(|part| Value::String(part.into()))
+            .collect::<Vec<_>>();
+        Ok(vec![Value::Tuple(Tuple::new(parts.into_boxed_slice()))])
+    });
+    add(&mut module, "concat", &["left", "right"], |arguments| {
+        Ok(vec![Value::String(format!("{}{}", string(&arguments[0])?, string(&arguments[1])?).into())])
+    });
+
+    module
+}
@@ -0,0 +1,50 @@
+use crate::machine::Fault;
+use crate::program::module::Module;
+use crate::stdlib::{add, empty_module, index, vector};
+use crate::value::integer::Integer;
+use crate::value::vector::Vector;
+use crate::value::vector_math::{dot, reduce, VectorReduction};
+use crate::value::Value;
+
+/// Builds `std::vector`.
+pub fn module() -> Module {
+    let mut module = empty_module("vector");
+
+    add(&mut module, "length", &["vector"], |arguments| {
+        Ok(vec![Value::Integer(Integer::U64(vector(&arguments[0])?.length() as u64))])
+    });
+    add(&mut module, "reverse", &["vector"], |arguments| {
+        let mut reversed = vector(&arguments[0])?.clone();
+        reversed.reverse();
+        Ok(vec![Value::Vector(reversed)])
+    });
+    add(&mut module, "slice", &["vector", "start", "end"], |arguments| {
+        let slice = vector(&arguments[0])?.slice(index(&arguments[1])?, index(&arguments[2])?)?;
+        Ok(vec![Value::Vector(slice)])
+    });
+    add(&mut module, "sum", &["vector"], |arguments| Ok(vec![reduce(vector(&arguments[0])?, VectorReduction::Sum)?]));
+    add(&mut module, "min", &["vector"], |arguments| Ok(vec![reduce(vector(&arguments[0])?, VectorReduction::Min)?]));
+    add(&mut module, "max", &["vector"], |arguments| Ok(vec![reduce(vector(&arguments[0])?, VectorReduction::Max)?]));
+    add(&mut module, "mean", &["vector"], |arguments| Ok(vec![reduce(vector(&arguments[0])?, VectorReduction::Mean)?]));
+    add(&mut module, "dot", &["left", "right"], |arguments| Ok(vec![dot(vector(&arguments[0])?, vector(&arguments[1])?)?]));
+    add(&mut module, "contains", &["vector", "value"], |arguments| {
+        let position = position(vector(&arguments[0])?, &arguments[1])?;
+        Ok(vec![Value::Boolean(position.is_some())])
+    });
+    add(&mut module, "index_of", &["vector", "value"], |arguments| {
+        let position = position(vector(&arguments[0])?, &arguments[1])?;
+        Ok(vec![Value::Integer(Integer::I64(position.map(|position| position as i64).unwrap_or(-1)))])
+    });
+
+    module
+}
+
+/// The first index holding an element equal to `value`. Values of different types are never equal.
+fn position(vector: &Vector, value: &Value) -> Result<Option<usize>, Fault> {
+    for index in 0..vector.length() {
+        if vector.get(index)? == *value {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
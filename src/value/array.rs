@@ -0,0 +1,251 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
+use crate::machine::Fault;
+use crate::stack::StackChunk;
+use crate::value::integer::Integer;
+use crate::value::tuple::Tuple;
+use crate::value::vector::{Vector, VectorType};
+use crate::value::vector_math;
+use crate::value::Value;
+
+/// An n-dimensional array over one contiguous buffer.
+///
+/// The element at `[i, j, ...]` is at `offset + i * strides[0] + j * strides[1] + ...` in the buffer.
+/// Transposing and slicing only change the shape, strides and offset, so they share the buffer with
+/// the original. Setting an element copies the buffer first if it is shared.
+#[derive(Clone)]
+pub struct Array {
+    data: Vector,
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+    offset: usize,
+}
+
+impl Array {
+
+    /// An array of zeros. The size in `typ` is ignored.
+    pub fn new(shape: Vec<usize>, typ: &VectorType) -> Result<Array, Fault> {
+        let data = Vector::new(element_count(&shape)?, typ)?;
+        Array::contiguous(data, shape)
+    }
+
+    /// Lays out a vector in row-major order. Its length has to match the shape.
+    pub fn from_vector(data: Vector, shape: Vec<usize>) -> Result<Array, Fault> {
+        check_size(data.length(), &shape)?;
+        Array::contiguous(data, shape)
+    }
+
+    /// Faults with `OutOfMemory` if a stride doesn't fit in a `usize`.
+    fn contiguous(data: Vector, shape: Vec<usize>) -> Result<Array, Fault> {
+        let mut strides = vec![1usize; shape.len()];
+        for axis in (0..shape.len().saturating_sub(1)).rev() {
+            strides[axis] = strides[axis + 1].checked_mul(shape[axis + 1]).ok_or(Fault::OutOfMemory)?;
+        }
+        Ok(Array {
+            data,
+            shape,
+            strides,
+            offset: 0,
+        })
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn get(&self, indices: &[usize]) -> Result<Value, Fault> {
+        self.data.get(self.position(indices)?)
+    }
+
+    pub fn set(&mut self, indices: &[usize], value: Value) -> Result<(), Fault> {
+        let position = self.position(indices)?;
+        self.data.set(position, value)
+    }
+
+    /// The same elements in a new shape with the same number of elements.
+    pub fn reshape(&self, shape: Vec<usize>) -> Result<Array, Fault> {
+        check_size(element_count(&self.shape)?, &shape)?;
+        Array::contiguous(self.to_vector(), shape)
+    }
+
+    /// Reverses the axes, so a matrix's rows become its columns.
+    pub fn transpose(&self) -> Array {
+        Array {
+            data: self.data.clone(),
+            shape: self.shape.iter().rev().copied().collect(),
+            strides: self.strides.iter().rev().copied().collect(),
+            offset: self.offset,
+        }
+    }
+
+    /// A view of the elements from `start` up to but not including `end` along `axis`.
+    pub fn slice(&self, axis: usize, start: usize, end: usize) -> Result<Array, Fault> {
+        if axis >= self.shape.len() || start > end || end > self.shape[axis] {
+            return Err(Fault::OutOfBounds);
+        }
+        let mut shape = self.shape.clone();
+        shape[axis] = end - start;
+        Ok(Array {
+            data: self.data.clone(),
+            shape,
+            strides: self.strides.clone(),
+            offset: self.offset + start * self.strides[axis],
+        })
+    }
+
+    /// The product of two matrices with the same element type, each element being a `vector_math::dot`.
+    pub fn matrix_multiply(&self, other: &Array) -> Result<Array, Fault> {
+        let (rows, inner, columns) = match (self.shape.as_slice(), other.shape.as_slice()) {
+            ([rows, inner], [other_inner, columns]) if inner == other_inner => (*rows, *inner, *columns),
+            _ => return Err(Fault::InvalidOperation(format!("Cannot multiply arrays of shapes {:?} and {:?}", self.shape, other.shape))),
+        };
+        let left = self.to_vector();
+        let right = other.transpose().to_vector();
+        let mut result = left.gather(&[]);
+        let columns_of_right = (0..columns)
+            .map(|column| right.slice(column * inner, (column + 1) * inner))
+            .collect::<Result<Vec<_>, Fault>>()?;
+        for row in 0..rows {
+            let row = left.slice(row * inner, (row + 1) * inner)?;
+            for column in columns_of_right.iter() {
+                result.push(vector_math::dot(&row, column)?)?;
+            }
+        }
+        Array::contiguous(result, vec![rows, columns])
+    }
+
+    /// The elements in row-major order.
+    pub fn to_vector(&self) -> Vector {
+        let mut positions = Vec::with_capacity(self.shape.iter().product());
+        let mut indices = vec![0; self.shape.len()];
+        if self.shape.contains(&0) {
+            return self.data.gather(&positions);
+        }
+        loop {
+            positions.push(self.offset + indices.iter().zip(&self.strides).map(|(index, stride)| index * stride).sum::<usize>());
+            // Counts up like an odometer, the last axis fastest.
+            let mut axis = self.shape.len();
+            loop {
+                if axis == 0 {
+                    return self.data.gather(&positions);
+                }
+                axis -= 1;
+                indices[axis] += 1;
+                if indices[axis] < self.shape[axis] {
+                    break;
+                }
+                indices[axis] = 0;
+            }
+        }
+    }
+
+    pub fn contains_function(&self) -> bool {
+        self.data.contains_function()
+    }
+
+    /// Only looks at the elements in view, since those are the ones compared.
+    pub fn contains_nan(&self) -> bool {
+        self.to_vector().contains_nan()
+    }
+
+    pub fn into_chunk(self) -> Box<dyn StackChunk> {
+        Box::new(self)
+    }
+
+    fn position(&self, indices: &[usize]) -> Result<usize, Fault> {
+        if indices.len() != self.shape.len() {
+            return Err(Fault::InvalidOperation(format!("Expected {} indices, found {}", self.shape.len(), indices.len())));
+        }
+        let mut position = self.offset;
+        for ((index, size), stride) in indices.iter().zip(&self.shape).zip(&self.strides) {
+            if index >= size {
+                return Err(Fault::OutOfBounds);
+            }
+            position += index * stride;
+        }
+        Ok(position)
+    }
+
+    /// Formats the elements from `position` on along `axis` and the axes after it.
+    fn format(&self, f: &mut std::fmt::Formatter<'_>, axis: usize, position: usize, debug: bool) -> std::fmt::Result {
+        if axis == self.shape.len() {
+            let value = self.data.get(position).map_err(|_| std::fmt::Error)?;
+            return if debug { write!(f, "{:?}", value) } else { write!(f, "{}", value) };
+        }
+        write!(f, "[")?;
+        for index in 0..self.shape[axis] {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            self.format(f, axis + 1, position + index * self.strides[axis], debug)?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// The number of elements in an array of `shape`. Faults with `OutOfMemory` if it doesn't fit in a `usize`.
+fn element_count(shape: &[usize]) -> Result<usize, Fault> {
+    if shape.contains(&0) {
+        return Ok(0);
+    }
+    shape.iter().try_fold(1usize, |count, size| count.checked_mul(*size)).ok_or(Fault::OutOfMemory)
+}
+
+fn check_size(length: usize, shape: &[usize]) -> Result<(), Fault> {
+    if length != element_count(shape)? {
+        return Err(Fault::InvalidOperation(format!("Cannot lay out {} elements in shape {:?}", length, shape)));
+    }
+    Ok(())
+}
+
+/// Reads a tuple of indices or sizes. Negative integers are out of bounds.
+pub fn indices(tuple: &Tuple) -> Result<Vec<usize>, Fault> {
+    tuple.data.iter()
+        .map(|value| match value {
+            Value::Integer(index) => index.clone().to_usize().ok_or(Fault::OutOfBounds),
+            _ => Err(Fault::NotAnInteger),
+        })
+        .collect()
+}
+
+/// The shape as a tuple of `U64`s.
+pub fn shape_tuple(shape: &[usize]) -> Tuple {
+    Tuple::new(shape.iter().map(|size| Value::Integer(Integer::U64(*size as u64))).collect())
+}
+
+/// Arrays are equal if they have the same shape and elements, however they are laid out.
+impl PartialEq for Array {
+    fn eq(&self, other: &Array) -> bool {
+        self.shape == other.shape && self.to_vector() == other.to_vector()
+    }
+}
+
+/// Arrays of the same shape are ordered by their elements in row-major order, others are unordered.
+impl PartialOrd for Array {
+    fn partial_cmp(&self, other: &Array) -> Option<Ordering> {
+        if self.shape != other.shape {
+            return None;
+        }
+        self.to_vector().partial_cmp(&other.to_vector())
+    }
+}
+
+impl Hash for Array {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.shape.hash(state);
+        self.to_vector().hash(state);
+    }
+}
+
+impl Display for Array {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.format(f, 0, self.offset, false)
+    }
+}
+
+impl Debug for Array {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.format(f, 0, self.offset, true)
+    }
+}
@@ -0,0 +1,280 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use malachite::num::arithmetic::traits::{Abs, Pow, UnsignedAbs};
+use malachite::num::basic::traits::Zero;
+use malachite::num::logic::traits::SignificantBits;
+use malachite::{Natural, Rational};
+use crate::machine::Fault;
+use crate::value::decimal::Rounding;
+use crate::value::integer::check_big_bits;
+
+/// The smallest scale of a quotient made by the `/` operator, which has to round somewhere.
+pub const DIVISION_SCALE: u64 = 16;
+/// The most digits a number may have after the decimal point. Operations that would need more
+/// fault with `Overflow`, except multiplication, which rounds to this many digits.
+pub const MAX_SCALE: u64 = 1 << 20;
+
+/// A base 10 number with a fixed number of digits after the decimal point, like SQL `NUMERIC`.
+/// The value is `unscaled / 10^scale`, so `12.50` is stored as 1250 with a scale of 2.
+///
+/// Addition, subtraction and multiplication are exact. Their results have the larger scale of the
+/// operands, or the sum of the scales for multiplication. Division rounds to a scale picked by the caller.
+/// The scale never exceeds `MAX_SCALE`, which keeps lining up two scales cheap.
+#[derive(Clone)]
+pub struct BigDecimal {
+    unscaled: malachite::Integer,
+    scale: u64,
+}
+
+impl BigDecimal {
+    /// Faults with `Overflow` if `scale` is above `MAX_SCALE`.
+    pub fn new(unscaled: malachite::Integer, scale: u64) -> Result<BigDecimal, Fault> {
+        if scale > MAX_SCALE {
+            return Err(Fault::Overflow);
+        }
+        Ok(BigDecimal { unscaled, scale })
+    }
+
+    pub fn zero() -> BigDecimal {
+        BigDecimal::from(malachite::Integer::ZERO)
+    }
+
+    pub fn unscaled(&self) -> &malachite::Integer {
+        &self.unscaled
+    }
+
+    pub fn scale(&self) -> u64 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.unscaled == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.unscaled < 0
+    }
+
+    pub fn abs(&self) -> BigDecimal {
+        BigDecimal { unscaled: (&self.unscaled).abs(), scale: self.scale }
+    }
+
+    /// The same value with `scale` digits after the decimal point. Scales can always grow
+    /// exactly up to `MAX_SCALE`, shrinking them rounds with `rounding`.
+    pub fn with_scale(&self, scale: u64, rounding: Rounding) -> Result<BigDecimal, Fault> {
+        if scale > MAX_SCALE {
+            return Err(Fault::Overflow);
+        }
+        if scale >= self.scale {
+            BigDecimal::new(&self.unscaled * power_of_ten(scale - self.scale), scale)
+        } else {
+            BigDecimal::rounded(&self.to_rational(), scale as i64, rounding)
+        }
+    }
+
+    /// Rounds a rational to `digits` digits after the decimal point, or to a multiple of a power
+    /// of ten if it is negative. The result has a scale of `digits`, or 0 if it is negative.
+    /// Faults with `Overflow` if `digits` is further than `MAX_SCALE` from zero.
+    pub fn rounded(value: &Rational, digits: i64, rounding: Rounding) -> Result<BigDecimal, Fault> {
+        if digits.unsigned_abs() > MAX_SCALE {
+            return Err(Fault::Overflow);
+        }
+        let scale = Rational::from(10).pow(digits);
+        let rounded = rounding.round(value * &scale);
+        match u64::try_from(digits) {
+            Ok(digits) => BigDecimal::new(rounded, digits),
+            Err(_) => BigDecimal::new(rounded * power_of_ten(digits.unsigned_abs()), 0),
+        }
+    }
+
+    /// Converts a rational exactly, which is only possible if its denominator has no prime
+    /// factors other than 2 and 5 and the result fits in `MAX_SCALE` digits.
+    pub fn from_rational(value: &Rational) -> Option<BigDecimal> {
+        let mut denominator = value.to_denominator();
+        let (mut twos, mut fives) = (0u64, 0u64);
+        while (&denominator % Natural::from(2u32)) == 0u32 {
+            denominator >>= 1;
+            twos += 1;
+        }
+        while (&denominator % Natural::from(5u32)) == 0u32 {
+            denominator /= Natural::from(5u32);
+            fives += 1;
+        }
+        if denominator != 1u32 {
+            return None;
+        }
+        let scale = twos.max(fives);
+        if scale > MAX_SCALE {
+            return None;
+        }
+        let unscaled = value * Rational::from(power_of_ten(scale));
+        Some(BigDecimal { unscaled: malachite::Integer::try_from(unscaled).unwrap(), scale })
+    }
+
+    pub fn to_rational(&self) -> Rational {
+        Rational::from_integers(self.unscaled.clone(), power_of_ten(self.scale))
+    }
+
+    /// The integer part, rounded towards zero.
+    pub fn to_integer(&self) -> malachite::Integer {
+        &self.unscaled / power_of_ten(self.scale)
+    }
+
+    /// Divides by `rhs` and rounds the quotient to `digits` digits after the decimal point.
+    pub fn divide(&self, rhs: &BigDecimal, digits: i64, rounding: Rounding) -> Result<BigDecimal, Fault> {
+        if rhs.is_zero() {
+            return Err(Fault::DivisionByZero);
+        }
+        BigDecimal::rounded(&(self.to_rational() / rhs.to_rational()), digits, rounding)
+    }
+
+    /// The remainder of a division rounded towards zero, with the larger scale of the operands.
+    pub fn remainder(&self, rhs: &BigDecimal) -> Result<BigDecimal, Fault> {
+        if rhs.is_zero() {
+            return Err(Fault::DivisionByZero);
+        }
+        let (left, right, scale) = self.align(rhs);
+        Ok(BigDecimal { unscaled: left % right, scale })
+    }
+
+    /// Raises to the power of `exponent`. Positive powers are exact, negative ones are divisions
+    /// and round half to even to at least `DIVISION_SCALE` digits.
+    /// Faults with `Overflow` if the exact power would need more than `MAX_SCALE` digits, and with
+    /// `OutOfMemory` if its digits would take more than `MAX_BIG_BITS` bits.
+    pub fn pow(&self, exponent: i64) -> Result<BigDecimal, Fault> {
+        let magnitude = exponent.unsigned_abs();
+        let scale = self.scale.checked_mul(magnitude).ok_or(Fault::Overflow)?;
+        if self.unscaled.unsigned_abs_ref().significant_bits() > 1 {
+            check_big_bits(self.unscaled.unsigned_abs_ref().significant_bits().checked_mul(magnitude))?;
+        }
+        let power = BigDecimal::new((&self.unscaled).pow(magnitude), scale)?;
+        if exponent >= 0 {
+            return Ok(power);
+        }
+        let scale = self.scale.max(DIVISION_SCALE);
+        BigDecimal::from(malachite::Integer::from(1)).divide(&power, scale as i64, Rounding::HalfEven)
+    }
+
+    /// Both unscaled values at the larger scale, and that scale.
+    fn align(&self, rhs: &BigDecimal) -> (malachite::Integer, malachite::Integer, u64) {
+        let scale = self.scale.max(rhs.scale);
+        let left = &self.unscaled * power_of_ten(scale - self.scale);
+        let right = &rhs.unscaled * power_of_ten(scale - rhs.scale);
+        (left, right, scale)
+    }
+}
+
+fn power_of_ten(exponent: u64) -> malachite::Integer {
+    malachite::Integer::from(10).pow(exponent)
+}
+
+impl std::ops::Add for BigDecimal {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let (left, right, scale) = self.align(&rhs);
+        BigDecimal { unscaled: left + right, scale }
+    }
+}
+
+impl std::ops::Sub for BigDecimal {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let (left, right, scale) = self.align(&rhs);
+        BigDecimal { unscaled: left - right, scale }
+    }
+}
+
+/// Products that would have more than `MAX_SCALE` digits round half to even to `MAX_SCALE` digits.
+impl std::ops::Mul for BigDecimal {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let product = BigDecimal { unscaled: self.unscaled * rhs.unscaled, scale: self.scale + rhs.scale };
+        if product.scale <= MAX_SCALE {
+            return product;
+        }
+        let excess = Rational::from_integers(product.unscaled, power_of_ten(product.scale - MAX_SCALE));
+        BigDecimal { unscaled: Rounding::HalfEven.round(excess), scale: MAX_SCALE }
+    }
+}
+
+impl std::ops::Neg for BigDecimal {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        BigDecimal { unscaled: -self.unscaled, scale: self.scale }
+    }
+}
+
+impl From<malachite::Integer> for BigDecimal {
+    fn from(value: malachite::Integer) -> Self {
+        BigDecimal { unscaled: value, scale: 0 }
+    }
+}
+
+/// Numbers with different scales are equal if they have the same value, so `1.5 == 1.50`.
+impl PartialEq for BigDecimal {
+    fn eq(&self, other: &BigDecimal) -> bool {
+        let (left, right, _) = self.align(other);
+        left == right
+    }
+}
+
+impl PartialOrd for BigDecimal {
+    fn partial_cmp(&self, other: &BigDecimal) -> Option<Ordering> {
+        let (left, right, _) = self.align(other);
+        left.partial_cmp(&right)
+    }
+}
+
+/// Hashes the value rather than the representation, like `PartialEq`.
+impl Hash for BigDecimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_rational().hash(state);
+    }
+}
+
+/// Parses plain decimal notation like `-12.50`. The scale is the number of digits written after the point.
+impl FromStr for BigDecimal {
+    type Err = Fault;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match string.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, string.strip_prefix('+').unwrap_or(string)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let valid = |part: &str| part.chars().all(|character| character.is_ascii_digit());
+        if whole.len() + fraction.len() == 0 || !valid(whole) || !valid(fraction) {
+            return Err(Fault::InvalidString);
+        }
+        let unscaled = malachite::Integer::from_str(&format!("{}{}", whole, fraction)).unwrap();
+        let unscaled = if negative { -unscaled } else { unscaled };
+        BigDecimal::new(unscaled, fraction.len() as u64)
+    }
+}
+
+impl Display for BigDecimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = (&self.unscaled).unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let digits = if digits.len() <= scale { format!("{}{}", "0".repeat(scale + 1 - digits.len()), digits) } else { digits };
+        let sign = if self.is_negative() { "-" } else { "" };
+        if scale == 0 {
+            write!(f, "{}{}", sign, digits)
+        } else {
+            let (whole, fraction) = digits.split_at(digits.len() - scale);
+            write!(f, "{}{}.{}", sign, whole, fraction)
+        }
+    }
+}
+
+impl Debug for BigDecimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
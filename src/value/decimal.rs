@@ -1,25 +1,49 @@
 use std::alloc::Layout;
 use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
 use malachite::num::arithmetic::traits::Pow;
 use malachite::num::basic::traits::Zero;
+use malachite::num::conversion::traits::RoundingFrom;
+use malachite::num::logic::traits::SignificantBits;
+use malachite::rounding_modes::RoundingMode;
+use malachite::Rational;
+use crate::machine::Fault;
 use crate::stack::StackChunk;
-use crate::value::integer::{Integer, IntegerType};
+use crate::value::big_decimal::{BigDecimal, DIVISION_SCALE, MAX_SCALE};
+use crate::value::integer::{check_big_bits, Integer};
 use crate::value::{Value, ValueType};
 
+/// Floats have at most 1074 digits after the decimal point and are below 10^309, so rounding them
+/// to more digits than this either way gives the same result.
+const FLOAT_ROUNDING_DIGITS: i64 = 1100;
+
 #[derive(Debug,Clone, PartialEq, PartialOrd)]
 pub enum DecimalType {
     F32,
     F64,
     Rational,
+    BigDecimal,
 }
 
 impl DecimalType {
+    /// The type both operands of a mixed operation are converted to. Floats widen to `F64`, and since
+    /// a `Rational` can't be represented exactly by either float, mixing one with a float also gives `F64`.
+    /// Mixing the exact types gives a `Rational`, which can hold any `BigDecimal`.
+    pub fn promote(&self, other: &DecimalType) -> DecimalType {
+        match (self, other) {
+            (DecimalType::F32, DecimalType::F32) => DecimalType::F32,
+            (DecimalType::BigDecimal, DecimalType::BigDecimal) => DecimalType::BigDecimal,
+            (DecimalType::Rational | DecimalType::BigDecimal, DecimalType::Rational | DecimalType::BigDecimal) => DecimalType::Rational,
+            _ => DecimalType::F64,
+        }
+    }
 
     pub fn get_array_layout(&self, size: usize) -> Layout {
         match self {
             DecimalType::F32 => Layout::array::<f32>(size).unwrap(),
             DecimalType::F64 => Layout::array::<f64>(size).unwrap(),
             DecimalType::Rational => Layout::array::<malachite::Rational>(size).unwrap(),
+            DecimalType::BigDecimal => Layout::array::<BigDecimal>(size).unwrap(),
         }
     }
 }
@@ -30,6 +54,50 @@ impl Display for DecimalType {
             DecimalType::F32 => write!(f, "f32"),
             DecimalType::F64 => write!(f, "f64"),
             DecimalType::Rational => write!(f, "rational"),
+            DecimalType::BigDecimal => write!(f, "big_decimal"),
+        }
+    }
+}
+
+/// Which way a decimal is rounded to a number of digits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    Floor,
+    Ceiling,
+    /// Round to the nearest value and half way cases to the even one.
+    HalfEven,
+    /// Round to the nearest value and half way cases away from zero, like SQL `ROUND`.
+    HalfUp,
+    /// Round towards zero.
+    Truncate,
+}
+
+impl Rounding {
+    /// Rounds `value` to an integer.
+    pub fn round(self, value: Rational) -> malachite::Integer {
+        let mode = match self {
+            Rounding::Floor => RoundingMode::Floor,
+            Rounding::Ceiling => RoundingMode::Ceiling,
+            Rounding::HalfEven => RoundingMode::Nearest,
+            Rounding::Truncate => RoundingMode::Down,
+            Rounding::HalfUp => {
+                let half = Rational::from_signeds(1, 2);
+                let rounded = if value < 0 { value - half } else { value + half };
+                return malachite::Integer::rounding_from(rounded, RoundingMode::Down).0;
+            }
+        };
+        malachite::Integer::rounding_from(value, mode).0
+    }
+}
+
+impl Display for Rounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rounding::Floor => write!(f, "floor"),
+            Rounding::Ceiling => write!(f, "ceiling"),
+            Rounding::HalfEven => write!(f, "half_even"),
+            Rounding::HalfUp => write!(f, "half_up"),
+            Rounding::Truncate => write!(f, "truncate"),
         }
     }
 }
@@ -39,26 +107,32 @@ pub enum Decimal {
     F32(f32),
     F64(f64),
     Rational(malachite::Rational),
+    BigDecimal(BigDecimal),
 }
 
 
 impl Decimal {
 
-    pub fn cast(self, into_type: ValueType) -> Value {
-        match into_type {
-            ValueType::Decimal(DecimalType::F32) => Value::Decimal(Decimal::F32(self.into())),
-            ValueType::Decimal(DecimalType::F64) => Value::Decimal(Decimal::F64(self.into())),
-            ValueType::Boolean => Value::Boolean(self.into()),
-            ValueType::Integer(IntegerType::I8) => Value::Integer(Integer::I8(self.into())),
-            ValueType::Integer(IntegerType::I16) => Value::Integer(Integer::I16(self.into())),
-            ValueType::Integer(IntegerType::I32) => Value::Integer(Integer::I32(self.into())),
-            ValueType::Integer(IntegerType::I64) => Value::Integer(Integer::I64(self.into())),
-            ValueType::Integer(IntegerType::U8) => Value::Integer(Integer::U8(self.into())),
-            ValueType::Integer(IntegerType::U16) => Value::Integer(Integer::U16(self.into())),
-            ValueType::Integer(IntegerType::U32) => Value::Integer(Integer::U32(self.into())),
-            ValueType::Integer(IntegerType::U64) => Value::Integer(Integer::U64(self.into())),
-            x => panic!("Cannot cast decimal to {:?}", x),
-        }
+    /// Converts to a decimal or integer type, or to a boolean that is true for anything but zero.
+    /// Integers are truncated towards zero and fault with `Overflow` or `Underflow` if they don't fit.
+    /// Faults with `TypeMismatch` for any other type.
+    pub fn cast(self, into_type: ValueType) -> Result<Value, Fault> {
+        let result = match into_type {
+            ValueType::Decimal(typ) => Value::Decimal(self.convert(&typ)?),
+            ValueType::Integer(typ) => {
+                let value = match self {
+                    Decimal::F32(value) => Rational::try_from(value).map_err(|_| not_finite(value))?,
+                    Decimal::F64(value) => Rational::try_from(value).map_err(|_| not_finite(value))?,
+                    Decimal::Rational(value) => value,
+                    Decimal::BigDecimal(value) => value.to_rational(),
+                };
+                let truncated = malachite::Integer::rounding_from(&value, RoundingMode::Down).0;
+                Value::Integer(Integer::of_type(typ, truncated)?)
+            }
+            ValueType::Boolean => Value::Boolean(!self.is_zero()),
+            _ => return Err(Fault::TypeMismatch),
+        };
+        Ok(result)
     }
 
     pub fn is_zero(&self) -> bool {
@@ -66,6 +140,7 @@ impl Decimal {
             Decimal::F32(value) => *value == 0.0,
             Decimal::F64(value) => *value == 0.0,
             Decimal::Rational(value) => value == &malachite::Rational::ZERO,
+            Decimal::BigDecimal(value) => value.is_zero(),
         }
     }
 
@@ -74,6 +149,7 @@ impl Decimal {
             Decimal::F32(value) => *value < 0.0,
             Decimal::F64(value) => *value < 0.0,
             Decimal::Rational(value) => value < &malachite::Rational::ZERO,
+            Decimal::BigDecimal(value) => value.is_negative(),
         }
     }
 
@@ -82,38 +158,183 @@ impl Decimal {
             Decimal::F32(value) => Box::new(value),
             Decimal::F64(value) => Box::new(value),
             Decimal::Rational(value) => Box::new(value),
+            Decimal::BigDecimal(value) => Box::new(value),
         }
     }
 
-    pub fn powd(self, exponent: Self) -> Self {
-        match (self, exponent) {
-            (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left.powf(right)),
-            (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left.powf(right)),
-            (Decimal::F32(left), Decimal::F64(right)) => Decimal::F64((left as f64).powf(right)),
-            (Decimal::F64(left), Decimal::F32(right)) => Decimal::F64(left.powf(right as f64)),
-            (x, y) => panic!("Cannot raise {:?} to {:?}", x, y),
+    pub fn get_type(&self) -> DecimalType {
+        match self {
+            Decimal::F32(_) => DecimalType::F32,
+            Decimal::F64(_) => DecimalType::F64,
+            Decimal::Rational(_) => DecimalType::Rational,
+            Decimal::BigDecimal(_) => DecimalType::BigDecimal,
+        }
+    }
+
+    /// Converts the decimal to `typ`. Floats become `Rational`s exactly, which faults for NaN and
+    /// the infinities, and `Rational`s are rounded to the nearest float.
+    ///
+    /// Floats become `BigDecimal`s through their shortest decimal representation, so 0.1 gives 0.1 and not
+    /// the exact value of the float. `Rational`s only convert if they have a finite decimal representation.
+    pub fn convert(self, typ: &DecimalType) -> Result<Decimal, Fault> {
+        let result = match (self, typ) {
+            (Decimal::F32(value), DecimalType::F64) => Decimal::F64(value as f64),
+            (Decimal::F64(value), DecimalType::F32) => Decimal::F32(value as f32),
+            (Decimal::F32(value), DecimalType::Rational) => Decimal::Rational(Rational::try_from(value).map_err(|_| not_finite(value))?),
+            (Decimal::F64(value), DecimalType::Rational) => Decimal::Rational(Rational::try_from(value).map_err(|_| not_finite(value))?),
+            (Decimal::Rational(value), DecimalType::F32) => Decimal::F32(f32::rounding_from(&value, RoundingMode::Nearest).0),
+            (Decimal::Rational(value), DecimalType::F64) => Decimal::F64(f64::rounding_from(&value, RoundingMode::Nearest).0),
+            (Decimal::F32(value), DecimalType::BigDecimal) if value.is_finite() => Decimal::BigDecimal(value.to_string().parse()?),
+            (Decimal::F64(value), DecimalType::BigDecimal) if value.is_finite() => Decimal::BigDecimal(value.to_string().parse()?),
+            (Decimal::F32(value), DecimalType::BigDecimal) => return Err(not_finite(value)),
+            (Decimal::F64(value), DecimalType::BigDecimal) => return Err(not_finite(value)),
+            (Decimal::Rational(value), DecimalType::BigDecimal) => {
+                let result = BigDecimal::from_rational(&value)
+                    .ok_or_else(|| Fault::InvalidOperation(format!("{} has no finite decimal representation", value)))?;
+                Decimal::BigDecimal(result)
+            }
+            (Decimal::BigDecimal(value), DecimalType::F32) => Decimal::F32(f32::rounding_from(&value.to_rational(), RoundingMode::Nearest).0),
+            (Decimal::BigDecimal(value), DecimalType::F64) => Decimal::F64(f64::rounding_from(&value.to_rational(), RoundingMode::Nearest).0),
+            (Decimal::BigDecimal(value), DecimalType::Rational) => Decimal::Rational(value.to_rational()),
+            (value, _) => value,
+        };
+        Ok(result)
+    }
+
+    /// Converts both operands to the type given by `DecimalType::promote`.
+    pub fn promote(self, other: Decimal) -> (Decimal, Decimal) {
+        let typ = self.get_type().promote(&other.get_type());
+        // Only floats are converted to `Rational`, and only when they already are one.
+        (self.convert(&typ).unwrap(), other.convert(&typ).unwrap())
+    }
+
+    /// Rounds to `digits` digits after the decimal point, or to a multiple of a power of ten if it is negative.
+    /// Floats are rounded exactly, so 2.675 rounds down to 2.67 since it is really 2.67499999999999982236431605997495353221893310546875.
+    /// `BigDecimal`s end up with a scale of `digits`. Exact types fault with `Overflow` if `digits` is
+    /// further than `MAX_SCALE` from zero.
+    pub fn round(self, digits: i64, rounding: Rounding) -> Result<Decimal, Fault> {
+        let round = |value: Rational, digits: i64| {
+            let scale = Rational::from(10).pow(digits);
+            Rational::from(rounding.round(value * &scale)) / scale
+        };
+        let float_digits = digits.clamp(-FLOAT_ROUNDING_DIGITS, FLOAT_ROUNDING_DIGITS);
+        let result = match self {
+            Decimal::F32(value) if value.is_finite() => Decimal::F32(f32::rounding_from(&round(Rational::try_from(value).unwrap(), float_digits), RoundingMode::Nearest).0),
+            Decimal::F64(value) if value.is_finite() => Decimal::F64(f64::rounding_from(&round(Rational::try_from(value).unwrap(), float_digits), RoundingMode::Nearest).0),
+            Decimal::Rational(_) if digits.unsigned_abs() > MAX_SCALE => return Err(Fault::Overflow),
+            Decimal::Rational(value) => Decimal::Rational(round(value, digits)),
+            Decimal::BigDecimal(value) => Decimal::BigDecimal(BigDecimal::rounded(&value.to_rational(), digits, rounding)?),
+            value => value,
+        };
+        Ok(result)
+    }
+
+    /// Divides by `rhs` and rounds the quotient to `digits` digits like `round`.
+    /// `BigDecimal` quotients are rounded from the exact value.
+    pub fn divide_to(self, rhs: Decimal, digits: i64, rounding: Rounding) -> Result<Decimal, Fault> {
+        if rhs.is_zero() {
+            return Err(Fault::DivisionByZero);
+        }
+        match self.promote(rhs) {
+            (Decimal::BigDecimal(left), Decimal::BigDecimal(right)) => Ok(Decimal::BigDecimal(left.divide(&right, digits, rounding)?)),
+            (left, right) => left.divide(right)?.round(digits, rounding),
         }
     }
 
-    /// Raises the decimal to the power of the integer.
-    /// For Rationals the exponent must be a i64.
-    /// For floats the exponent must be a i32.
-    pub fn powi(self, exponent: Integer) -> Self {
+    /// Divides by `rhs`, promoting mixed operands. `BigDecimal` quotients round like `big_decimal_quotient`.
+    /// Faults with `DivisionByZero` if `rhs` is zero, floats included.
+    pub fn divide(self, rhs: Decimal) -> Result<Decimal, Fault> {
+        if rhs.is_zero() {
+            return Err(Fault::DivisionByZero);
+        }
+        let result = match self.promote(rhs) {
+            (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left / right),
+            (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left / right),
+            (Decimal::Rational(left), Decimal::Rational(right)) => Decimal::Rational(left / right),
+            (Decimal::BigDecimal(left), Decimal::BigDecimal(right)) => Decimal::BigDecimal(big_decimal_quotient(left, right)?),
+            _ => unreachable!(),
+        };
+        Ok(result)
+    }
 
-        match self {
-            Decimal::F32(value) => {
-                let exponent: i32 = exponent.into();
-                Decimal::F32(value.powi(exponent))
+    /// The remainder of a division rounded towards zero, like `%` on floats, promoting mixed operands.
+    /// Faults with `DivisionByZero` if `rhs` is zero, floats included.
+    pub fn remainder(self, rhs: Decimal) -> Result<Decimal, Fault> {
+        if rhs.is_zero() {
+            return Err(Fault::DivisionByZero);
+        }
+        let result = match self.promote(rhs) {
+            (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left % right),
+            (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left % right),
+            (Decimal::Rational(left), Decimal::Rational(right)) => Decimal::Rational(rational_remainder(left, right)),
+            (Decimal::BigDecimal(left), Decimal::BigDecimal(right)) => Decimal::BigDecimal(left.remainder(&right)?),
+            _ => unreachable!(),
+        };
+        Ok(result)
+    }
+
+    /// Raises the decimal to the power of another decimal, promoting mixed operands.
+    /// `Rational`s and `BigDecimal`s stay exact for whole exponents, the result of any other exponent is irrational
+    /// and is computed as an `F64`.
+    pub fn powd(self, exponent: Self) -> Result<Self, Fault> {
+        let result = match self.promote(exponent) {
+            (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left.powf(right)),
+            (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left.powf(right)),
+            (Decimal::BigDecimal(left), Decimal::BigDecimal(right)) if right.scale() == 0 || right.to_rational().to_denominator() == 1u32 => {
+                return Decimal::BigDecimal(left).powi(Integer::Integer(right.to_integer()));
+            }
+            (Decimal::BigDecimal(left), Decimal::BigDecimal(right)) => {
+                let left = f64::rounding_from(&left.to_rational(), RoundingMode::Nearest).0;
+                Decimal::F64(left.powf(f64::rounding_from(&right.to_rational(), RoundingMode::Nearest).0))
+            }
+            (Decimal::Rational(left), Decimal::Rational(right)) => {
+                match malachite::Integer::try_from(&right) {
+                    Ok(right) => return Decimal::Rational(left).powi(Integer::Integer(right)),
+                    Err(_) => {
+                        let left = f64::rounding_from(&left, RoundingMode::Nearest).0;
+                        Decimal::F64(left.powf(f64::rounding_from(&right, RoundingMode::Nearest).0))
+                    }
+                }
+            }
+            _ => unreachable!(),
+        };
+        Ok(result)
+    }
+
+    /// Raises the decimal to the power of an integer of any type.
+    /// Exponents of exact types must fit in an `i64` and zero can't be raised to a negative power.
+    /// `Rational` powers whose numerator or denominator would pass `MAX_BIG_BITS` bits fault with `OutOfMemory`.
+    pub fn powi(self, exponent: Integer) -> Result<Self, Fault> {
+        let exponent = exponent.to_big_integer();
+        let result = match self {
+            Decimal::F32(value) => match i32::try_from(&exponent) {
+                Ok(exponent) => Decimal::F32(value.powi(exponent)),
+                Err(_) => Decimal::F32(value.powf(f32::rounding_from(&exponent, RoundingMode::Nearest).0)),
             },
-            Decimal::F64(value) => {
-                let exponent: i32 = exponent.into();
-                Decimal::F64(value.powi(exponent))
+            Decimal::F64(value) => match i32::try_from(&exponent) {
+                Ok(exponent) => Decimal::F64(value.powi(exponent)),
+                Err(_) => Decimal::F64(value.powf(f64::rounding_from(&exponent, RoundingMode::Nearest).0)),
             },
             Decimal::Rational(value) => {
-                let exponent: i64 = exponent.into();
+                let exponent = i64::try_from(&exponent).map_err(|_| Fault::Overflow)?;
+                if value == 0 && exponent < 0 {
+                    return Err(Fault::DivisionByZero);
+                }
+                let bits = value.numerator_ref().significant_bits().max(value.denominator_ref().significant_bits());
+                if bits > 1 {
+                    check_big_bits(bits.checked_mul(exponent.unsigned_abs()))?;
+                }
                 Decimal::Rational(value.pow(exponent))
             },
-        }
+            Decimal::BigDecimal(value) => {
+                let exponent = i64::try_from(&exponent).map_err(|_| Fault::Overflow)?;
+                if value.is_zero() && exponent < 0 {
+                    return Err(Fault::DivisionByZero);
+                }
+                Decimal::BigDecimal(value.pow(exponent)?)
+            },
+        };
+        Ok(result)
     }
 
     pub fn is_rational(&self) -> bool {
@@ -126,83 +347,43 @@ impl Decimal {
 
 }
 
-impl std::ops::Add for Decimal {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left + right),
-            (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left + right),
-            (Decimal::Rational(left), Decimal::Rational(right)) => Decimal::Rational(left + right),
-            (Decimal::F32(left), Decimal::F64(right)) => Decimal::F64(left as f64 + right),
-            (Decimal::F64(left), Decimal::F32(right)) => Decimal::F64(left + right as f64),
-            (x, y) => panic!("Cannot add {:?} and {:?}", x, y),
+/// Generates an operator that promotes mixed operands first.
+macro_rules! generate_op {
+    ($Opname:ident, $fun_name:ident, $op:tt, $rational:expr, $big_decimal:expr) => {
+        impl std::ops::$Opname for Decimal {
+            type Output = Self;
+
+            fn $fun_name(self, rhs: Self) -> Self::Output {
+                match self.promote(rhs) {
+                    (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left $op right),
+                    (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left $op right),
+                    (Decimal::Rational(left), Decimal::Rational(right)) => Decimal::Rational($rational(left, right)),
+                    (Decimal::BigDecimal(left), Decimal::BigDecimal(right)) => Decimal::BigDecimal($big_decimal(left, right)),
+                    _ => unreachable!(),
+                }
+            }
         }
     }
-
 }
 
-impl std::ops::Sub for Decimal {
-    type Output = Self;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left - right),
-            (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left - right),
-            (Decimal::Rational(left), Decimal::Rational(right)) => Decimal::Rational(left - right),
-            (Decimal::F32(left), Decimal::F64(right)) => Decimal::F64(left as f64 - right),
-            (Decimal::F64(left), Decimal::F32(right)) => Decimal::F64(left - right as f64),
-            (x, y) => panic!("Cannot subtract {:?} and {:?}", x, y),
-        }
-    }
+generate_op!(Add, add, +, |left, right| left + right, |left, right| left + right);
+generate_op!(Sub, sub, -, |left, right| left - right, |left, right| left - right);
+generate_op!(Mul, mul, *, |left, right| left * right, |left, right| left * right);
 
+/// Quotients round half to even, to at least `DIVISION_SCALE` digits and never fewer than the operands have.
+pub(crate) fn big_decimal_quotient(left: BigDecimal, right: BigDecimal) -> Result<BigDecimal, Fault> {
+    let scale = left.scale().max(right.scale()).max(DIVISION_SCALE);
+    left.divide(&right, scale as i64, Rounding::HalfEven)
 }
 
-impl std::ops::Mul for Decimal {
-    type Output = Self;
-
-    fn mul(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left * right),
-            (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left * right),
-            (Decimal::Rational(left), Decimal::Rational(right)) => Decimal::Rational(left * right),
-            (Decimal::F32(left), Decimal::F64(right)) => Decimal::F64(left as f64 * right),
-            (Decimal::F64(left), Decimal::F32(right)) => Decimal::F64(left * right as f64),
-            (x, y) => panic!("Cannot multiply {:?} and {:?}", x, y),
-        }
-    }
-
+/// The remainder of a division rounded towards zero, like `%` on floats.
+fn rational_remainder(left: Rational, right: Rational) -> Rational {
+    let quotient = malachite::Integer::rounding_from(&left / &right, RoundingMode::Down).0;
+    left - right * Rational::from(quotient)
 }
 
-impl std::ops::Div for Decimal {
-    type Output = Self;
-
-    fn div(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left / right),
-            (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left / right),
-            (Decimal::Rational(left), Decimal::Rational(right)) => Decimal::Rational(left / right),
-            (Decimal::F32(left), Decimal::F64(right)) => Decimal::F64(left as f64 / right),
-            (Decimal::F64(left), Decimal::F32(right)) => Decimal::F64(left / right as f64),
-            (x, y) => panic!("Cannot divide {:?} and {:?}", x, y),
-        }
-    }
-
-}
-
-impl std::ops::Rem for Decimal {
-    type Output = Self;
-
-    fn rem(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Decimal::F32(left), Decimal::F32(right)) => Decimal::F32(left % right),
-            (Decimal::F64(left), Decimal::F64(right)) => Decimal::F64(left % right),
-            (Decimal::F32(left), Decimal::F64(right)) => Decimal::F64(left as f64 % right),
-            (Decimal::F64(left), Decimal::F32(right)) => Decimal::F64(left % right as f64),
-            (x, y) => panic!("Cannot divide {:?} and {:?}", x, y),
-        }
-    }
-
+fn not_finite<T: Display>(value: T) -> Fault {
+    Fault::InvalidOperation(format!("Cannot convert {} to an exact number", value))
 }
 
 impl std::ops::Neg for Decimal {
@@ -213,6 +394,7 @@ impl std::ops::Neg for Decimal {
             Decimal::F32(value) => Decimal::F32(-value),
             Decimal::F64(value) => Decimal::F64(-value),
             Decimal::Rational(value) => Decimal::Rational(-value),
+            Decimal::BigDecimal(value) => Decimal::BigDecimal(-value),
         }
     }
 
@@ -224,6 +406,7 @@ impl PartialEq<Decimal> for Decimal {
             (Decimal::F32(left), Decimal::F32(right)) => left == right,
             (Decimal::F64(left), Decimal::F64(right)) => left == right,
             (Decimal::Rational(left), Decimal::Rational(right)) => left == right,
+            (Decimal::BigDecimal(left), Decimal::BigDecimal(right)) => left == right,
             (x, y) => panic!("Cannot compare {:?} and {:?}", x, y),
         }
     }
@@ -235,11 +418,29 @@ impl PartialOrd<Decimal> for Decimal {
             (Decimal::F32(left), Decimal::F32(right)) => left.partial_cmp(right),
             (Decimal::F64(left), Decimal::F64(right)) => left.partial_cmp(right),
             (Decimal::Rational(left), Decimal::Rational(right)) => left.partial_cmp(right),
+            (Decimal::BigDecimal(left), Decimal::BigDecimal(right)) => left.partial_cmp(right),
             (x, y) => panic!("Cannot compare {:?} and {:?}", x, y),
         }
     }
 }
 
+/// Floats hash their bits with `-0.0` folded into `0.0`, since the two are equal.
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Decimal::F32(value) => float_bits(*value as f64).hash(state),
+            Decimal::F64(value) => float_bits(*value).hash(state),
+            Decimal::Rational(value) => value.hash(state),
+            Decimal::BigDecimal(value) => value.hash(state),
+        }
+    }
+}
+
+pub(crate) fn float_bits(value: f64) -> u64 {
+    if value == 0.0 { 0 } else { value.to_bits() }
+}
+
 impl From<u8> for Decimal {
     fn from(value: u8) -> Self {
         Decimal::F32(value as f32)
@@ -306,6 +507,12 @@ impl From<malachite::Rational> for Decimal {
     }
 }
 
+impl From<BigDecimal> for Decimal {
+    fn from(value: BigDecimal) -> Self {
+        Decimal::BigDecimal(value)
+    }
+}
+
 impl From<Integer> for Decimal {
     fn from(value: Integer) -> Self {
         match value {
@@ -365,6 +572,7 @@ impl Display for Decimal {
             Decimal::F32(value) => write!(f, "{}", value),
             Decimal::F64(value) => write!(f, "{}", value),
             Decimal::Rational(value) => write!(f, "{}", value),
+            Decimal::BigDecimal(value) => write!(f, "{}", value),
         }
     }
 }
@@ -375,6 +583,7 @@ impl Debug for Decimal {
             Decimal::F32(value) => write!(f, "{:?}", value),
             Decimal::F64(value) => write!(f, "{:?}", value),
             Decimal::Rational(value) => write!(f, "{:?}", value),
+            Decimal::BigDecimal(value) => write!(f, "{:?}", value),
         }
     }
 }
\ No newline at end of file
@@ -1,7 +1,10 @@
+use std::cmp::Ordering;
 use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
 use crate::instruction::Instruction;
 use crate::machine::environment::Environment;
 use crate::stack::StackChunk;
+use crate::machine::Fault;
 
 #[derive(Clone)]
 pub struct Function {
@@ -36,8 +39,9 @@ impl Function {
     pub fn get_environment(&self) -> &Environment {
         &self.environment
     }
-    pub fn get_instruction(&self, index: usize) -> &Instruction {
-        &self.code[index]
+    /// Faults with `InvalidJump` if `index` is outside the code, which happens when a jump leaves the function.
+    pub fn get_instruction(&self, index: usize) -> Result<&Instruction, Fault> {
+        self.code.get(index).ok_or(Fault::InvalidJump)
     }
 
     pub fn into_chunk(self) -> Box<dyn StackChunk> {
@@ -46,6 +50,25 @@ impl Function {
 
 }
 
+/// Functions are incomparable: they are never equal, not even to themselves, and have no order.
+/// The machine faults with `NotComparable` before comparing values that contain them.
+impl PartialEq for Function {
+    fn eq(&self, _: &Function) -> bool {
+        false
+    }
+}
+
+impl PartialOrd for Function {
+    fn partial_cmp(&self, _: &Function) -> Option<Ordering> {
+        None
+    }
+}
+
+/// Hashes nothing, which is consistent with a function never being equal to anything.
+impl Hash for Function {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
 
 impl Display for Function {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
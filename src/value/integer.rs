@@ -1,12 +1,17 @@
 use std::alloc::Layout;
 use std::fmt::{Debug, Display};
-use std::ops::{Neg, Not};
-use malachite::Natural;
-use malachite::num::arithmetic::traits::Pow;
+use std::hash::{Hash, Hasher};
+use std::ops::{Neg, Not, Shl, Shr};
+use malachite::{Natural, Rational};
+use malachite::num::arithmetic::traits::{
+    BinomialCoefficient, DivMod, DivRem, Factorial, FloorSqrt, Gcd, Lcm, Mod, ModInverse, ModPow, Pow, UnsignedAbs,
+};
+use malachite::num::logic::traits::{BitAccess, CountOnes, SignificantBits};
 use malachite::num::basic::traits::Zero;
+use crate::machine::Fault;
 use crate::stack::StackChunk;
 use crate::value::{Value, ValueType};
-use crate::value::decimal::DecimalType;
+use crate::value::decimal::Decimal;
 
 #[derive(Debug,Clone, Copy, PartialEq, PartialOrd)]
 pub enum IntegerType {
@@ -54,6 +59,55 @@ impl IntegerType {
     }
 }
 
+impl IntegerType {
+    /// The width of a fixed width type, `None` for `Natural` and `Integer`.
+    pub fn bits(&self) -> Option<u32> {
+        match self {
+            IntegerType::U8 | IntegerType::I8 => Some(8),
+            IntegerType::U16 | IntegerType::I16 => Some(16),
+            IntegerType::U32 | IntegerType::I32 => Some(32),
+            IntegerType::U64 | IntegerType::I64 => Some(64),
+            IntegerType::Natural | IntegerType::Integer => None,
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, IntegerType::I8 | IntegerType::I16 | IntegerType::I32 | IntegerType::I64 | IntegerType::Integer)
+    }
+
+    /// The type both sides of a binary operation are converted to before it is applied.
+    ///
+    /// This is the smallest type that holds every value of both types:
+    /// - Two unsigned or two signed fixed width types give the wider one.
+    /// - An unsigned and a signed fixed width type give the signed type if it is wider, otherwise
+    ///   the signed type twice as wide as the unsigned one. `U64` with any signed type gives `Integer`.
+    /// - `Natural` with an unsigned type gives `Natural`, with a signed type it gives `Integer`.
+    /// - `Integer` with anything gives `Integer`.
+    pub fn promote(self, other: IntegerType) -> IntegerType {
+        if self == other {
+            return self;
+        }
+        match (self.bits(), other.bits()) {
+            (Some(left), Some(right)) if self.is_signed() == other.is_signed() => {
+                if left >= right { self } else { other }
+            }
+            (Some(left), Some(right)) => {
+                let (unsigned, signed) = if self.is_signed() { (right, left) } else { (left, right) };
+                let signed_type = if self.is_signed() { self } else { other };
+                match unsigned {
+                    _ if signed > unsigned => signed_type,
+                    8 => IntegerType::I16,
+                    16 => IntegerType::I32,
+                    32 => IntegerType::I64,
+                    _ => IntegerType::Integer,
+                }
+            }
+            _ if self.is_signed() || other.is_signed() => IntegerType::Integer,
+            _ => IntegerType::Natural,
+        }
+    }
+}
+
 impl Display for IntegerType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -87,24 +141,17 @@ pub enum Integer {
 }
 
 impl Integer {
-    pub fn cast(self, into_type: ValueType) -> Value {
-        match into_type {
-            ValueType::Integer(IntegerType::I8) => Value::Integer(Integer::I8(self.into())),
-            ValueType::Integer(IntegerType::I16) => Value::Integer(Integer::I16(self.into())),
-            ValueType::Integer(IntegerType::I32) => Value::Integer(Integer::I32(self.into())),
-            ValueType::Integer(IntegerType::I64) => Value::Integer(Integer::I64(self.into())),
-            ValueType::Integer(IntegerType::U8) => Value::Integer(Integer::U8(self.into())),
-            ValueType::Integer(IntegerType::U16) => Value::Integer(Integer::U16(self.into())),
-            ValueType::Integer(IntegerType::U32) => Value::Integer(Integer::U32(self.into())),
-            ValueType::Integer(IntegerType::U64) => Value::Integer(Integer::U64(self.into())),
-            ValueType::Integer(IntegerType::Natural) => Value::Integer(Integer::Natural(self.into())),
-            ValueType::Integer(IntegerType::Integer) => Value::Integer(Integer::Integer(self.into())),
-            ValueType::Boolean => Value::Boolean(self.into()),
-            ValueType::Decimal(DecimalType::F32) => Value::Decimal(self.into()),
-            ValueType::Decimal(DecimalType::F64) => Value::Decimal(self.into()),
-            ValueType::Decimal(DecimalType::Rational) => Value::Decimal(self.into()),
-            _ => panic!("Cannot cast integer to {}", into_type),
-        }
+    /// Converts to an integer or decimal type, or to a boolean that is true for anything but zero.
+    /// Faults with `Overflow` or `Underflow` if the value doesn't fit in an integer type, and with
+    /// `TypeMismatch` for any other type.
+    pub fn cast(self, into_type: ValueType) -> Result<Value, Fault> {
+        let result = match into_type {
+            ValueType::Integer(typ) => Value::Integer(Integer::of_type(typ, self.to_big_integer())?),
+            ValueType::Decimal(typ) => Value::Decimal(Decimal::Rational(Rational::from(self.to_big_integer())).convert(&typ)?),
+            ValueType::Boolean => Value::Boolean(!self.is_zero()),
+            _ => return Err(Fault::TypeMismatch),
+        };
+        Ok(result)
     }
 
     pub fn is_zero(&self) -> bool {
@@ -152,51 +199,751 @@ impl Integer {
         }
     }
 
+    /// The value as a `usize`, or `None` if it is negative or too large.
     pub fn to_usize(self) -> Option<usize> {
         match self {
             Integer::U8(value) => Some(value as usize),
             Integer::U16(value) => Some(value as usize),
-            Integer::U32(value) => Some(value as usize),
-            Integer::U64(value) => Some(value as usize),
-            Integer::I8(value) => Some(value as usize),
-            Integer::I16(value) => Some(value as usize),
-            Integer::I32(value) => Some(value as usize),
-            Integer::I64(value) => Some(value as usize),
-            Integer::Natural(_) => None,
-            Integer::Integer(_) => None,
+            Integer::U32(value) => usize::try_from(value).ok(),
+            Integer::U64(value) => usize::try_from(value).ok(),
+            Integer::I8(value) => usize::try_from(value).ok(),
+            Integer::I16(value) => usize::try_from(value).ok(),
+            Integer::I32(value) => usize::try_from(value).ok(),
+            Integer::I64(value) => usize::try_from(value).ok(),
+            Integer::Natural(value) => usize::try_from(&value).ok(),
+            Integer::Integer(value) => usize::try_from(&value).ok(),
         }
     }
+
+    /// Converts the integer into an arbitrary precision integer, whatever its type.
+    pub fn to_big_integer(&self) -> malachite::Integer {
+        match self {
+            Integer::U8(value) => malachite::Integer::from(*value),
+            Integer::U16(value) => malachite::Integer::from(*value),
+            Integer::U32(value) => malachite::Integer::from(*value),
+            Integer::U64(value) => malachite::Integer::from(*value),
+            Integer::I8(value) => malachite::Integer::from(*value),
+            Integer::I16(value) => malachite::Integer::from(*value),
+            Integer::I32(value) => malachite::Integer::from(*value),
+            Integer::I64(value) => malachite::Integer::from(*value),
+            Integer::Natural(value) => malachite::Integer::from(value),
+            Integer::Integer(value) => value.clone(),
+        }
+    }
+
+    pub fn get_type(&self) -> IntegerType {
+        match self {
+            Integer::U8(_) => IntegerType::U8,
+            Integer::U16(_) => IntegerType::U16,
+            Integer::U32(_) => IntegerType::U32,
+            Integer::U64(_) => IntegerType::U64,
+            Integer::I8(_) => IntegerType::I8,
+            Integer::I16(_) => IntegerType::I16,
+            Integer::I32(_) => IntegerType::I32,
+            Integer::I64(_) => IntegerType::I64,
+            Integer::Natural(_) => IntegerType::Natural,
+            Integer::Integer(_) => IntegerType::Integer,
+        }
+    }
+
+    /// Converts `value` into an integer of type `typ`.
+    /// Faults with `Overflow` or `Underflow` if the value doesn't fit in that type.
+    pub fn of_type(typ: IntegerType, value: malachite::Integer) -> Result<Integer, Fault> {
+        let out_of_range = if value < 0 { Fault::Underflow } else { Fault::Overflow };
+        let result = match typ {
+            IntegerType::U8 => u8::try_from(&value).ok().map(Integer::U8),
+            IntegerType::U16 => u16::try_from(&value).ok().map(Integer::U16),
+            IntegerType::U32 => u32::try_from(&value).ok().map(Integer::U32),
+            IntegerType::U64 => u64::try_from(&value).ok().map(Integer::U64),
+            IntegerType::I8 => i8::try_from(&value).ok().map(Integer::I8),
+            IntegerType::I16 => i16::try_from(&value).ok().map(Integer::I16),
+            IntegerType::I32 => i32::try_from(&value).ok().map(Integer::I32),
+            IntegerType::I64 => i64::try_from(&value).ok().map(Integer::I64),
+            IntegerType::Natural => Natural::try_from(value).ok().map(Integer::Natural),
+            IntegerType::Integer => Some(Integer::Integer(value)),
+        };
+        result.ok_or(out_of_range)
+    }
+
+    /// Converts `value` into an integer of the same type as `self`.
+    /// Faults with `Overflow` or `Underflow` if the value doesn't fit in that type.
+    pub fn of_same_type(&self, value: malachite::Integer) -> Result<Integer, Fault> {
+        Integer::of_type(self.get_type(), value)
+    }
+
+    /// Converts a `Natural` that fits in a `U64`, or an `Integer` that fits in an `I64`, to that type.
+    /// Other values are returned as they are.
+    pub fn narrow(self) -> Integer {
+        match self {
+            Integer::Natural(value) => match u64::try_from(&value) {
+                Ok(value) => Integer::U64(value),
+                Err(_) => Integer::Natural(value),
+            },
+            Integer::Integer(value) => match i64::try_from(&value) {
+                Ok(value) => Integer::I64(value),
+                Err(_) => Integer::Integer(value),
+            },
+            value => value,
+        }
+    }
+
+    /// Whether negating the value doesn't fit in its type, which only happens to the smallest signed values.
+    pub fn negation_overflows(&self) -> bool {
+        matches!(self, Integer::I8(i8::MIN) | Integer::I16(i16::MIN) | Integer::I32(i32::MIN) | Integer::I64(i64::MIN))
+    }
+
+    /// Converts both integers to their common type, see [`IntegerType::promote`].
+    pub fn promote(self, other: Integer) -> (Integer, Integer) {
+        let (left_type, right_type) = (self.get_type(), other.get_type());
+        if left_type == right_type {
+            return (self, other);
+        }
+        let typ = left_type.promote(right_type);
+        // The common type holds every value of both types, so the conversions can't fail.
+        let convert = |value: Integer| {
+            if value.get_type() == typ {
+                value
+            } else {
+                Integer::of_type(typ, value.to_big_integer()).unwrap()
+            }
+        };
+        (convert(self), convert(other))
+    }
 }
 
-macro_rules! generate_op {
-    ($Opname:ident, $fun_name:ident, $op:tt) => {
-        impl std::ops::$Opname for Integer {
-            type Output = Self;
-        
-            fn $fun_name(self, rhs: Self) -> Self::Output {
-                match (self, rhs) {
-                    (Integer::U8(left), Integer::U8(right)) => Integer::U8(left $op right),
-                    (Integer::U16(left), Integer::U16(right)) => Integer::U16(left $op right),
-                    (Integer::U32(left), Integer::U32(right)) => Integer::U32(left $op right),
-                    (Integer::U64(left), Integer::U64(right)) => Integer::U64(left $op right),
-                    (Integer::I8(left), Integer::I8(right)) => Integer::I8(left $op right),
-                    (Integer::I16(left), Integer::I16(right)) => Integer::I16(left $op right),
-                    (Integer::I32(left), Integer::I32(right)) => Integer::I32(left $op right),
-                    (Integer::I64(left), Integer::I64(right)) => Integer::I64(left $op right),
-                    (Integer::Natural(left), Integer::Natural(right)) => Integer::Natural(left $op right),
-                    (Integer::Integer(left), Integer::Integer(right)) => Integer::Integer(left $op right),
-                    (x, y) => panic!("Cannot add {:?} and {:?}", x, y),
+/// What an arithmetic instruction does with a result that doesn't fit in its integer type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverflowMode {
+    /// Fault with `Overflow` or `Underflow`.
+    Checked,
+    /// Keep the low bits of the result.
+    Wrapping,
+    /// Clamp the result to the smallest or largest value of the type.
+    Saturating,
+}
+
+/// The most bits an arbitrary precision result may take. Operations whose result would be larger
+/// fault with `OutOfMemory` before computing it.
+pub const MAX_BIG_BITS: u64 = 1 << 30;
+
+/// Faults with `OutOfMemory` unless a result of `bits` bits is allowed. `None` means the count overflowed.
+pub(crate) fn check_big_bits(bits: Option<u64>) -> Result<(), Fault> {
+    match bits {
+        Some(bits) if bits <= MAX_BIG_BITS => Ok(()),
+        _ => Err(Fault::OutOfMemory),
+    }
+}
+
+/// The fault for a result that is too large (`Overflow`) or too small (`Underflow`) for its type.
+fn out_of_range(negative: bool) -> Fault {
+    if negative { Fault::Underflow } else { Fault::Overflow }
+}
+
+/// Applies an overflowing operation to two fixed width values and resolves an overflow by `$mode`.
+/// `$negative` tells whether the exact result would have been negative.
+macro_rules! fixed_width_result {
+    ($id:ident, $left:expr, $right:expr, $mode:expr, $overflowing:ident, $saturating:ident, $negative:expr) => {{
+        let (left, right) = ($left, $right);
+        let (wrapped, overflowed) = left.$overflowing(right);
+        if !overflowed {
+            Ok((Integer::$id(wrapped), false))
+        } else {
+            match $mode {
+                OverflowMode::Checked => Err(out_of_range($negative(left, right))),
+                OverflowMode::Wrapping => Ok((Integer::$id(wrapped), true)),
+                OverflowMode::Saturating => Ok((Integer::$id(left.$saturating(right)), true)),
+            }
+        }
+    }};
+}
+
+/// Generates an arithmetic method that returns the result together with whether it overflowed.
+/// Both sides must have the same type. `Integer` never overflows and `Natural` only by going below zero.
+macro_rules! generate_overflowing_op {
+    ($fun_name:ident, $overflowing:ident, $saturating:ident, $op:tt, $natural:expr) => {
+        pub fn $fun_name(self, rhs: Integer, mode: OverflowMode) -> Result<(Integer, bool), Fault> {
+            macro_rules! fixed {
+                ($id:ident, $left:expr, $right:expr) => {
+                    fixed_width_result!($id, $left, $right, mode, $overflowing, $saturating,
+                        |left, right| (malachite::Integer::from(left) $op malachite::Integer::from(right)) < 0)
+                };
+            }
+            match (self, rhs) {
+                (Integer::U8(left), Integer::U8(right)) => fixed!(U8, left, right),
+                (Integer::U16(left), Integer::U16(right)) => fixed!(U16, left, right),
+                (Integer::U32(left), Integer::U32(right)) => fixed!(U32, left, right),
+                (Integer::U64(left), Integer::U64(right)) => fixed!(U64, left, right),
+                (Integer::I8(left), Integer::I8(right)) => fixed!(I8, left, right),
+                (Integer::I16(left), Integer::I16(right)) => fixed!(I16, left, right),
+                (Integer::I32(left), Integer::I32(right)) => fixed!(I32, left, right),
+                (Integer::I64(left), Integer::I64(right)) => fixed!(I64, left, right),
+                (Integer::Natural(left), Integer::Natural(right)) => $natural(left, right, mode),
+                (Integer::Integer(left), Integer::Integer(right)) => Ok((Integer::Integer(left $op right), false)),
+                _ => Err(Fault::TypeMismatch),
+            }
+        }
+    };
+}
+
+impl Integer {
+    generate_overflowing_op!(overflowing_add, overflowing_add, saturating_add, +,
+        |left: Natural, right: Natural, _| Ok((Integer::Natural(left + right), false)));
+    generate_overflowing_op!(overflowing_sub, overflowing_sub, saturating_sub, -, natural_subtract);
+    generate_overflowing_op!(overflowing_mul, overflowing_mul, saturating_mul, *,
+        |left: Natural, right: Natural, _| Ok((Integer::Natural(left * right), false)));
+
+    /// Raises `self` to the power of `exponent`, which can be any non negative integer.
+    pub fn overflowing_pow(self, exponent: Integer, mode: OverflowMode) -> Result<(Integer, bool), Fault> {
+        if exponent.is_negative() {
+            return Err(Fault::InvalidOperation(format!("Cannot raise {} to the negative power {}", self, exponent)));
+        }
+        let exponent = u64::try_from(&exponent.to_big_integer()).map_err(|_| Fault::Overflow)?;
+        // Clamping keeps the parity, which is all that matters for 0, 1 and -1. Every other base overflows anyway.
+        let clamped = u32::try_from(exponent).unwrap_or(u32::MAX - (exponent % 2 == 0) as u32);
+        macro_rules! fixed {
+            ($id:ident, $type:ty, $left:expr) => {{
+                let left: $type = $left;
+                match left.checked_pow(clamped) {
+                    Some(result) => Ok((Integer::$id(result), false)),
+                    None => {
+                        let negative = left < 0 as $type && exponent % 2 == 1;
+                        match mode {
+                            OverflowMode::Checked => Err(out_of_range(negative)),
+                            OverflowMode::Wrapping => {
+                                let (mut wrapped, mut square, mut bits) = (1 as $type, left, exponent);
+                                while bits > 0 {
+                                    if bits & 1 == 1 {
+                                        wrapped = wrapped.wrapping_mul(square);
+                                    }
+                                    square = square.wrapping_mul(square);
+                                    bits >>= 1;
+                                }
+                                Ok((Integer::$id(wrapped), true))
+                            }
+                            OverflowMode::Saturating if negative => Ok((Integer::$id(<$type>::MIN), true)),
+                            OverflowMode::Saturating => Ok((Integer::$id(<$type>::MAX), true)),
+                        }
+                    }
+                }
+            }};
+        }
+        match self {
+            Integer::U8(left) => fixed!(U8, u8, left),
+            Integer::U16(left) => fixed!(U16, u16, left),
+            Integer::U32(left) => fixed!(U32, u32, left),
+            Integer::U64(left) => fixed!(U64, u64, left),
+            Integer::I8(left) => fixed!(I8, i8, left),
+            Integer::I16(left) => fixed!(I16, i16, left),
+            Integer::I32(left) => fixed!(I32, i32, left),
+            Integer::I64(left) => fixed!(I64, i64, left),
+            Integer::Natural(left) => {
+                check_pow_bits(left.significant_bits(), exponent)?;
+                Ok((Integer::Natural(left.pow(exponent)), false))
+            }
+            Integer::Integer(left) => {
+                check_pow_bits(left.significant_bits(), exponent)?;
+                Ok((Integer::Integer(left.pow(exponent)), false))
+            }
+        }
+    }
+
+    /// Shifts `self` left by `amount`, which can be any non negative integer.
+    /// Shifting out a bit that differs from the sign counts as an overflow.
+    pub fn overflowing_shl(self, amount: Integer, mode: OverflowMode) -> Result<(Integer, bool), Fault> {
+        if amount.is_negative() {
+            return Err(Fault::InvalidOperation(format!("Cannot shift {} by the negative amount {}", self, amount)));
+        }
+        let amount = u64::try_from(&amount.to_big_integer()).map_err(|_| Fault::Overflow)?;
+        macro_rules! fixed {
+            ($id:ident, $type:ty, $left:expr) => {{
+                let left: $type = $left;
+                let shifted = u32::try_from(amount).ok().and_then(|amount| left.checked_shl(amount)).unwrap_or(0);
+                let restored = u32::try_from(amount).ok().and_then(|amount| shifted.checked_shr(amount));
+                if left == 0 || restored == Some(left) {
+                    Ok((Integer::$id(shifted), false))
+                } else {
+                    match mode {
+                        OverflowMode::Checked => Err(out_of_range(left < 0 as $type)),
+                        OverflowMode::Wrapping => Ok((Integer::$id(shifted), true)),
+                        OverflowMode::Saturating if left < 0 as $type => Ok((Integer::$id(<$type>::MIN), true)),
+                        OverflowMode::Saturating => Ok((Integer::$id(<$type>::MAX), true)),
+                    }
+                }
+            }};
+        }
+        match self {
+            Integer::U8(left) => fixed!(U8, u8, left),
+            Integer::U16(left) => fixed!(U16, u16, left),
+            Integer::U32(left) => fixed!(U32, u32, left),
+            Integer::U64(left) => fixed!(U64, u64, left),
+            Integer::I8(left) => fixed!(I8, i8, left),
+            Integer::I16(left) => fixed!(I16, i16, left),
+            Integer::I32(left) => fixed!(I32, i32, left),
+            Integer::I64(left) => fixed!(I64, i64, left),
+            Integer::Natural(left) => {
+                if left != 0u32 {
+                    check_big_bits(left.significant_bits().checked_add(amount))?;
                 }
+                Ok((Integer::Natural(left << amount), false))
             }
+            Integer::Integer(left) => {
+                if left != 0 {
+                    check_big_bits(left.significant_bits().checked_add(amount))?;
+                }
+                Ok((Integer::Integer(left << amount), false))
+            }
+        }
+    }
+    /// Shifts `self` right by `amount`, which can be any non negative integer.
+    /// Signed values shift in copies of the sign bit, so shifting far enough leaves 0 or -1.
+    pub fn shift_right(self, amount: Integer) -> Result<Integer, Fault> {
+        if amount.is_negative() {
+            return Err(Fault::InvalidOperation(format!("Cannot shift {} by the negative amount {}", self, amount)));
+        }
+        let amount = u64::try_from(&amount.to_big_integer()).unwrap_or(u64::MAX);
+        macro_rules! fixed {
+            ($id:ident, $type:ty, $left:expr) => {{
+                let bits = <$type>::BITS as u64;
+                Integer::$id(if amount < bits { $left >> amount } else { $left >> (bits - 1) >> 1 })
+            }};
+        }
+        let result = match self {
+            Integer::U8(left) => fixed!(U8, u8, left),
+            Integer::U16(left) => fixed!(U16, u16, left),
+            Integer::U32(left) => fixed!(U32, u32, left),
+            Integer::U64(left) => fixed!(U64, u64, left),
+            Integer::I8(left) => fixed!(I8, i8, left),
+            Integer::I16(left) => fixed!(I16, i16, left),
+            Integer::I32(left) => fixed!(I32, i32, left),
+            Integer::I64(left) => fixed!(I64, i64, left),
+            Integer::Natural(left) => Integer::Natural(left >> amount),
+            Integer::Integer(left) => Integer::Integer(left >> amount),
+        };
+        Ok(result)
+    }
+
+    /// The bits of a fixed width integer and how many there are.
+    /// Arbitrary precision integers have no fixed width and never carry.
+    fn bit_pattern(&self) -> Option<(u64, u64)> {
+        match self {
+            Integer::U8(value) => Some((*value as u64, 8)),
+            Integer::U16(value) => Some((*value as u64, 16)),
+            Integer::U32(value) => Some((*value as u64, 32)),
+            Integer::U64(value) => Some((*value, 64)),
+            Integer::I8(value) => Some((*value as u8 as u64, 8)),
+            Integer::I16(value) => Some((*value as u16 as u64, 16)),
+            Integer::I32(value) => Some((*value as u32 as u64, 32)),
+            Integer::I64(value) => Some((*value as u64, 64)),
+            Integer::Natural(_) | Integer::Integer(_) => None,
+        }
+    }
+
+    /// Whether adding the bit patterns of `self` and `rhs` carries out of the top bit.
+    pub fn add_carry(&self, rhs: &Integer) -> bool {
+        match (self.bit_pattern(), rhs.bit_pattern()) {
+            (Some((left, bits)), Some((right, _))) => (left as u128 + right as u128) >> bits != 0,
+            _ => false,
+        }
+    }
+
+    /// Whether subtracting the bit pattern of `rhs` from that of `self` borrows.
+    pub fn subtract_borrow(&self, rhs: &Integer) -> bool {
+        match (self.bit_pattern(), rhs.bit_pattern()) {
+            (Some((left, _)), Some((right, _))) => left < right,
+            _ => false,
         }
     }
+
+    /// The last bit shifted out when shifting `self` left by `amount`.
+    pub fn shift_left_carry(&self, amount: &Integer) -> bool {
+        match (self.bit_pattern(), u64::try_from(&amount.to_big_integer())) {
+            (Some((left, bits)), Ok(amount)) if (1..=bits).contains(&amount) => (left >> (bits - amount)) & 1 == 1,
+            _ => false,
+        }
+    }
+
+    /// The last bit shifted out when shifting `self` right by `amount`.
+    pub fn shift_right_carry(&self, amount: &Integer) -> bool {
+        match (self.bit_pattern(), u64::try_from(&amount.to_big_integer())) {
+            (Some((left, bits)), Ok(amount)) if (1..=bits).contains(&amount) => (left >> (amount - 1)) & 1 == 1,
+            (Some(_), Ok(amount)) if amount > 0 => self.is_negative(),
+            _ => false,
+        }
+    }
+    /// The number of one bits. Negative `Integer`s have infinitely many and fault.
+    pub fn pop_count(&self) -> Result<u64, Fault> {
+        let count = match self {
+            Integer::U8(value) => value.count_ones() as u64,
+            Integer::U16(value) => value.count_ones() as u64,
+            Integer::U32(value) => value.count_ones() as u64,
+            Integer::U64(value) => value.count_ones() as u64,
+            Integer::I8(value) => value.count_ones() as u64,
+            Integer::I16(value) => value.count_ones() as u64,
+            Integer::I32(value) => value.count_ones() as u64,
+            Integer::I64(value) => value.count_ones() as u64,
+            Integer::Natural(value) => value.count_ones(),
+            Integer::Integer(value) => value.checked_count_ones().ok_or_else(|| {
+                Fault::InvalidOperation(format!("Cannot count the one bits of the negative integer {}", value))
+            })?,
+        };
+        Ok(count)
+    }
+
+    /// The number of bits needed to write the absolute value, leaving out the sign. Zero needs none.
+    pub fn bit_length(&self) -> u64 {
+        match self {
+            Integer::U8(value) => (u8::BITS - value.leading_zeros()) as u64,
+            Integer::U16(value) => (u16::BITS - value.leading_zeros()) as u64,
+            Integer::U32(value) => (u32::BITS - value.leading_zeros()) as u64,
+            Integer::U64(value) => (u64::BITS - value.leading_zeros()) as u64,
+            Integer::I8(value) => (u8::BITS - value.unsigned_abs().leading_zeros()) as u64,
+            Integer::I16(value) => (u16::BITS - value.unsigned_abs().leading_zeros()) as u64,
+            Integer::I32(value) => (u32::BITS - value.unsigned_abs().leading_zeros()) as u64,
+            Integer::I64(value) => (u64::BITS - value.unsigned_abs().leading_zeros()) as u64,
+            Integer::Natural(value) => value.significant_bits(),
+            Integer::Integer(value) => value.significant_bits(),
+        }
+    }
+
+    /// Reads bit `index` of the two's complement representation.
+    /// Bits past the width of a fixed width type are copies of the sign bit.
+    pub fn test_bit(&self, index: &Integer) -> Result<bool, Fault> {
+        let index = bit_index(index)?;
+        let bit = match (self.bit_pattern(), self) {
+            (Some((bits, width)), _) if index < width => (bits >> index) & 1 == 1,
+            (Some(_), _) => self.is_negative(),
+            (None, Integer::Natural(value)) => value.get_bit(index),
+            (None, Integer::Integer(value)) => value.get_bit(index),
+            (None, _) => unreachable!(),
+        };
+        Ok(bit)
+    }
+
+    /// Sets bit `index` of the two's complement representation to `bit`.
+    /// Changing a bit past the width of a fixed width type faults, since the result wouldn't fit.
+    pub fn with_bit(self, index: &Integer, bit: bool) -> Result<Integer, Fault> {
+        let index = bit_index(index)?;
+        macro_rules! fixed {
+            ($id:ident, $type:ty, $value:expr) => {{
+                let value: $type = $value;
+                if index < <$type>::BITS as u64 {
+                    let mask = (1 as $type) << index;
+                    Integer::$id(if bit { value | mask } else { value & !mask })
+                } else if bit == self.is_negative() {
+                    Integer::$id(value)
+                } else {
+                    return Err(out_of_range(!bit));
+                }
+            }};
+        }
+        let result = match self {
+            Integer::U8(value) => fixed!(U8, u8, value),
+            Integer::U16(value) => fixed!(U16, u16, value),
+            Integer::U32(value) => fixed!(U32, u32, value),
+            Integer::U64(value) => fixed!(U64, u64, value),
+            Integer::I8(value) => fixed!(I8, i8, value),
+            Integer::I16(value) => fixed!(I16, i16, value),
+            Integer::I32(value) => fixed!(I32, i32, value),
+            Integer::I64(value) => fixed!(I64, i64, value),
+            Integer::Natural(mut value) => {
+                value.assign_bit(index, bit);
+                Integer::Natural(value)
+            }
+            Integer::Integer(mut value) => {
+                value.assign_bit(index, bit);
+                Integer::Integer(value)
+            }
+        };
+        Ok(result)
+    }
+}
+
+/// How a division rounds its quotient, which also decides the sign of the remainder.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DivisionMode {
+    /// Round towards zero. The remainder has the sign of the dividend.
+    Truncate,
+    /// Round towards negative infinity. The remainder has the sign of the divisor.
+    Floor,
+    /// Round so that the remainder is never negative.
+    Euclidean,
+}
+
+impl Display for DivisionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DivisionMode::Truncate => write!(f, "truncate"),
+            DivisionMode::Floor => write!(f, "floor"),
+            DivisionMode::Euclidean => write!(f, "euclidean"),
+        }
+    }
+}
+
+impl Integer {
+    /// Divides `self` by `rhs` and returns the quotient and the remainder.
+    /// The quotient of the smallest signed value by -1 doesn't fit and faults with `Overflow`.
+    pub fn divide_remainder(self, rhs: Integer, mode: DivisionMode) -> Result<(Integer, Integer), Fault> {
+        let (quotient, remainder) = self.division(rhs, mode)?;
+        Ok((quotient.ok_or(Fault::Overflow)?, remainder))
+    }
+
+    /// The remainder of `self` divided by `rhs`, which always fits even when the quotient doesn't.
+    pub fn remainder(self, rhs: Integer, mode: DivisionMode) -> Result<Integer, Fault> {
+        Ok(self.division(rhs, mode)?.1)
+    }
+
+    fn division(self, rhs: Integer, mode: DivisionMode) -> Result<(Option<Integer>, Integer), Fault> {
+        if rhs.is_zero() {
+            return Err(Fault::DivisionByZero);
+        }
+        macro_rules! unsigned {
+            ($id:ident, $left:expr, $right:expr) => {
+                (Some(Integer::$id($left / $right)), Integer::$id($left % $right))
+            };
+        }
+        macro_rules! signed {
+            ($id:ident, $left:expr, $right:expr) => {{
+                let (left, right) = ($left, $right);
+                let (quotient, remainder) = match mode {
+                    DivisionMode::Euclidean => (left.checked_div_euclid(right), left.wrapping_rem_euclid(right)),
+                    _ => (left.checked_div(right), left.wrapping_rem(right)),
+                };
+                let (quotient, remainder) = if mode == DivisionMode::Floor && remainder != 0 && (remainder < 0) != (right < 0) {
+                    (quotient.map(|quotient| quotient - 1), remainder + right)
+                } else {
+                    (quotient, remainder)
+                };
+                (quotient.map(Integer::$id), Integer::$id(remainder))
+            }};
+        }
+        let result = match (self, rhs) {
+            (Integer::U8(left), Integer::U8(right)) => unsigned!(U8, left, right),
+            (Integer::U16(left), Integer::U16(right)) => unsigned!(U16, left, right),
+            (Integer::U32(left), Integer::U32(right)) => unsigned!(U32, left, right),
+            (Integer::U64(left), Integer::U64(right)) => unsigned!(U64, left, right),
+            (Integer::I8(left), Integer::I8(right)) => signed!(I8, left, right),
+            (Integer::I16(left), Integer::I16(right)) => signed!(I16, left, right),
+            (Integer::I32(left), Integer::I32(right)) => signed!(I32, left, right),
+            (Integer::I64(left), Integer::I64(right)) => signed!(I64, left, right),
+            (Integer::Natural(left), Integer::Natural(right)) => {
+                let (quotient, remainder) = left.div_rem(right);
+                (Some(Integer::Natural(quotient)), Integer::Natural(remainder))
+            }
+            (Integer::Integer(left), Integer::Integer(right)) => {
+                let (quotient, remainder) = match mode {
+                    DivisionMode::Truncate => left.div_rem(&right),
+                    _ => left.div_mod(&right),
+                };
+                let (quotient, remainder) = if mode == DivisionMode::Euclidean && remainder < 0 {
+                    (quotient + malachite::Integer::from(1), remainder - right)
+                } else {
+                    (quotient, remainder)
+                };
+                (Some(Integer::Integer(quotient)), Integer::Integer(remainder))
+            }
+            _ => return Err(Fault::TypeMismatch),
+        };
+        Ok(result)
+    }
+}
+
+/// Number theory, done on arbitrary precision values. The other operands can have any type and
+/// the result has the type of `self`, or of the modulus for modular arithmetic, faulting with
+/// `Overflow` if it doesn't fit.
+impl Integer {
+    pub fn gcd(self, rhs: &Integer) -> Result<Integer, Fault> {
+        let result = self.to_big_integer().unsigned_abs().gcd(rhs.to_big_integer().unsigned_abs());
+        self.of_same_type(malachite::Integer::from(result))
+    }
+
+    pub fn lcm(self, rhs: &Integer) -> Result<Integer, Fault> {
+        let result = self.to_big_integer().unsigned_abs().lcm(rhs.to_big_integer().unsigned_abs());
+        self.of_same_type(malachite::Integer::from(result))
+    }
+
+    pub fn factorial(self) -> Result<Integer, Fault> {
+        let count = u64::try_from(&self.to_big_integer())
+            .map_err(|_| Fault::InvalidOperation(format!("Cannot take the factorial of {}", self)))?;
+        if self.get_type().bits().is_some() {
+            // 20! is the largest factorial that fits in 64 bits.
+            if count > 20 {
+                return Err(Fault::Overflow);
+            }
+        } else {
+            // log2(n!) is below n * log2(n).
+            check_big_bits(count.checked_mul(u64::BITS as u64 - count.leading_zeros() as u64))?;
+        }
+        self.of_same_type(malachite::Integer::from(Natural::factorial(count)))
+    }
+
+    /// The number of ways to choose `k` elements out of `self`.
+    pub fn binomial(self, k: &Integer) -> Result<Integer, Fault> {
+        let (n, k) = (self.to_natural("binomial")?, k.to_natural("binomial")?);
+        if k > n {
+            return self.of_same_type(malachite::Integer::ZERO);
+        }
+        let k = (&n - &k).min(k);
+        if self.get_type().bits().is_some() {
+            return self.of_same_type(malachite::Integer::from(small_binomial(&n, &k)?));
+        }
+        // Choosing k out of n is below n^k.
+        check_big_bits(u64::try_from(&k).ok().and_then(|k| k.checked_mul(n.significant_bits())))?;
+        self.of_same_type(malachite::Integer::from(Natural::binomial_coefficient(n, k)))
+    }
+
+    /// The integer square root, rounded down.
+    pub fn isqrt(self) -> Result<Integer, Fault> {
+        let result = self.to_natural("isqrt")?.floor_sqrt();
+        self.of_same_type(malachite::Integer::from(result))
+    }
+
+    /// Raises `self` to the power of `exponent` modulo `modulus`.
+    /// A negative exponent raises the modular inverse instead.
+    pub fn pow_mod(&self, exponent: &Integer, modulus: &Integer) -> Result<Integer, Fault> {
+        let modulus_value = modulus_value(modulus)?;
+        let base = if exponent.is_negative() {
+            inverse(reduce(self, &modulus_value), &modulus_value).ok_or_else(|| not_invertible(self, modulus))?
+        } else {
+            reduce(self, &modulus_value)
+        };
+        let exponent = exponent.to_big_integer().unsigned_abs();
+        modulus.of_same_type(malachite::Integer::from(base.mod_pow(exponent, modulus_value)))
+    }
+
+    /// The value that gives 1 when multiplied by `self` modulo `modulus`.
+    /// Faults with `InvalidOperation` if `self` and `modulus` aren't coprime.
+    pub fn mod_inverse(&self, modulus: &Integer) -> Result<Integer, Fault> {
+        let modulus_value = modulus_value(modulus)?;
+        let result = inverse(reduce(self, &modulus_value), &modulus_value).ok_or_else(|| not_invertible(self, modulus))?;
+        modulus.of_same_type(malachite::Integer::from(result))
+    }
+
+    /// Miller-Rabin with the first twelve primes as witnesses.
+    /// This is exact below 3.3 * 10^24 and a very strong probable prime test above that.
+    pub fn is_prime(&self) -> bool {
+        const WITNESSES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+        let n = match Natural::try_from(self.to_big_integer()) {
+            Ok(n) => n,
+            Err(_) => return false,
+        };
+        if n < 2u32 {
+            return false;
+        }
+        for witness in WITNESSES {
+            if n == witness {
+                return true;
+            }
+            if (&n % Natural::from(witness)) == 0u32 {
+                return false;
+            }
+        }
+
+        let one = Natural::from(1u32);
+        let n_minus_one = &n - &one;
+        let mut odd = n_minus_one.clone();
+        let mut twos = 0u64;
+        while (&odd % Natural::from(2u32)) == 0u32 {
+            odd >>= 1;
+            twos += 1;
+        }
+
+        'witnesses: for witness in WITNESSES {
+            let mut x = Natural::from(witness).mod_pow(&odd, &n);
+            if x == one || x == n_minus_one {
+                continue;
+            }
+            for _ in 1..twos {
+                x = (&x * &x) % &n;
+                if x == n_minus_one {
+                    continue 'witnesses;
+                }
+            }
+            return false;
+        }
+        true
+    }
+
+    fn to_natural(&self, function: &str) -> Result<Natural, Fault> {
+        Natural::try_from(self.to_big_integer())
+            .map_err(|_| Fault::InvalidOperation(format!("{} expects non negative integers, got {}", function, self)))
+    }
+}
+
+fn modulus_value(modulus: &Integer) -> Result<Natural, Fault> {
+    if modulus.is_zero() {
+        return Err(Fault::DivisionByZero);
+    }
+    modulus.to_natural("Modular arithmetic")
+}
+
+/// The representative of `value` between 0 and `modulus`.
+fn reduce(value: &Integer, modulus: &Natural) -> Natural {
+    Natural::try_from(value.to_big_integer().mod_op(malachite::Integer::from(modulus))).unwrap()
+}
+
+fn inverse(value: Natural, modulus: &Natural) -> Option<Natural> {
+    if *modulus == 1u32 {
+        Some(Natural::ZERO)
+    } else if value == 0u32 {
+        None
+    } else {
+        value.mod_inverse(modulus)
+    }
+}
+
+fn not_invertible(value: &Integer, modulus: &Integer) -> Fault {
+    Fault::InvalidOperation(format!("{} has no inverse modulo {}", value, modulus))
+}
+
+/// Bounds the size of a power of a base with `bits` significant bits. Powers of 0, 1 and -1 never grow.
+fn check_pow_bits(bits: u64, exponent: u64) -> Result<(), Fault> {
+    if bits <= 1 {
+        return Ok(());
+    }
+    check_big_bits(bits.checked_mul(exponent))
+}
+
+/// Computes the binomial coefficient of values that fit in 64 bits, faulting with `Overflow` as
+/// soon as it no longer fits in 64 bits either. `k` is at most `n / 2`, where the coefficients
+/// grow with every step, so this stops after a few dozen steps for any large result.
+fn small_binomial(n: &Natural, k: &Natural) -> Result<u64, Fault> {
+    let n = u64::try_from(n).map_err(|_| Fault::Overflow)? as u128;
+    let k = u64::try_from(k).map_err(|_| Fault::Overflow)? as u128;
+    let mut result: u128 = 1;
+    for i in 1..=k {
+        result = result * (n - k + i) / i;
+        if result > u64::MAX as u128 {
+            return Err(Fault::Overflow);
+        }
+    }
+    Ok(result as u64)
+}
+
+/// Reads a bit index, which can have any integer type. Indices past `u64::MAX` are clamped,
+/// every bit that far out is a copy of the sign bit anyway.
+fn bit_index(index: &Integer) -> Result<u64, Fault> {
+    if index.is_negative() {
+        return Err(Fault::InvalidOperation(format!("Bit index {} is negative", index)));
+    }
+    Ok(u64::try_from(&index.to_big_integer()).unwrap_or(u64::MAX))
 }
 
-macro_rules! generate_shift {
+/// Naturals have no width to wrap around in, so only saturating subtraction goes below zero without a fault.
+fn natural_subtract(left: Natural, right: Natural, mode: OverflowMode) -> Result<(Integer, bool), Fault> {
+    if left >= right {
+        Ok((Integer::Natural(left - right), false))
+    } else if mode == OverflowMode::Saturating {
+        Ok((Integer::Natural(Natural::ZERO), true))
+    } else {
+        Err(Fault::Underflow)
+    }
+}
+
+macro_rules! generate_op {
     ($Opname:ident, $fun_name:ident, $op:tt) => {
         impl std::ops::$Opname for Integer {
             type Output = Self;
-
+        
             fn $fun_name(self, rhs: Self) -> Self::Output {
                 match (self, rhs) {
                     (Integer::U8(left), Integer::U8(right)) => Integer::U8(left $op right),
@@ -207,14 +954,15 @@ macro_rules! generate_shift {
                     (Integer::I16(left), Integer::I16(right)) => Integer::I16(left $op right),
                     (Integer::I32(left), Integer::I32(right)) => Integer::I32(left $op right),
                     (Integer::I64(left), Integer::I64(right)) => Integer::I64(left $op right),
-                    (x, y) => panic!("Cannot perform op with {:?} and {:?}", x, y),
+                    (Integer::Natural(left), Integer::Natural(right)) => Integer::Natural(left $op right),
+                    (Integer::Integer(left), Integer::Integer(right)) => Integer::Integer(left $op right),
+                    (x, y) => panic!("Cannot add {:?} and {:?}", x, y),
                 }
             }
         }
     }
 }
 
-
 generate_op!(Add, add, +);
 generate_op!(Sub, sub, -);
 generate_op!(Mul, mul, *);
@@ -223,28 +971,41 @@ generate_op!(Rem, rem, %);
 generate_op!(BitAnd, bitand, &);
 generate_op!(BitOr, bitor, |);
 generate_op!(BitXor, bitxor, ^);
-generate_shift!(Shl, shl, <<);
-generate_shift!(Shr, shr, >>);
+
+/// Wraps like the other operators on fixed width types. The amount can have any type.
+impl Shl for Integer {
+    type Output = Self;
+
+    fn shl(self, rhs: Self) -> Self::Output {
+        match self.overflowing_shl(rhs, OverflowMode::Wrapping) {
+            Ok((result, _)) => result,
+            Err(fault) => panic!("Cannot shift left: {:?}", fault),
+        }
+    }
+}
+
+/// The amount can have any type.
+impl Shr for Integer {
+    type Output = Self;
+
+    fn shr(self, rhs: Self) -> Self::Output {
+        match self.shift_right(rhs) {
+            Ok(result) => result,
+            Err(fault) => panic!("Cannot shift right: {:?}", fault),
+        }
+    }
+}
 
 impl Pow<Integer> for Integer {
     type Output = Self;
 
+    /// Wraps like the other operators on fixed width types. The exponent can have any type.
     fn pow(self, rhs: Integer) -> Self::Output {
-        match (self, rhs) {
-            (Integer::U8(left), Integer::U32(right)) => Integer::U8(left.pow(right)),
-            (Integer::U16(left), Integer::U32(right)) => Integer::U16(left.pow(right)),
-            (Integer::U32(left), Integer::U32(right)) => Integer::U32(left.pow(right)),
-            (Integer::U64(left), Integer::U32(right)) => Integer::U64(left.pow(right)),
-            (Integer::I8(left), Integer::U32(right)) => Integer::I8(left.pow(right)),
-            (Integer::I16(left), Integer::U32(right)) => Integer::I16(left.pow(right)),
-            (Integer::I32(left), Integer::U32(right)) => Integer::I32(left.pow(right)),
-            (Integer::I64(left), Integer::U32(right)) => Integer::I64(left.pow(right)),
-            (Integer::Natural(left), Integer::U64(right)) => Integer::Natural(left.pow(right)),
-            (Integer::Integer(left), Integer::U64(right)) => Integer::Integer(left.pow(right)),
-            (x, y) => panic!("Cannot pow {:?} and {:?}", x, y),
+        match self.overflowing_pow(rhs, OverflowMode::Wrapping) {
+            Ok((result, _)) => result,
+            Err(fault) => panic!("Cannot pow: {:?}", fault),
         }
     }
-
 }
 
 impl Neg for Integer {
@@ -259,7 +1020,7 @@ impl Neg for Integer {
                     if value > 128 {
                         Integer::I16(-(value as i16))
                     } else {
-                        Integer::I8(-(value as i8))
+                        Integer::I8((value as i8).wrapping_neg())
                     }
                 }
             }
@@ -270,7 +1031,7 @@ impl Neg for Integer {
                     if value > 32768 {
                         Integer::I32(-(value as i32))
                     } else {
-                        Integer::I16(-(value as i16))
+                        Integer::I16((value as i16).wrapping_neg())
                     }
                 }
             }
@@ -281,7 +1042,7 @@ impl Neg for Integer {
                     if value > 2147483648 {
                         Integer::I64(-(value as i64))
                     } else {
-                        Integer::I32(-(value as i32))
+                        Integer::I32((value as i32).wrapping_neg())
                     }
                 }
             }
@@ -292,7 +1053,7 @@ impl Neg for Integer {
                     if value > 9223372036854775808 {
                         Integer::Integer(-<u64 as Into<malachite::Integer>>::into(value))
                     } else {
-                        Integer::I64(-(value as i64))
+                        Integer::I64((value as i64).wrapping_neg())
                     }
                 }
             }
@@ -324,20 +1085,8 @@ impl Neg for Integer {
                     Integer::I64(-value)
                 }
             }
-            Integer::Natural(value) => {
-                if value == Natural::ZERO {
-                    Integer::Natural(Natural::ZERO)
-                } else {
-                    Integer::Integer(-<Natural as Into<malachite::Integer>>::into(value))
-                }
-            }
-            Integer::Integer(value) => {
-                if value == malachite::Integer::ZERO {
-                    Integer::Integer(malachite::Integer::ZERO)
-                } else {
-                    Integer::Integer(-value)
-                }
-            }
+            Integer::Natural(value) => Integer::Integer(-malachite::Integer::from(value)),
+            Integer::Integer(value) => Integer::Integer(-value),
         }
     }
 }
@@ -355,8 +1104,9 @@ impl Not for Integer {
             Integer::I16(value) => Integer::I16(!value),
             Integer::I32(value) => Integer::I32(!value),
             Integer::I64(value) => Integer::I64(!value),
+            // The complement of a natural number is negative, like `-n - 1` for integers.
+            Integer::Natural(value) => Integer::Integer(!malachite::Integer::from(value)),
             Integer::Integer(value) => Integer::Integer(!value),
-            x => panic!("Cannot not {:?}", x),
         }
     }
 }
@@ -398,6 +1148,25 @@ impl PartialOrd<Integer> for Integer {
     }
 }
 
+/// Includes the type, since integers of different types are never equal as values.
+impl Hash for Integer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Integer::U8(value) => value.hash(state),
+            Integer::U16(value) => value.hash(state),
+            Integer::U32(value) => value.hash(state),
+            Integer::U64(value) => value.hash(state),
+            Integer::I8(value) => value.hash(state),
+            Integer::I16(value) => value.hash(state),
+            Integer::I32(value) => value.hash(state),
+            Integer::I64(value) => value.hash(state),
+            Integer::Natural(value) => value.hash(state),
+            Integer::Integer(value) => value.hash(state),
+        }
+    }
+}
+
 
 macro_rules! generate_from_base {
     ($id:ident, $type:ty) => {
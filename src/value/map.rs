@@ -0,0 +1,118 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
+use fxhash::{FxHashMap, FxHasher};
+use crate::machine::Fault;
+use crate::stack::StackChunk;
+use crate::value::tuple::Tuple;
+use crate::value::Value;
+
+/// A dictionary keyed by any value that can be compared.
+///
+/// Keys follow `Value` equality, so `1u8` and `1i64` are different keys. Keys that hold a
+/// function or a NaN are rejected with `NotComparable` since they could never be found again.
+#[derive(Clone, Default)]
+pub struct Map {
+    data: FxHashMap<Value, Value>,
+}
+
+impl Map {
+
+    pub fn new() -> Map {
+        Map {
+            data: FxHashMap::default(),
+        }
+    }
+
+    pub fn get(&self, key: &Value) -> Result<&Value, Fault> {
+        self.data.get(key).ok_or(Fault::KeyNotFound)
+    }
+
+    /// Inserts or replaces the value for `key`, returning the old one.
+    pub fn insert(&mut self, key: Value, value: Value) -> Result<Option<Value>, Fault> {
+        if key.contains_function() || key.contains_nan() {
+            return Err(Fault::NotComparable);
+        }
+        Ok(self.data.insert(key, value))
+    }
+
+    pub fn remove(&mut self, key: &Value) -> Result<Value, Fault> {
+        self.data.remove(key).ok_or(Fault::KeyNotFound)
+    }
+
+    pub fn contains(&self, key: &Value) -> bool {
+        self.data.contains_key(key)
+    }
+
+    pub fn length(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The keys in iteration order, which is the same order `values` uses while the map is unchanged.
+    pub fn keys(&self) -> Tuple {
+        Tuple::new(self.data.keys().cloned().collect())
+    }
+
+    pub fn values(&self) -> Tuple {
+        Tuple::new(self.data.values().cloned().collect())
+    }
+
+    pub fn contains_function(&self) -> bool {
+        self.data.values().any(Value::contains_function)
+    }
+
+    pub fn contains_nan(&self) -> bool {
+        self.data.values().any(Value::contains_nan)
+    }
+
+    pub fn into_chunk(self) -> Box<dyn StackChunk> {
+        Box::new(self)
+    }
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Map) -> bool {
+        self.data == other.data
+    }
+}
+
+/// Maps have no order, they are only ever equal or unordered.
+impl PartialOrd for Map {
+    fn partial_cmp(&self, other: &Map) -> Option<Ordering> {
+        if self == other { Some(Ordering::Equal) } else { None }
+    }
+}
+
+/// Combines the hashes of the entries with a sum, so the result doesn't depend on iteration order.
+impl Hash for Map {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut sum = 0u64;
+        for entry in self.data.iter() {
+            let mut hasher = FxHasher::default();
+            entry.hash(&mut hasher);
+            sum = sum.wrapping_add(hasher.finish());
+        }
+        self.data.len().hash(state);
+        sum.hash(state);
+    }
+}
+
+impl Display for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut entries = String::new();
+        for (key, value) in self.data.iter() {
+            entries.push_str(&format!("{}: {}, ", key, value));
+        }
+        write!(f, "{{{}}}", entries)
+    }
+}
+
+impl Debug for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut entries = String::new();
+        for (key, value) in self.data.iter() {
+            entries.push_str(&format!("{:?}: {:?}, ", key, value));
+        }
+        write!(f, "{{{}}}", entries)
+    }
+}
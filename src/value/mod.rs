@@ -1,8 +1,15 @@
+use std::cmp::Ordering;
 use std::fmt::{Debug, Display, LowerHex};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use crate::machine::Fault;
 use crate::stack::StackChunk;
+use crate::value::array::Array;
+use crate::value::big_decimal::BigDecimal;
 use crate::value::decimal::{Decimal, DecimalType};
 use crate::value::function::Function;
 use crate::value::integer::{Integer, IntegerType};
+use crate::value::map::Map;
 use crate::value::product::ProductType;
 use crate::value::sum::SumType;
 use crate::value::tuple::Tuple;
@@ -10,10 +17,14 @@ use crate::value::vector::{Vector, VectorType};
 
 pub mod integer;
 pub mod decimal;
+pub mod big_decimal;
 pub mod vector;
+pub mod vector_math;
+pub mod array;
 pub mod product;
 pub mod sum;
 pub mod tuple;
+pub mod map;
 pub mod function;
 
 #[derive(Debug, Clone)]
@@ -22,11 +33,13 @@ pub enum ValueType {
     Integer(IntegerType),
     Decimal(DecimalType),
     Vector(VectorType),
+    Array(VectorType),
     Product,
     Sum,
     Function,
     Reference,
     Tuple,
+    Map,
     Character,
     Boolean,
 }
@@ -38,11 +51,13 @@ impl Display for ValueType {
             ValueType::Integer(integer) => write!(f, "Integer: {}", integer),
             ValueType::Decimal(float) => write!(f, "Decimal: {}", float),
             ValueType::Vector(vector) => write!(f, "Vector: {}", vector),
+            ValueType::Array(array) => write!(f, "Array: {}", array),
             ValueType::Product => write!(f, "Product"),
             ValueType::Sum => write!(f, "Sum"),
             ValueType::Function => write!(f, "Function"),
             ValueType::Reference => write!(f, "Reference"),
             ValueType::Tuple => write!(f, "Tuple"),
+            ValueType::Map => write!(f, "Map"),
             ValueType::Character => write!(f, "Character"),
             ValueType::Boolean => write!(f, "Boolean"),
         }
@@ -66,26 +81,31 @@ impl Debug for Reference {
 
 #[derive(Debug, Clone)]
 pub enum Value {
-    String(String),
+    String(Arc<str>),
     Integer(Integer),
     Decimal(Decimal),
     Vector(Vector),
+    Array(Array),
     Product(ProductType),
     Sum(SumType),
     Function(Function),
     Reference(Reference),
     Tuple(Tuple),
+    Map(Map),
     Character(char),
     Boolean(bool),
 }
 
 impl Value {
 
-    pub fn cast(self, into_type: ValueType) -> Value {
-        match self {
-            Value::Integer(integer) => integer.cast(into_type),
-            Value::Decimal(decimal) => decimal.cast(into_type),
-            x => panic!("Cannot cast {:?} to {:?}", x, into_type),
+    /// Converts the value to `into_type`, see `Integer::cast`, `Decimal::cast` and `Vector::cast`.
+    /// Faults with `TypeMismatch` for any other value.
+    pub fn cast(self, into_type: ValueType) -> Result<Value, Fault> {
+        match (self, &into_type) {
+            (Value::Integer(integer), _) => integer.cast(into_type),
+            (Value::Decimal(decimal), _) => decimal.cast(into_type),
+            (Value::Vector(vector), ValueType::Vector(typ)) => Ok(Value::Vector(vector.cast(typ)?)),
+            _ => Err(Fault::TypeMismatch),
         }
     }
 
@@ -95,29 +115,64 @@ impl Value {
             Value::Integer(integer) => integer.into_chunk(),
             Value::Decimal(decimal) => decimal.into_chunk(),
             Value::Vector(vector) => vector.into_chunk(),
+            Value::Array(array) => array.into_chunk(),
             Value::Product(product) => product.into_chunk(),
             Value::Sum(sum) => sum.into_chunk(),
             Value::Function(function) => function.into_chunk(),
             Value::Reference(reference) => Box::new(reference),
             Value::Tuple(tuple) => tuple.into_chunk(),
+            Value::Map(map) => map.into_chunk(),
             Value::Character(character) => Box::new(character),
             Value::Boolean(boolean) => Box::new(boolean),
         }
     }
+
+    /// Whether this is a function or a compound value holding one. Functions can't be compared.
+    pub fn contains_function(&self) -> bool {
+        match self {
+            Value::Vector(vector) => vector.contains_function(),
+            Value::Array(array) => array.contains_function(),
+            Value::Product(product) => product.contains_function(),
+            Value::Sum(sum) => sum.contains_function(),
+            Value::Function(_) => true,
+            Value::Tuple(tuple) => tuple.contains_function(),
+            Value::Map(map) => map.contains_function(),
+            _ => false,
+        }
+    }
+
+    /// Whether this is a float NaN or a compound value holding one. NaN isn't equal to itself.
+    pub fn contains_nan(&self) -> bool {
+        match self {
+            Value::Decimal(Decimal::F32(value)) => value.is_nan(),
+            Value::Decimal(Decimal::F64(value)) => value.is_nan(),
+            Value::Vector(vector) => vector.contains_nan(),
+            Value::Array(array) => array.contains_nan(),
+            Value::Product(product) => product.contains_nan(),
+            Value::Sum(sum) => sum.contains_nan(),
+            Value::Tuple(tuple) => tuple.contains_nan(),
+            Value::Map(map) => map.contains_nan(),
+            _ => false,
+        }
+    }
 }
 
+/// Values of different kinds are never equal. Numbers must also have the same type, so inside
+/// compound values `1u8` and `1i64` differ instead of panicking.
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Value::String(string), Value::String(other_string)) => string == other_string,
-            (Value::Integer(integer), Value::Integer(other_integer)) => integer == other_integer,
-            (Value::Decimal(decimal), Value::Decimal(other_decimal)) => decimal == other_decimal,
-            (Value::Vector(vector), Value::Vector(other_vector)) => todo!("Vector equality"),
-            (Value::Product(product), Value::Product(other_product)) => todo!("Product equality"),
-            (Value::Sum(sum), Value::Sum(other_sum)) => todo!("Sum equality"),
-            (Value::Function(function), Value::Function(other_function)) => todo!("Function equality"),
-            (Value::Reference(reference), Value::Reference(other_reference)) => todo!("Reference equality"),
-            (Value::Tuple(tuple), Value::Tuple(other_tuple)) => todo!("Tuple equality"),
+            (Value::Integer(integer), Value::Integer(other_integer)) => integer.get_type() == other_integer.get_type() && integer == other_integer,
+            (Value::Decimal(decimal), Value::Decimal(other_decimal)) => decimal.get_type() == other_decimal.get_type() && decimal == other_decimal,
+            (Value::Vector(vector), Value::Vector(other_vector)) => vector == other_vector,
+            (Value::Array(array), Value::Array(other_array)) => array == other_array,
+            (Value::Product(product), Value::Product(other_product)) => product == other_product,
+            (Value::Sum(sum), Value::Sum(other_sum)) => sum == other_sum,
+            (Value::Function(function), Value::Function(other_function)) => function == other_function,
+            (Value::Reference(reference), Value::Reference(other_reference)) => reference == other_reference,
+            (Value::Tuple(tuple), Value::Tuple(other_tuple)) => tuple == other_tuple,
+            (Value::Map(map), Value::Map(other_map)) => map == other_map,
             (Value::Character(character), Value::Character(other_character)) => character == other_character,
             (Value::Boolean(boolean), Value::Boolean(other_boolean)) => boolean == other_boolean,
             _ => false,
@@ -125,18 +180,34 @@ impl PartialEq for Value {
     }
 }
 
+/// Lets values be map keys. NaN is the only value not equal to itself, so maps reject keys holding one.
+impl Eq for Value {}
+
+/// Compound values are ordered lexicographically. Values of different kinds or number types are unordered.
 impl PartialOrd for Value {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
             (Value::String(string), Value::String(other_string)) => string.partial_cmp(other_string),
-            (Value::Integer(integer), Value::Integer(other_integer)) => integer.partial_cmp(other_integer),
-            (Value::Decimal(decimal), Value::Decimal(other_decimal)) => decimal.partial_cmp(other_decimal),
-            (Value::Vector(vector), Value::Vector(other_vector)) => todo!(),
-            (Value::Product(product), Value::Product(other_product)) => todo!(),
-            (Value::Sum(sum), Value::Sum(other_sum)) => todo!(),
-            (Value::Function(function), Value::Function(other_function)) => todo!(),
-            (Value::Reference(reference), Value::Reference(other_reference)) => todo!(),
-            (Value::Tuple(tuple), Value::Tuple(other_tuple)) => todo!(),
+            (Value::Integer(integer), Value::Integer(other_integer)) => {
+                if integer.get_type() != other_integer.get_type() {
+                    return None;
+                }
+                integer.partial_cmp(other_integer)
+            },
+            (Value::Decimal(decimal), Value::Decimal(other_decimal)) => {
+                if decimal.get_type() != other_decimal.get_type() {
+                    return None;
+                }
+                decimal.partial_cmp(other_decimal)
+            },
+            (Value::Vector(vector), Value::Vector(other_vector)) => vector.partial_cmp(other_vector),
+            (Value::Array(array), Value::Array(other_array)) => array.partial_cmp(other_array),
+            (Value::Product(product), Value::Product(other_product)) => product.partial_cmp(other_product),
+            (Value::Sum(sum), Value::Sum(other_sum)) => sum.partial_cmp(other_sum),
+            (Value::Function(function), Value::Function(other_function)) => function.partial_cmp(other_function),
+            (Value::Reference(reference), Value::Reference(other_reference)) => reference.partial_cmp(other_reference),
+            (Value::Tuple(tuple), Value::Tuple(other_tuple)) => tuple.partial_cmp(other_tuple),
+            (Value::Map(map), Value::Map(other_map)) => map.partial_cmp(other_map),
             (Value::Character(character), Value::Character(other_character)) => character.partial_cmp(other_character),
             (Value::Boolean(boolean), Value::Boolean(other_boolean)) => boolean.partial_cmp(other_boolean),
             _ => None,
@@ -144,6 +215,28 @@ impl PartialOrd for Value {
     }
 }
 
+/// Consistent with `PartialEq`: equal values hash the same.
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::String(string) => string.hash(state),
+            Value::Integer(integer) => integer.hash(state),
+            Value::Decimal(decimal) => decimal.hash(state),
+            Value::Vector(vector) => vector.hash(state),
+            Value::Array(array) => array.hash(state),
+            Value::Product(product) => product.hash(state),
+            Value::Sum(sum) => sum.hash(state),
+            Value::Function(function) => function.hash(state),
+            Value::Reference(reference) => reference.hash(state),
+            Value::Tuple(tuple) => tuple.hash(state),
+            Value::Map(map) => map.hash(state),
+            Value::Character(character) => character.hash(state),
+            Value::Boolean(boolean) => boolean.hash(state),
+        }
+    }
+}
+
 
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -152,13 +245,89 @@ impl Display for Value {
             Value::Integer(integer) => write!(f, "{}", integer),
             Value::Decimal(float) => write!(f, "{}", float),
             Value::Vector(vector) => write!(f, "{}", vector),
+            Value::Array(array) => write!(f, "{}", array),
             Value::Product(product) => write!(f, "{}", product),
             Value::Sum(sum) => write!(f, "{}", sum),
             Value::Function(function) => write!(f, "{}", function),
             Value::Reference(reference) => write!(f, "{}", reference),
             Value::Tuple(tuple) => write!(f, "{}", tuple),
+            Value::Map(map) => write!(f, "{}", map),
             Value::Character(character) => write!(f, "{}", character),
             Value::Boolean(boolean) => write!(f, "{}", boolean),
         }
     }
-}
\ No newline at end of file
+}
+macro_rules! generate_value_conversions {
+    ($variant:ident, $inner:ident, $id:ident, $type:ty) => {
+        impl From<$type> for Value {
+            fn from(value: $type) -> Self {
+                Value::$variant($inner::$id(value))
+            }
+        }
+
+        impl TryFrom<Value> for $type {
+            type Error = Fault;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                match value {
+                    Value::$variant($inner::$id(value)) => Ok(value),
+                    _ => Err(Fault::TypeMismatch),
+                }
+            }
+        }
+    };
+    ($variant:ident, $type:ty) => {
+        impl From<$type> for Value {
+            fn from(value: $type) -> Self {
+                Value::$variant(value)
+            }
+        }
+
+        impl TryFrom<Value> for $type {
+            type Error = Fault;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                match value {
+                    Value::$variant(value) => Ok(value),
+                    _ => Err(Fault::TypeMismatch),
+                }
+            }
+        }
+    };
+}
+
+generate_value_conversions!(Integer, Integer, U8, u8);
+generate_value_conversions!(Integer, Integer, U16, u16);
+generate_value_conversions!(Integer, Integer, U32, u32);
+generate_value_conversions!(Integer, Integer, U64, u64);
+generate_value_conversions!(Integer, Integer, I8, i8);
+generate_value_conversions!(Integer, Integer, I16, i16);
+generate_value_conversions!(Integer, Integer, I32, i32);
+generate_value_conversions!(Integer, Integer, I64, i64);
+generate_value_conversions!(Integer, Integer, Natural, malachite::Natural);
+generate_value_conversions!(Integer, Integer, Integer, malachite::Integer);
+generate_value_conversions!(Decimal, Decimal, F32, f32);
+generate_value_conversions!(Decimal, Decimal, F64, f64);
+generate_value_conversions!(Decimal, Decimal, Rational, malachite::Rational);
+generate_value_conversions!(Decimal, Decimal, BigDecimal, BigDecimal);
+generate_value_conversions!(String, Arc<str>);
+generate_value_conversions!(Integer, Integer);
+generate_value_conversions!(Decimal, Decimal);
+generate_value_conversions!(Vector, Vector);
+generate_value_conversions!(Array, Array);
+generate_value_conversions!(Tuple, Tuple);
+generate_value_conversions!(Map, Map);
+generate_value_conversions!(Character, char);
+generate_value_conversions!(Boolean, bool);
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value.into())
+    }
+}
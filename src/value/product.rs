@@ -1,5 +1,7 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
 use crate::stack::StackChunk;
 use crate::value::Value;
 
@@ -11,9 +13,52 @@ pub struct ProductType {
 
 impl ProductType {
 
+    /// A product with no name or fields, used to fill new vectors.
+    pub fn empty() -> ProductType {
+        ProductType {
+            name: "".into(),
+            fields: HashMap::new(),
+        }
+    }
+
     pub fn into_chunk(self) -> Box<dyn StackChunk> {
         Box::new(self)
     }
+
+    pub fn contains_function(&self) -> bool {
+        self.fields.values().any(Value::contains_function)
+    }
+
+    pub fn contains_nan(&self) -> bool {
+        self.fields.values().any(Value::contains_nan)
+    }
+
+    /// The fields ordered by name, so comparing and hashing don't depend on the map's order.
+    fn sorted_fields(&self) -> Vec<(&str, &Value)> {
+        let mut fields = self.fields.iter().map(|(name, value)| (&**name, value)).collect::<Vec<_>>();
+        fields.sort_by_key(|(name, _)| *name);
+        fields
+    }
+}
+
+impl PartialEq for ProductType {
+    fn eq(&self, other: &ProductType) -> bool {
+        self.name == other.name && self.fields == other.fields
+    }
+}
+
+/// Orders by type name, then by the fields in name order.
+impl PartialOrd for ProductType {
+    fn partial_cmp(&self, other: &ProductType) -> Option<Ordering> {
+        (&self.name, self.sorted_fields()).partial_cmp(&(&other.name, other.sorted_fields()))
+    }
+}
+
+impl Hash for ProductType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.sorted_fields().hash(state);
+    }
 }
 
 impl Display for ProductType {
@@ -1,5 +1,7 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
 use crate::stack::StackChunk;
 use crate::value::Value;
 
@@ -13,9 +15,54 @@ pub struct SumType {
 
 impl SumType {
 
+    /// A sum with no name or fields and tag zero, used to fill new vectors.
+    pub fn empty() -> SumType {
+        SumType {
+            name: "".into(),
+            tag: 0,
+            fields: HashMap::new(),
+        }
+    }
+
     pub fn into_chunk(self) -> Box<dyn StackChunk> {
         Box::new(self)
     }
+
+    pub fn contains_function(&self) -> bool {
+        self.fields.values().any(Value::contains_function)
+    }
+
+    pub fn contains_nan(&self) -> bool {
+        self.fields.values().any(Value::contains_nan)
+    }
+
+    /// The fields ordered by name, so comparing and hashing don't depend on the map's order.
+    fn sorted_fields(&self) -> Vec<(&str, &Value)> {
+        let mut fields = self.fields.iter().map(|(name, value)| (&**name, value)).collect::<Vec<_>>();
+        fields.sort_by_key(|(name, _)| *name);
+        fields
+    }
+}
+
+impl PartialEq for SumType {
+    fn eq(&self, other: &SumType) -> bool {
+        self.name == other.name && self.tag == other.tag && self.fields == other.fields
+    }
+}
+
+/// Orders by type name, then by tag, then by the fields in name order.
+impl PartialOrd for SumType {
+    fn partial_cmp(&self, other: &SumType) -> Option<Ordering> {
+        (&self.name, self.tag, self.sorted_fields()).partial_cmp(&(&other.name, other.tag, other.sorted_fields()))
+    }
+}
+
+impl Hash for SumType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.tag.hash(state);
+        self.sorted_fields().hash(state);
+    }
 }
 
 impl Display for SumType {
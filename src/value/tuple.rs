@@ -1,33 +1,67 @@
 use std::fmt::{Debug, Display};
+use std::sync::Arc;
+use crate::machine::Fault;
 use crate::stack::StackChunk;
+use crate::value::vector::Vector;
 use crate::value::Value;
 
-#[derive(Clone)]
+/// A fixed list of values, shared so that cloning a tuple is cheap.
+#[derive(Clone, PartialEq, PartialOrd, Hash)]
 pub struct Tuple {
-    pub data: Box<[Value]>
+    pub data: Arc<[Value]>
 }
 
 impl Tuple {
 
     pub fn new(data: Box<[Value]>) -> Tuple {
         Tuple {
-            data,
+            data: data.into(),
         }
     }
 
     pub fn empty() -> Tuple {
         Tuple {
-            data: Box::new([]),
+            data: Arc::new([]),
         }
     }
 
-    pub fn get(&self, index: usize) -> &Value {
-        &self.data[index]
+    /// Reads the elements of any vector.
+    pub fn from_vector(vector: &Vector) -> Result<Tuple, Fault> {
+        let data = (0..vector.length()).map(|index| vector.get(index)).collect::<Result<Vec<_>, Fault>>()?;
+        Ok(Tuple::new(data.into_boxed_slice()))
+    }
+
+    pub fn get(&self, index: usize) -> Result<&Value, Fault> {
+        self.data.get(index).ok_or(Fault::OutOfBounds)
+    }
+
+    /// A new tuple with the element at `index` replaced, leaving this one unchanged.
+    pub fn set(&self, index: usize, value: Value) -> Result<Tuple, Fault> {
+        let mut data = self.data.to_vec();
+        *data.get_mut(index).ok_or(Fault::OutOfBounds)? = value;
+        Ok(Tuple::new(data.into_boxed_slice()))
+    }
+
+    pub fn length(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The elements in an `Any` vector.
+    pub fn to_vector(&self) -> Vector {
+        Vector::Any(Arc::new(self.data.to_vec()))
     }
 
     pub fn into_chunk(self) -> Box<dyn StackChunk> {
         Box::new(self)
     }
+
+    pub fn contains_function(&self) -> bool {
+        self.data.iter().any(Value::contains_function)
+    }
+
+    pub fn contains_nan(&self) -> bool {
+        self.data.iter().any(Value::contains_nan)
+    }
 }
 
 impl Display for Tuple {
@@ -1,14 +1,20 @@
 use std::alloc::Layout;
+use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use malachite::Natural;
 use malachite::num::basic::traits::Zero;
 use crate::machine::Fault;
 use crate::stack::StackChunk;
-use crate::stack::vector_chunk::{F32Vector, F64Vector, FunctionVector, I16Vector, I32Vector, I64Vector, I8Vector, IntegerVector, NaturalVector, RationalVector, ReferenceVector, TupleVector, U16Vector, U32Vector, U64Vector, U8Vector, VectorVector};
+use crate::stack::vector_chunk::{F32Vector, F64Vector, FunctionVector, I16Vector, I32Vector, I64Vector, I8Vector, IntegerVector, NaturalVector, RationalVector, BigDecimalVector, ReferenceVector, TupleVector, U16Vector, U32Vector, U64Vector, U8Vector, VectorVector, StringVector, BooleanVector, CharacterVector, ProductVector, SumVector, AnyVector};
 use crate::value::{Reference, Value, ValueType};
-use crate::value::decimal::{Decimal, DecimalType};
+use crate::value::big_decimal::BigDecimal;
+use crate::value::decimal::{float_bits, Decimal, DecimalType};
 use crate::value::function::Function;
 use crate::value::integer::{Integer, IntegerType};
+use crate::value::product::ProductType;
+use crate::value::sum::SumType;
 use crate::value::tuple::Tuple;
 
 #[derive(Debug, Clone)]
@@ -26,9 +32,16 @@ pub enum VectorType {
     Natural(usize),
     Integer(usize),
     Rational(usize),
+    BigDecimal(usize),
     Reference(usize),
     Tuple(usize),
     Function(usize),
+    String(usize),
+    Boolean(usize),
+    Character(usize),
+    Product(usize),
+    Sum(usize),
+    Any(usize),
     Vector(Box<VectorType>, usize),
 }
 
@@ -49,10 +62,17 @@ impl VectorType {
             VectorType::Natural(size) => *size,
             VectorType::Integer(size) => *size,
             VectorType::Rational(size) => *size,
+            VectorType::BigDecimal(size) => *size,
             VectorType::Reference(size) => *size,
             VectorType::Vector(_, size) => *size,
             VectorType::Tuple(size) => *size,
             VectorType::Function(size) => *size,
+            VectorType::String(size) => *size,
+            VectorType::Boolean(size) => *size,
+            VectorType::Character(size) => *size,
+            VectorType::Product(size) => *size,
+            VectorType::Sum(size) => *size,
+            VectorType::Any(size) => *size,
         }
     }
 
@@ -71,8 +91,15 @@ impl VectorType {
             VectorType::Natural(size) => Layout::array::<Natural>(*size).unwrap(),
             VectorType::Integer(size) => Layout::array::<malachite::Integer>(*size).unwrap(),
             VectorType::Rational(size) => Layout::array::<malachite::Rational>(*size).unwrap(),
+            VectorType::BigDecimal(size) => Layout::array::<BigDecimal>(*size).unwrap(),
             VectorType::Tuple(size) => Layout::array::<Tuple>(*size).unwrap(),
             VectorType::Function(size) => Layout::array::<Function>(*size).unwrap(),
+            VectorType::String(size) => Layout::array::<Arc<str>>(*size).unwrap(),
+            VectorType::Boolean(size) => Layout::array::<bool>(*size).unwrap(),
+            VectorType::Character(size) => Layout::array::<char>(*size).unwrap(),
+            VectorType::Product(size) => Layout::array::<ProductType>(*size).unwrap(),
+            VectorType::Sum(size) => Layout::array::<SumType>(*size).unwrap(),
+            VectorType::Any(size) => Layout::array::<Value>(*size).unwrap(),
             VectorType::Reference(size) => Layout::array::<Reference>(*size).unwrap(),
             _ => panic!("Cannot get layout of VectorType::Vector"),
         }
@@ -92,10 +119,17 @@ impl VectorType {
             VectorType::Natural(_) => Layout::array::<Natural>(size).unwrap(),
             VectorType::Integer(_) => Layout::array::<malachite::Integer>(size).unwrap(),
             VectorType::Rational(_) => Layout::array::<malachite::Rational>(size).unwrap(),
+            VectorType::BigDecimal(_) => Layout::array::<BigDecimal>(size).unwrap(),
             VectorType::Reference(_) => Layout::array::<Reference>(size).unwrap(),
             VectorType::Vector(_,_) => Layout::array::<Vector>(size).unwrap(),
             VectorType::Tuple(_) => Layout::array::<Tuple>(size).unwrap(),
             VectorType::Function(_) => Layout::array::<Function>(size).unwrap(),
+            VectorType::String(_) => Layout::array::<Arc<str>>(size).unwrap(),
+            VectorType::Boolean(_) => Layout::array::<bool>(size).unwrap(),
+            VectorType::Character(_) => Layout::array::<char>(size).unwrap(),
+            VectorType::Product(_) => Layout::array::<ProductType>(size).unwrap(),
+            VectorType::Sum(_) => Layout::array::<SumType>(size).unwrap(),
+            VectorType::Any(_) => Layout::array::<Value>(size).unwrap(),
         }
     }
 }
@@ -116,58 +150,213 @@ impl Display for VectorType {
             VectorType::Natural(size) => write!(f, "Natural x {}", size),
             VectorType::Integer(size) => write!(f, "Integer x {}", size),
             VectorType::Rational(size) => write!(f, "Rational x {}", size),
+            VectorType::BigDecimal(size) => write!(f, "BigDecimal x {}", size),
             VectorType::Reference(size) => write!(f, "Reference x {}", size),
             VectorType::Vector(typ, size) => write!(f, "Vector of {} x {}", typ, size),
             VectorType::Tuple(size) => write!(f, "Tuple x {}", size),
             VectorType::Function(size) => write!(f, "Function x {}", size),
+            VectorType::String(size) => write!(f, "String x {}", size),
+            VectorType::Boolean(size) => write!(f, "Boolean x {}", size),
+            VectorType::Character(size) => write!(f, "Character x {}", size),
+            VectorType::Product(size) => write!(f, "Product x {}", size),
+            VectorType::Sum(size) => write!(f, "Sum x {}", size),
+            VectorType::Any(size) => write!(f, "Any x {}", size),
         }
     }
 }
 
 
+/// Applies `$body` to the elements of any vector.
+macro_rules! each_vector {
+    ($vector:expr, $pointer:ident => $body:expr) => {
+        match $vector {
+            Vector::U8($pointer) => $body,
+            Vector::U16($pointer) => $body,
+            Vector::U32($pointer) => $body,
+            Vector::U64($pointer) => $body,
+            Vector::I8($pointer) => $body,
+            Vector::I16($pointer) => $body,
+            Vector::I32($pointer) => $body,
+            Vector::I64($pointer) => $body,
+            Vector::F32($pointer) => $body,
+            Vector::F64($pointer) => $body,
+            Vector::Natural($pointer) => $body,
+            Vector::Integer($pointer) => $body,
+            Vector::Rational($pointer) => $body,
+            Vector::BigDecimal($pointer) => $body,
+            Vector::Reference($pointer) => $body,
+            Vector::Vector($pointer) => $body,
+            Vector::Tuple($pointer) => $body,
+            Vector::Function($pointer) => $body,
+            Vector::String($pointer) => $body,
+            Vector::Boolean($pointer) => $body,
+            Vector::Character($pointer) => $body,
+            Vector::Product($pointer) => $body,
+            Vector::Sum($pointer) => $body,
+            Vector::Any($pointer) => $body,
+        }
+    };
+}
+
+/// Applies `$body` to the elements of any vector and wraps the result in the same kind of vector.
+macro_rules! map_vector {
+    ($vector:expr, $pointer:ident => $body:expr) => {
+        match $vector {
+            Vector::U8($pointer) => Vector::U8(Arc::new($body)),
+            Vector::U16($pointer) => Vector::U16(Arc::new($body)),
+            Vector::U32($pointer) => Vector::U32(Arc::new($body)),
+            Vector::U64($pointer) => Vector::U64(Arc::new($body)),
+            Vector::I8($pointer) => Vector::I8(Arc::new($body)),
+            Vector::I16($pointer) => Vector::I16(Arc::new($body)),
+            Vector::I32($pointer) => Vector::I32(Arc::new($body)),
+            Vector::I64($pointer) => Vector::I64(Arc::new($body)),
+            Vector::F32($pointer) => Vector::F32(Arc::new($body)),
+            Vector::F64($pointer) => Vector::F64(Arc::new($body)),
+            Vector::Natural($pointer) => Vector::Natural(Arc::new($body)),
+            Vector::Integer($pointer) => Vector::Integer(Arc::new($body)),
+            Vector::Rational($pointer) => Vector::Rational(Arc::new($body)),
+            Vector::BigDecimal($pointer) => Vector::BigDecimal(Arc::new($body)),
+            Vector::Reference($pointer) => Vector::Reference(Arc::new($body)),
+            Vector::Vector($pointer) => Vector::Vector(Arc::new($body)),
+            Vector::Tuple($pointer) => Vector::Tuple(Arc::new($body)),
+            Vector::Function($pointer) => Vector::Function(Arc::new($body)),
+            Vector::String($pointer) => Vector::String(Arc::new($body)),
+            Vector::Boolean($pointer) => Vector::Boolean(Arc::new($body)),
+            Vector::Character($pointer) => Vector::Character(Arc::new($body)),
+            Vector::Product($pointer) => Vector::Product(Arc::new($body)),
+            Vector::Sum($pointer) => Vector::Sum(Arc::new($body)),
+            Vector::Any($pointer) => Vector::Any(Arc::new($body)),
+        }
+    };
+}
+
+/// Applies `$body` to the elements of a vector and a value of its element type, or faults with `TypeMismatch`.
+macro_rules! with_element {
+    ($vector:expr, $value:expr, $pointer:ident, $element:ident => $body:expr) => {
+        match ($vector, $value) {
+            (Vector::U8($pointer), Value::Integer(Integer::U8($element))) => $body,
+            (Vector::U16($pointer), Value::Integer(Integer::U16($element))) => $body,
+            (Vector::U32($pointer), Value::Integer(Integer::U32($element))) => $body,
+            (Vector::U64($pointer), Value::Integer(Integer::U64($element))) => $body,
+            (Vector::I8($pointer), Value::Integer(Integer::I8($element))) => $body,
+            (Vector::I16($pointer), Value::Integer(Integer::I16($element))) => $body,
+            (Vector::I32($pointer), Value::Integer(Integer::I32($element))) => $body,
+            (Vector::I64($pointer), Value::Integer(Integer::I64($element))) => $body,
+            (Vector::F32($pointer), Value::Decimal(Decimal::F32($element))) => $body,
+            (Vector::F64($pointer), Value::Decimal(Decimal::F64($element))) => $body,
+            (Vector::Natural($pointer), Value::Integer(Integer::Natural($element))) => $body,
+            (Vector::Integer($pointer), Value::Integer(Integer::Integer($element))) => $body,
+            (Vector::Rational($pointer), Value::Decimal(Decimal::Rational($element))) => $body,
+            (Vector::BigDecimal($pointer), Value::Decimal(Decimal::BigDecimal($element))) => $body,
+            (Vector::Reference($pointer), Value::Reference($element)) => $body,
+            (Vector::Vector($pointer), Value::Vector($element)) => $body,
+            (Vector::Tuple($pointer), Value::Tuple($element)) => $body,
+            (Vector::Function($pointer), Value::Function($element)) => $body,
+            (Vector::String($pointer), Value::String($element)) => $body,
+            (Vector::Boolean($pointer), Value::Boolean($element)) => $body,
+            (Vector::Character($pointer), Value::Character($element)) => $body,
+            (Vector::Product($pointer), Value::Product($element)) => $body,
+            (Vector::Sum($pointer), Value::Sum($element)) => $body,
+            (Vector::Any($pointer), $element) => $body,
+            _ => Err(Fault::TypeMismatch),
+        }
+    };
+}
+
+/// Applies `$body` to the elements of two vectors of the same type, or faults with `TypeMismatch`.
+macro_rules! with_same_vectors {
+    ($left_vector:expr, $right_vector:expr, $left:ident, $right:ident => $body:expr) => {
+        match ($left_vector, $right_vector) {
+            (Vector::U8($left), Vector::U8($right)) => $body,
+            (Vector::U16($left), Vector::U16($right)) => $body,
+            (Vector::U32($left), Vector::U32($right)) => $body,
+            (Vector::U64($left), Vector::U64($right)) => $body,
+            (Vector::I8($left), Vector::I8($right)) => $body,
+            (Vector::I16($left), Vector::I16($right)) => $body,
+            (Vector::I32($left), Vector::I32($right)) => $body,
+            (Vector::I64($left), Vector::I64($right)) => $body,
+            (Vector::F32($left), Vector::F32($right)) => $body,
+            (Vector::F64($left), Vector::F64($right)) => $body,
+            (Vector::Natural($left), Vector::Natural($right)) => $body,
+            (Vector::Integer($left), Vector::Integer($right)) => $body,
+            (Vector::Rational($left), Vector::Rational($right)) => $body,
+            (Vector::BigDecimal($left), Vector::BigDecimal($right)) => $body,
+            (Vector::Reference($left), Vector::Reference($right)) => $body,
+            (Vector::Vector($left), Vector::Vector($right)) => $body,
+            (Vector::Tuple($left), Vector::Tuple($right)) => $body,
+            (Vector::Function($left), Vector::Function($right)) => $body,
+            (Vector::String($left), Vector::String($right)) => $body,
+            (Vector::Boolean($left), Vector::Boolean($right)) => $body,
+            (Vector::Character($left), Vector::Character($right)) => $body,
+            (Vector::Product($left), Vector::Product($right)) => $body,
+            (Vector::Sum($left), Vector::Sum($right)) => $body,
+            (Vector::Any($left), Vector::Any($right)) => $body,
+            _ => Err(Fault::TypeMismatch),
+        }
+    };
+}
+
+/// A vector of one element type. The elements are shared behind an `Arc`, so cloning a vector
+/// is cheap, and changing a shared vector copies its elements first.
 #[derive(Clone)]
 pub enum Vector {
-    U8(Vec<u8>),
-    U16(Vec<u16>),
-    U32(Vec<u32>),
-    U64(Vec<u64>),
-    I8(Vec<i8>),
-    I16(Vec<i16>),
-    I32(Vec<i32>),
-    I64(Vec<i64>),
-    F32(Vec<f32>),
-    F64(Vec<f64>),
-    Natural(Vec<Natural>),
-    Integer(Vec<malachite::Integer>),
-    Rational(Vec<malachite::Rational>),
-    Reference(Vec<Reference>),
-    Vector(Vec<Vector>),
-    Tuple(Vec<Tuple>),
-    Function(Vec<Function>),
+    U8(Arc<Vec<u8>>),
+    U16(Arc<Vec<u16>>),
+    U32(Arc<Vec<u32>>),
+    U64(Arc<Vec<u64>>),
+    I8(Arc<Vec<i8>>),
+    I16(Arc<Vec<i16>>),
+    I32(Arc<Vec<i32>>),
+    I64(Arc<Vec<i64>>),
+    F32(Arc<Vec<f32>>),
+    F64(Arc<Vec<f64>>),
+    Natural(Arc<Vec<Natural>>),
+    Integer(Arc<Vec<malachite::Integer>>),
+    Rational(Arc<Vec<malachite::Rational>>),
+    BigDecimal(Arc<Vec<BigDecimal>>),
+    Reference(Arc<Vec<Reference>>),
+    Vector(Arc<Vec<Vector>>),
+    Tuple(Arc<Vec<Tuple>>),
+    Function(Arc<Vec<Function>>),
+    String(Arc<Vec<Arc<str>>>),
+    Boolean(Arc<Vec<bool>>),
+    Character(Arc<Vec<char>>),
+    Product(Arc<Vec<ProductType>>),
+    Sum(Arc<Vec<SumType>>),
+    Any(Arc<Vec<Value>>),
 }
 
 impl Vector {
 
-    pub fn new(size: usize, typ: &VectorType) -> Self {
-        match typ {
-            VectorType::U8(_) => Vector::U8(vec![0; size]),
-            VectorType::U16(_) => Vector::U16(vec![0; size]),
-            VectorType::U32(_) => Vector::U32(vec![0; size]),
-            VectorType::U64(_) => Vector::U64(vec![0; size]),
-            VectorType::I8(_) => Vector::I8(vec![0; size]),
-            VectorType::I16(_) => Vector::I16(vec![0; size]),
-            VectorType::I32(_) => Vector::I32(vec![0; size]),
-            VectorType::I64(_) => Vector::I64(vec![0; size]),
-            VectorType::F32(_) => Vector::F32(vec![0.0; size]),
-            VectorType::F64(_) => Vector::F64(vec![0.0; size]),
-            VectorType::Natural(_) => Vector::Natural(vec![Natural::ZERO; size]),
-            VectorType::Integer(_) => Vector::Integer(vec![malachite::Integer::ZERO; size]),
-            VectorType::Rational(_) => Vector::Rational(vec![malachite::Rational::ZERO; size]),
-            VectorType::Reference(_) => Vector::Reference(vec![Reference(0); size]),
-            VectorType::Vector(typ, sub_size) => Vector::Vector(vec![Vector::new(*sub_size, typ); size]),
-            VectorType::Tuple(_) => Vector::Tuple(vec![Tuple::empty(); size]),
-            VectorType::Function(_) => Vector::Function(vec![Function::empty(); size]),
-        }
+    /// A vector of `size` default elements. Faults with `OutOfMemory` if it can't be allocated.
+    pub fn new(size: usize, typ: &VectorType) -> Result<Self, Fault> {
+        let vector = match typ {
+            VectorType::U8(_) => Vector::U8(filled(0, size)?),
+            VectorType::U16(_) => Vector::U16(filled(0, size)?),
+            VectorType::U32(_) => Vector::U32(filled(0, size)?),
+            VectorType::U64(_) => Vector::U64(filled(0, size)?),
+            VectorType::I8(_) => Vector::I8(filled(0, size)?),
+            VectorType::I16(_) => Vector::I16(filled(0, size)?),
+            VectorType::I32(_) => Vector::I32(filled(0, size)?),
+            VectorType::I64(_) => Vector::I64(filled(0, size)?),
+            VectorType::F32(_) => Vector::F32(filled(0.0, size)?),
+            VectorType::F64(_) => Vector::F64(filled(0.0, size)?),
+            VectorType::Natural(_) => Vector::Natural(filled(Natural::ZERO, size)?),
+            VectorType::Integer(_) => Vector::Integer(filled(malachite::Integer::ZERO, size)?),
+            VectorType::Rational(_) => Vector::Rational(filled(malachite::Rational::ZERO, size)?),
+            VectorType::BigDecimal(_) => Vector::BigDecimal(filled(BigDecimal::zero(), size)?),
+            VectorType::Reference(_) => Vector::Reference(filled(Reference(0), size)?),
+            VectorType::Vector(typ, sub_size) => Vector::Vector(filled(Vector::new(*sub_size, typ)?, size)?),
+            VectorType::Tuple(_) => Vector::Tuple(filled(Tuple::empty(), size)?),
+            VectorType::Function(_) => Vector::Function(filled(Function::empty(), size)?),
+            VectorType::String(_) => Vector::String(filled(Arc::from(""), size)?),
+            VectorType::Boolean(_) => Vector::Boolean(filled(false, size)?),
+            VectorType::Character(_) => Vector::Character(filled('\0', size)?),
+            VectorType::Product(_) => Vector::Product(filled(ProductType::empty(), size)?),
+            VectorType::Sum(_) => Vector::Sum(filled(SumType::empty(), size)?),
+            VectorType::Any(_) => Vector::Any(filled(Value::Tuple(Tuple::empty()), size)?),
+        };
+        Ok(vector)
     }
 
     pub fn into_chunk(self) -> Box<dyn StackChunk> {
@@ -185,10 +374,17 @@ impl Vector {
             Vector::Natural(pointer) => Box::new(NaturalVector(pointer)),
             Vector::Integer(pointer) => Box::new(IntegerVector(pointer)),
             Vector::Rational(pointer) => Box::new(RationalVector(pointer)),
+            Vector::BigDecimal(pointer) => Box::new(BigDecimalVector(pointer)),
             Vector::Reference(pointer) => Box::new(ReferenceVector(pointer)),
             Vector::Vector(pointer) => Box::new(VectorVector(pointer)),
             Vector::Tuple(pointer) => Box::new(TupleVector(pointer)),
             Vector::Function(pointer) => Box::new(FunctionVector(pointer)),
+            Vector::String(pointer) => Box::new(StringVector(pointer)),
+            Vector::Boolean(pointer) => Box::new(BooleanVector(pointer)),
+            Vector::Character(pointer) => Box::new(CharacterVector(pointer)),
+            Vector::Product(pointer) => Box::new(ProductVector(pointer)),
+            Vector::Sum(pointer) => Box::new(SumVector(pointer)),
+            Vector::Any(pointer) => Box::new(AnyVector(pointer)),
         }
     }
 
@@ -285,6 +481,13 @@ impl Vector {
                     Err(Fault::OutOfBounds)
                 }
             },
+            Vector::BigDecimal(pointer) => {
+                if index < pointer.len() {
+                    Ok(Value::Decimal(Decimal::BigDecimal(pointer[index].clone())))
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
             Vector::Reference(pointer) => {
                 if index < pointer.len() {
                     Ok(Value::Reference(pointer[index]))
@@ -313,6 +516,48 @@ impl Vector {
                     Err(Fault::OutOfBounds)
                 }
             },
+            Vector::String(pointer) => {
+                if index < pointer.len() {
+                    Ok(Value::String(pointer[index].clone()))
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            Vector::Boolean(pointer) => {
+                if index < pointer.len() {
+                    Ok(Value::Boolean(pointer[index]))
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            Vector::Character(pointer) => {
+                if index < pointer.len() {
+                    Ok(Value::Character(pointer[index]))
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            Vector::Product(pointer) => {
+                if index < pointer.len() {
+                    Ok(Value::Product(pointer[index].clone()))
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            Vector::Sum(pointer) => {
+                if index < pointer.len() {
+                    Ok(Value::Sum(pointer[index].clone()))
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            Vector::Any(pointer) => {
+                if index < pointer.len() {
+                    Ok(pointer[index].clone())
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
         }
     }
 
@@ -320,7 +565,7 @@ impl Vector {
         match (self, value)  {
             (Vector::U8(pointer), Value::Integer(Integer::U8(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -328,7 +573,7 @@ impl Vector {
             },
             (Vector::U16(pointer), Value::Integer(Integer::U16(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -336,7 +581,7 @@ impl Vector {
             },
             (Vector::U32(pointer), Value::Integer(Integer::U32(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -344,7 +589,7 @@ impl Vector {
             },
             (Vector::U64(pointer), Value::Integer(Integer::U64(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -352,7 +597,7 @@ impl Vector {
             },
             (Vector::I8(pointer), Value::Integer(Integer::I8(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -360,7 +605,7 @@ impl Vector {
             },
             (Vector::I16(pointer), Value::Integer(Integer::I16(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -368,7 +613,7 @@ impl Vector {
             },
             (Vector::I32(pointer), Value::Integer(Integer::I32(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -376,7 +621,7 @@ impl Vector {
             },
             (Vector::I64(pointer), Value::Integer(Integer::I64(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -384,7 +629,7 @@ impl Vector {
             },
             (Vector::F32(pointer), Value::Decimal(Decimal::F32(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -392,7 +637,7 @@ impl Vector {
             },
             (Vector::F64(pointer), Value::Decimal(Decimal::F64(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -400,7 +645,7 @@ impl Vector {
             },
             (Vector::Natural(pointer), Value::Integer(Integer::Natural(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -408,7 +653,7 @@ impl Vector {
             },
             (Vector::Integer(pointer), Value::Integer(Integer::Integer(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -416,7 +661,15 @@ impl Vector {
             },
             (Vector::Rational(pointer), Value::Decimal(Decimal::Rational(value))) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
+                    Ok(())
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            (Vector::BigDecimal(pointer), Value::Decimal(Decimal::BigDecimal(value))) => {
+                if index < pointer.len() {
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -424,7 +677,7 @@ impl Vector {
             },
             (Vector::Reference(pointer), Value::Reference(value)) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -432,7 +685,7 @@ impl Vector {
             },
             (Vector::Vector(pointer), Value::Vector(value)) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -440,7 +693,7 @@ impl Vector {
             },
             (Vector::Tuple(pointer), Value::Tuple(value)) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -448,7 +701,55 @@ impl Vector {
             },
             (Vector::Function(pointer), Value::Function(value)) => {
                 if index < pointer.len() {
-                    pointer[index] = value;
+                    Arc::make_mut(pointer)[index] = value;
+                    Ok(())
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            (Vector::String(pointer), Value::String(value)) => {
+                if index < pointer.len() {
+                    Arc::make_mut(pointer)[index] = value;
+                    Ok(())
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            (Vector::Boolean(pointer), Value::Boolean(value)) => {
+                if index < pointer.len() {
+                    Arc::make_mut(pointer)[index] = value;
+                    Ok(())
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            (Vector::Character(pointer), Value::Character(value)) => {
+                if index < pointer.len() {
+                    Arc::make_mut(pointer)[index] = value;
+                    Ok(())
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            (Vector::Product(pointer), Value::Product(value)) => {
+                if index < pointer.len() {
+                    Arc::make_mut(pointer)[index] = value;
+                    Ok(())
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            (Vector::Sum(pointer), Value::Sum(value)) => {
+                if index < pointer.len() {
+                    Arc::make_mut(pointer)[index] = value;
+                    Ok(())
+                } else {
+                    Err(Fault::OutOfBounds)
+                }
+            },
+            (Vector::Any(pointer), value) => {
+                if index < pointer.len() {
+                    Arc::make_mut(pointer)[index] = value;
                     Ok(())
                 } else {
                     Err(Fault::OutOfBounds)
@@ -458,6 +759,139 @@ impl Vector {
         }
     }
 
+    /// Appends a value of the element type.
+    pub fn push(&mut self, value: Value) -> Result<(), Fault> {
+        with_element!(self, value, pointer, value => {
+            Arc::make_mut(pointer).push(value);
+            Ok(())
+        })
+    }
+
+    /// Removes the last element. Faults with `OutOfBounds` if the vector is empty.
+    pub fn pop(&mut self) -> Result<Value, Fault> {
+        let length = self.length();
+        if length == 0 {
+            return Err(Fault::OutOfBounds);
+        }
+        self.remove(length - 1)
+    }
+
+    /// Inserts a value before `index`, which can be the length to append.
+    pub fn insert(&mut self, index: usize, value: Value) -> Result<(), Fault> {
+        if index > self.length() {
+            return Err(Fault::OutOfBounds);
+        }
+        with_element!(self, value, pointer, value => {
+            Arc::make_mut(pointer).insert(index, value);
+            Ok(())
+        })
+    }
+
+    /// Removes the element at `index` and shifts the rest down.
+    pub fn remove(&mut self, index: usize) -> Result<Value, Fault> {
+        let value = self.get(index)?;
+        each_vector!(self, pointer => {
+            Arc::make_mut(pointer).remove(index);
+        });
+        Ok(value)
+    }
+
+    /// Shortens the vector to `length` elements. Faults with `OutOfBounds` if it is longer than the vector.
+    pub fn truncate(&mut self, length: usize) -> Result<(), Fault> {
+        if length > self.length() {
+            return Err(Fault::OutOfBounds);
+        }
+        each_vector!(self, pointer => Arc::make_mut(pointer).truncate(length));
+        Ok(())
+    }
+
+    /// Grows the vector to `length` elements with copies of `value`, or shortens it.
+    /// Faults with `OutOfMemory` if the longer vector can't be allocated.
+    pub fn resize(&mut self, length: usize, value: Value) -> Result<(), Fault> {
+        with_element!(self, value, pointer, value => {
+            let data = Arc::make_mut(pointer);
+            data.try_reserve(length.saturating_sub(data.len())).map_err(|_| Fault::OutOfMemory)?;
+            data.resize(length, value);
+            Ok(())
+        })
+    }
+
+    /// A new vector with the elements from `start` up to but not including `end`.
+    pub fn slice(&self, start: usize, end: usize) -> Result<Vector, Fault> {
+        if start > end || end > self.length() {
+            return Err(Fault::OutOfBounds);
+        }
+        Ok(map_vector!(self, pointer => pointer[start..end].to_vec()))
+    }
+
+    /// An empty vector that holds values of the same type as `value`.
+    /// Maps and arrays have no vector of their own, so they go in an `Any` vector.
+    pub fn empty_like(value: &Value) -> Vector {
+        match value {
+            Value::Integer(Integer::U8(_)) => Vector::U8(Arc::new(Vec::new())),
+            Value::Integer(Integer::U16(_)) => Vector::U16(Arc::new(Vec::new())),
+            Value::Integer(Integer::U32(_)) => Vector::U32(Arc::new(Vec::new())),
+            Value::Integer(Integer::U64(_)) => Vector::U64(Arc::new(Vec::new())),
+            Value::Integer(Integer::I8(_)) => Vector::I8(Arc::new(Vec::new())),
+            Value::Integer(Integer::I16(_)) => Vector::I16(Arc::new(Vec::new())),
+            Value::Integer(Integer::I32(_)) => Vector::I32(Arc::new(Vec::new())),
+            Value::Integer(Integer::I64(_)) => Vector::I64(Arc::new(Vec::new())),
+            Value::Integer(Integer::Natural(_)) => Vector::Natural(Arc::new(Vec::new())),
+            Value::Integer(Integer::Integer(_)) => Vector::Integer(Arc::new(Vec::new())),
+            Value::Decimal(Decimal::F32(_)) => Vector::F32(Arc::new(Vec::new())),
+            Value::Decimal(Decimal::F64(_)) => Vector::F64(Arc::new(Vec::new())),
+            Value::Decimal(Decimal::Rational(_)) => Vector::Rational(Arc::new(Vec::new())),
+            Value::Decimal(Decimal::BigDecimal(_)) => Vector::BigDecimal(Arc::new(Vec::new())),
+            Value::Reference(_) => Vector::Reference(Arc::new(Vec::new())),
+            Value::Vector(_) => Vector::Vector(Arc::new(Vec::new())),
+            Value::Tuple(_) => Vector::Tuple(Arc::new(Vec::new())),
+            Value::Function(_) => Vector::Function(Arc::new(Vec::new())),
+            Value::String(_) => Vector::String(Arc::new(Vec::new())),
+            Value::Boolean(_) => Vector::Boolean(Arc::new(Vec::new())),
+            Value::Character(_) => Vector::Character(Arc::new(Vec::new())),
+            Value::Product(_) => Vector::Product(Arc::new(Vec::new())),
+            Value::Sum(_) => Vector::Sum(Arc::new(Vec::new())),
+            Value::Map(_) | Value::Array(_) => Vector::Any(Arc::new(Vec::new())),
+        }
+    }
+
+    /// The same elements in a vector of type `typ`, whose size is ignored. Every vector casts to
+    /// `Any`, and a vector casts to another type if all its elements have that type.
+    pub fn cast(&self, typ: &VectorType) -> Result<Vector, Fault> {
+        let mut result = Vector::new(0, typ)?;
+        for index in 0..self.length() {
+            result.push(self.get(index)?)?;
+        }
+        Ok(result)
+    }
+
+    /// A new vector with the elements at `positions`, which have to be in bounds.
+    pub fn gather(&self, positions: &[usize]) -> Vector {
+        map_vector!(self, pointer => positions.iter().map(|position| &pointer[*position]).cloned().collect())
+    }
+
+    /// A new vector with the elements of `self` followed by those of `other`, which must have the same element type.
+    pub fn concat(&self, other: &Vector) -> Result<Vector, Fault> {
+        let mut result = self.clone();
+        with_same_vectors!(&mut result, other, left, right => {
+            Arc::make_mut(left).extend_from_slice(right);
+            Ok(())
+        })?;
+        Ok(result)
+    }
+
+    /// Overwrites the elements from `offset` on with `source[start..end]`. The copied range has
+    /// to fit in both vectors, which must have the same element type.
+    pub fn copy_from(&mut self, offset: usize, source: &Vector, start: usize, end: usize) -> Result<(), Fault> {
+        if start > end || end > source.length() {
+            return Err(Fault::OutOfBounds);
+        }
+        let offset_end = offset.checked_add(end - start).filter(|offset_end| *offset_end <= self.length()).ok_or(Fault::OutOfBounds)?;
+        with_same_vectors!(self, source, destination, source => {
+            Arc::make_mut(destination)[offset..offset_end].clone_from_slice(&source[start..end]);
+            Ok(())
+        })
+    }
 
 
     pub fn length(&self) -> usize {
@@ -475,10 +909,239 @@ impl Vector {
             Vector::Natural(pointer) => pointer.len(),
             Vector::Integer(pointer) => pointer.len(),
             Vector::Rational(pointer) => pointer.len(),
+            Vector::BigDecimal(pointer) => pointer.len(),
             Vector::Reference(pointer) => pointer.len(),
             Vector::Vector(pointer) => pointer.len(),
             Vector::Tuple(pointer) => pointer.len(),
             Vector::Function(pointer) => pointer.len(),
+            Vector::String(pointer) => pointer.len(),
+            Vector::Boolean(pointer) => pointer.len(),
+            Vector::Character(pointer) => pointer.len(),
+            Vector::Product(pointer) => pointer.len(),
+            Vector::Sum(pointer) => pointer.len(),
+            Vector::Any(pointer) => pointer.len(),
+        }
+    }
+
+    /// Sorts the elements in ascending order, keeping equal elements in their order.
+    /// Faults with `NotComparable` if two elements don't compare, like NaN or functions.
+    pub fn sort(&mut self) -> Result<(), Fault> {
+        each_vector!(self, pointer => {
+            *pointer = Arc::new(merge_sort(pointer, &mut |left, right| left.partial_cmp(right).ok_or(Fault::NotComparable))?);
+        });
+        Ok(())
+    }
+
+    /// Sorts the elements with `compare`, keeping equal elements in their order.
+    /// The first fault from `compare` stops the sort and leaves the vector unchanged.
+    pub fn sort_by(&mut self, mut compare: impl FnMut(Value, Value) -> Result<Ordering, Fault>) -> Result<(), Fault> {
+        let positions = (0..self.length()).collect::<Vec<_>>();
+        let positions = merge_sort(&positions, &mut |left, right| compare(self.get(*left)?, self.get(*right)?))?;
+        *self = self.gather(&positions);
+        Ok(())
+    }
+
+    /// Searches a sorted vector for `value`. Gives `Ok(position)` if it is found, otherwise
+    /// `Err(position)` where it could be inserted to keep the vector sorted.
+    pub fn binary_search(&self, value: &Value) -> Result<Result<usize, usize>, Fault> {
+        let (mut low, mut high) = (0, self.length());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.get(middle)?.partial_cmp(value).ok_or(Fault::TypeMismatch)? {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Ok(Ok(middle)),
+            }
+        }
+        Ok(Err(low))
+    }
+
+    pub fn reverse(&mut self) {
+        each_vector!(self, pointer => Arc::make_mut(pointer).reverse());
+    }
+
+    /// Removes consecutive equal elements, so a sorted vector ends up without duplicates.
+    pub fn dedup(&mut self) {
+        each_vector!(self, pointer => Arc::make_mut(pointer).dedup());
+    }
+
+    /// A new vector of the results of `function`. They have to have one type, which is the element
+    /// type of the new vector. An empty vector maps to an empty vector of the same type.
+    pub fn map(&self, mut function: impl FnMut(Value) -> Result<Value, Fault>) -> Result<Vector, Fault> {
+        let mut result: Option<Vector> = None;
+        for index in 0..self.length() {
+            let value = function(self.get(index)?)?;
+            let vector = match result.as_mut() {
+                Some(vector) => vector,
+                None => result.insert(Vector::empty_like(&value)),
+            };
+            vector.push(value)?;
+        }
+        Ok(result.unwrap_or_else(|| self.gather(&[])))
+    }
+
+    /// A new vector of the elements `keep` returns true for.
+    pub fn filter(&self, mut keep: impl FnMut(Value) -> Result<bool, Fault>) -> Result<Vector, Fault> {
+        let mut positions = Vec::new();
+        for index in 0..self.length() {
+            if keep(self.get(index)?)? {
+                positions.push(index);
+            }
+        }
+        Ok(self.gather(&positions))
+    }
+
+    pub fn contains_function(&self) -> bool {
+        match self {
+            Vector::Vector(pointer) => pointer.iter().any(Vector::contains_function),
+            Vector::Tuple(pointer) => pointer.iter().any(Tuple::contains_function),
+            Vector::Function(_) => true,
+            Vector::Product(pointer) => pointer.iter().any(ProductType::contains_function),
+            Vector::Sum(pointer) => pointer.iter().any(SumType::contains_function),
+            Vector::Any(pointer) => pointer.iter().any(Value::contains_function),
+            _ => false,
+        }
+    }
+
+    pub fn contains_nan(&self) -> bool {
+        match self {
+            Vector::F32(pointer) => pointer.iter().any(|value| value.is_nan()),
+            Vector::F64(pointer) => pointer.iter().any(|value| value.is_nan()),
+            Vector::Vector(pointer) => pointer.iter().any(Vector::contains_nan),
+            Vector::Tuple(pointer) => pointer.iter().any(Tuple::contains_nan),
+            Vector::Product(pointer) => pointer.iter().any(ProductType::contains_nan),
+            Vector::Sum(pointer) => pointer.iter().any(SumType::contains_nan),
+            Vector::Any(pointer) => pointer.iter().any(Value::contains_nan),
+            _ => false,
+        }
+    }
+}
+
+/// A stable merge sort that stops at the first fault from `compare`. Unlike the standard library's
+/// sorts it never panics when `compare` isn't a total order, which user callbacks needn't be.
+/// `size` copies of `value`, faulting with `OutOfMemory` instead of aborting if they don't fit.
+fn filled<T: Clone>(value: T, size: usize) -> Result<Arc<Vec<T>>, Fault> {
+    let mut data = Vec::new();
+    data.try_reserve_exact(size).map_err(|_| Fault::OutOfMemory)?;
+    data.resize(size, value);
+    Ok(Arc::new(data))
+}
+
+fn merge_sort<T: Clone>(data: &[T], compare: &mut impl FnMut(&T, &T) -> Result<Ordering, Fault>) -> Result<Vec<T>, Fault> {
+    if data.len() <= 1 {
+        return Ok(data.to_vec());
+    }
+    let (left, right) = data.split_at(data.len() / 2);
+    let (left, right) = (merge_sort(left, compare)?, merge_sort(right, compare)?);
+    let mut result = Vec::with_capacity(data.len());
+    let (mut left, mut right) = (left.into_iter().peekable(), right.into_iter().peekable());
+    while let (Some(first), Some(second)) = (left.peek(), right.peek()) {
+        if compare(second, first)? == Ordering::Less {
+            result.push(right.next().unwrap());
+        } else {
+            result.push(left.next().unwrap());
+        }
+    }
+    result.extend(left);
+    result.extend(right);
+    Ok(result)
+}
+
+/// Vectors are equal if they have the same element type and equal elements.
+impl PartialEq for Vector {
+    fn eq(&self, other: &Vector) -> bool {
+        match (self, other) {
+            (Vector::U8(left), Vector::U8(right)) => left == right,
+            (Vector::U16(left), Vector::U16(right)) => left == right,
+            (Vector::U32(left), Vector::U32(right)) => left == right,
+            (Vector::U64(left), Vector::U64(right)) => left == right,
+            (Vector::I8(left), Vector::I8(right)) => left == right,
+            (Vector::I16(left), Vector::I16(right)) => left == right,
+            (Vector::I32(left), Vector::I32(right)) => left == right,
+            (Vector::I64(left), Vector::I64(right)) => left == right,
+            (Vector::F32(left), Vector::F32(right)) => left == right,
+            (Vector::F64(left), Vector::F64(right)) => left == right,
+            (Vector::Natural(left), Vector::Natural(right)) => left == right,
+            (Vector::Integer(left), Vector::Integer(right)) => left == right,
+            (Vector::Rational(left), Vector::Rational(right)) => left == right,
+            (Vector::BigDecimal(left), Vector::BigDecimal(right)) => left == right,
+            (Vector::Reference(left), Vector::Reference(right)) => left == right,
+            (Vector::Vector(left), Vector::Vector(right)) => left == right,
+            (Vector::Tuple(left), Vector::Tuple(right)) => left == right,
+            (Vector::Function(left), Vector::Function(right)) => left == right,
+            (Vector::String(left), Vector::String(right)) => left == right,
+            (Vector::Boolean(left), Vector::Boolean(right)) => left == right,
+            (Vector::Character(left), Vector::Character(right)) => left == right,
+            (Vector::Product(left), Vector::Product(right)) => left == right,
+            (Vector::Sum(left), Vector::Sum(right)) => left == right,
+            (Vector::Any(left), Vector::Any(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+/// Vectors of the same element type are ordered lexicographically, others are unordered.
+impl PartialOrd for Vector {
+    fn partial_cmp(&self, other: &Vector) -> Option<Ordering> {
+        match (self, other) {
+            (Vector::U8(left), Vector::U8(right)) => left.partial_cmp(right),
+            (Vector::U16(left), Vector::U16(right)) => left.partial_cmp(right),
+            (Vector::U32(left), Vector::U32(right)) => left.partial_cmp(right),
+            (Vector::U64(left), Vector::U64(right)) => left.partial_cmp(right),
+            (Vector::I8(left), Vector::I8(right)) => left.partial_cmp(right),
+            (Vector::I16(left), Vector::I16(right)) => left.partial_cmp(right),
+            (Vector::I32(left), Vector::I32(right)) => left.partial_cmp(right),
+            (Vector::I64(left), Vector::I64(right)) => left.partial_cmp(right),
+            (Vector::F32(left), Vector::F32(right)) => left.partial_cmp(right),
+            (Vector::F64(left), Vector::F64(right)) => left.partial_cmp(right),
+            (Vector::Natural(left), Vector::Natural(right)) => left.partial_cmp(right),
+            (Vector::Integer(left), Vector::Integer(right)) => left.partial_cmp(right),
+            (Vector::Rational(left), Vector::Rational(right)) => left.partial_cmp(right),
+            (Vector::BigDecimal(left), Vector::BigDecimal(right)) => left.partial_cmp(right),
+            (Vector::Reference(left), Vector::Reference(right)) => left.partial_cmp(right),
+            (Vector::Vector(left), Vector::Vector(right)) => left.partial_cmp(right),
+            (Vector::Tuple(left), Vector::Tuple(right)) => left.partial_cmp(right),
+            (Vector::Function(left), Vector::Function(right)) => left.partial_cmp(right),
+            (Vector::String(left), Vector::String(right)) => left.partial_cmp(right),
+            (Vector::Boolean(left), Vector::Boolean(right)) => left.partial_cmp(right),
+            (Vector::Character(left), Vector::Character(right)) => left.partial_cmp(right),
+            (Vector::Product(left), Vector::Product(right)) => left.partial_cmp(right),
+            (Vector::Sum(left), Vector::Sum(right)) => left.partial_cmp(right),
+            (Vector::Any(left), Vector::Any(right)) => left.partial_cmp(right),
+            _ => None,
+        }
+    }
+}
+
+/// Float elements hash like `Decimal` floats, so `-0.0` and `0.0` hash the same.
+impl Hash for Vector {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Vector::U8(pointer) => pointer.hash(state),
+            Vector::U16(pointer) => pointer.hash(state),
+            Vector::U32(pointer) => pointer.hash(state),
+            Vector::U64(pointer) => pointer.hash(state),
+            Vector::I8(pointer) => pointer.hash(state),
+            Vector::I16(pointer) => pointer.hash(state),
+            Vector::I32(pointer) => pointer.hash(state),
+            Vector::I64(pointer) => pointer.hash(state),
+            Vector::F32(pointer) => pointer.iter().map(|x| float_bits(*x as f64)).collect::<Vec<_>>().hash(state),
+            Vector::F64(pointer) => pointer.iter().map(|x| float_bits(*x)).collect::<Vec<_>>().hash(state),
+            Vector::Natural(pointer) => pointer.hash(state),
+            Vector::Integer(pointer) => pointer.hash(state),
+            Vector::Rational(pointer) => pointer.hash(state),
+            Vector::BigDecimal(pointer) => pointer.hash(state),
+            Vector::Reference(pointer) => pointer.hash(state),
+            Vector::Vector(pointer) => pointer.hash(state),
+            Vector::Tuple(pointer) => pointer.hash(state),
+            Vector::Function(pointer) => pointer.hash(state),
+            Vector::String(pointer) => pointer.hash(state),
+            Vector::Boolean(pointer) => pointer.hash(state),
+            Vector::Character(pointer) => pointer.hash(state),
+            Vector::Product(pointer) => pointer.hash(state),
+            Vector::Sum(pointer) => pointer.hash(state),
+            Vector::Any(pointer) => pointer.hash(state),
         }
     }
 }
@@ -490,119 +1153,240 @@ impl Display for Vector {
         match self {
             Vector::U8(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::U16(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::U32(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::U64(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::I8(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::I16(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::I32(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::I64(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::F32(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::F64(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Natural(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Integer(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Rational(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::BigDecimal(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Reference(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Vector(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Tuple(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Function(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::String(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Boolean(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Character(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Product(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Sum(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Any(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", item)?;
                 }
                 write!(f, "]")
@@ -626,21 +1410,40 @@ impl Debug for Vector {
             Vector::F64(_) => write!(f, "{}", self),
             Vector::Natural(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{:?}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Integer(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{:?}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Rational(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::BigDecimal(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{:?}", item)?;
                 }
                 write!(f, "]")
@@ -648,21 +1451,90 @@ impl Debug for Vector {
             Vector::Reference(_) => write!(f, "{}", self),
             Vector::Vector(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{:?}", item)?;
                 }
                 write!(f, "]")
             },
             Vector::Tuple(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{:?}", item)?;
                 }
                 write!(f, "]")
             }
             Vector::Function(pointer) => {
                 write!(f, "[")?;
-                for item in pointer.iter() {
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::String(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Boolean(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Character(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Product(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Sum(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}", item)?;
+                }
+                write!(f, "]")
+            },
+            Vector::Any(pointer) => {
+                write!(f, "[")?;
+                for (i, item) in pointer.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{:?}", item)?;
                 }
                 write!(f, "]")
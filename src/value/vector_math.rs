@@ -0,0 +1,474 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Mul};
+use std::sync::Arc;
+use malachite::{Natural, Rational};
+use malachite::num::basic::traits::Zero;
+use crate::machine::Fault;
+use crate::value::big_decimal::BigDecimal;
+use crate::value::decimal::{big_decimal_quotient, Decimal};
+use crate::value::integer::{Integer, IntegerType};
+use crate::value::vector::Vector;
+use crate::value::Value;
+
+/// An element-wise operation between two vectors of the same type and length, or a vector and a
+/// scalar of its element type.
+#[derive(Clone, Copy, PartialEq)]
+pub enum VectorOperation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Display for VectorOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VectorOperation::Add => write!(f, "add"),
+            VectorOperation::Subtract => write!(f, "subtract"),
+            VectorOperation::Multiply => write!(f, "multiply"),
+            VectorOperation::Divide => write!(f, "divide"),
+        }
+    }
+}
+
+impl Debug for VectorOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+/// Reduces a vector of numbers to a single value.
+#[derive(Clone, Copy, PartialEq)]
+pub enum VectorReduction {
+    Sum,
+    Min,
+    Max,
+    Mean,
+}
+
+impl Display for VectorReduction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VectorReduction::Sum => write!(f, "sum"),
+            VectorReduction::Min => write!(f, "min"),
+            VectorReduction::Max => write!(f, "max"),
+            VectorReduction::Mean => write!(f, "mean"),
+        }
+    }
+}
+
+impl Debug for VectorReduction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+/// Applies `operation` element by element. At least one side has to be a vector, the other can be
+/// a scalar of the element type.
+///
+/// Fixed width integers fault with `Overflow` if any element doesn't fit, `Natural` vectors with
+/// `Underflow` if a difference is negative. Integer division truncates. A zero anywhere in the
+/// divisor faults with `DivisionByZero`, like `DecimalDivide` does for floats.
+pub fn elementwise(left: &Value, right: &Value, operation: VectorOperation) -> Result<Vector, Fault> {
+    let vector = match (left, right) {
+        (Value::Vector(vector), _) | (_, Value::Vector(vector)) => vector,
+        _ => return Err(Fault::NotAVector),
+    };
+    match vector {
+        Vector::U8(_) => apply::<u8>(left, right, operation),
+        Vector::U16(_) => apply::<u16>(left, right, operation),
+        Vector::U32(_) => apply::<u32>(left, right, operation),
+        Vector::U64(_) => apply::<u64>(left, right, operation),
+        Vector::I8(_) => apply::<i8>(left, right, operation),
+        Vector::I16(_) => apply::<i16>(left, right, operation),
+        Vector::I32(_) => apply::<i32>(left, right, operation),
+        Vector::I64(_) => apply::<i64>(left, right, operation),
+        Vector::F32(_) => apply::<f32>(left, right, operation),
+        Vector::F64(_) => apply::<f64>(left, right, operation),
+        Vector::Natural(_) => apply::<Natural>(left, right, operation),
+        Vector::Integer(_) => apply::<malachite::Integer>(left, right, operation),
+        Vector::Rational(_) => apply::<Rational>(left, right, operation),
+        Vector::BigDecimal(_) => apply::<BigDecimal>(left, right, operation),
+        _ => Err(Fault::TypeMismatch),
+    }
+}
+
+/// The sum of the products of two vectors of the same type and length, in the element type.
+pub fn dot(left: &Vector, right: &Vector) -> Result<Value, Fault> {
+    check_lengths(left.length(), right.length())?;
+    match (left, right) {
+        (Vector::U8(left), Vector::U8(right)) => u8::dot(left, right),
+        (Vector::U16(left), Vector::U16(right)) => u16::dot(left, right),
+        (Vector::U32(left), Vector::U32(right)) => u32::dot(left, right),
+        (Vector::U64(left), Vector::U64(right)) => u64::dot(left, right),
+        (Vector::I8(left), Vector::I8(right)) => i8::dot(left, right),
+        (Vector::I16(left), Vector::I16(right)) => i16::dot(left, right),
+        (Vector::I32(left), Vector::I32(right)) => i32::dot(left, right),
+        (Vector::I64(left), Vector::I64(right)) => i64::dot(left, right),
+        (Vector::F32(left), Vector::F32(right)) => f32::dot(left, right),
+        (Vector::F64(left), Vector::F64(right)) => f64::dot(left, right),
+        (Vector::Natural(left), Vector::Natural(right)) => Natural::dot(left, right),
+        (Vector::Integer(left), Vector::Integer(right)) => malachite::Integer::dot(left, right),
+        (Vector::Rational(left), Vector::Rational(right)) => Rational::dot(left, right),
+        (Vector::BigDecimal(left), Vector::BigDecimal(right)) => BigDecimal::dot(left, right),
+        _ => Err(Fault::TypeMismatch),
+    }
+}
+
+/// Reduces a vector of numbers.
+///
+/// Sums keep the element type and fault with `Overflow` if they don't fit. The mean of fixed width
+/// integers is an `F64`, of `Natural` and `Integer` vectors a `Rational`. Min and max skip NaN
+/// unless every element is NaN. Everything but the sum faults with `OutOfBounds` on an empty vector.
+pub fn reduce(vector: &Vector, reduction: VectorReduction) -> Result<Value, Fault> {
+    match vector {
+        Vector::U8(data) => reduce_with(data, reduction),
+        Vector::U16(data) => reduce_with(data, reduction),
+        Vector::U32(data) => reduce_with(data, reduction),
+        Vector::U64(data) => reduce_with(data, reduction),
+        Vector::I8(data) => reduce_with(data, reduction),
+        Vector::I16(data) => reduce_with(data, reduction),
+        Vector::I32(data) => reduce_with(data, reduction),
+        Vector::I64(data) => reduce_with(data, reduction),
+        Vector::F32(data) => reduce_with(data, reduction),
+        Vector::F64(data) => reduce_with(data, reduction),
+        Vector::Natural(data) => reduce_with(data, reduction),
+        Vector::Integer(data) => reduce_with(data, reduction),
+        Vector::Rational(data) => reduce_with(data, reduction),
+        Vector::BigDecimal(data) => reduce_with(data, reduction),
+        _ => Err(Fault::TypeMismatch),
+    }
+}
+
+/// One side of an element-wise operation.
+enum Operand<'a, T> {
+    Vector(&'a [T]),
+    Scalar(&'a T),
+}
+
+// Derived `Clone` and `Copy` would require them of `T`, but the operand only holds references.
+impl<T> Clone for Operand<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Operand<'_, T> {}
+
+/// The numeric element types, with the kernels for each.
+trait Element: Sized {
+    fn operand(value: &Value) -> Option<Operand<'_, Self>>;
+    fn into_vector(data: Vec<Self>) -> Vector;
+    fn into_value(value: Self) -> Value;
+    fn is_zero(&self) -> bool;
+    fn elementwise(left: Operand<Self>, right: Operand<Self>, operation: VectorOperation) -> Result<Vec<Self>, Fault>;
+    fn sum(data: &[Self]) -> Result<Value, Fault>;
+    fn dot(left: &[Self], right: &[Self]) -> Result<Value, Fault>;
+    /// The mean of a vector that isn't empty.
+    fn mean(data: &[Self]) -> Result<Value, Fault>;
+    /// The smallest (`Ordering::Less`) or largest (`Ordering::Greater`) element of a vector that isn't empty.
+    fn extreme(data: &[Self], wanted: Ordering) -> Value;
+}
+
+fn apply<T: Element>(left: &Value, right: &Value, operation: VectorOperation) -> Result<Vector, Fault> {
+    let left = T::operand(left).ok_or(Fault::TypeMismatch)?;
+    let right = T::operand(right).ok_or(Fault::TypeMismatch)?;
+    if let (Operand::Vector(left), Operand::Vector(right)) = (left, right) {
+        check_lengths(left.len(), right.len())?;
+    }
+    if operation == VectorOperation::Divide && contains_zero(right) {
+        return Err(Fault::DivisionByZero);
+    }
+    Ok(T::into_vector(T::elementwise(left, right, operation)?))
+}
+
+fn reduce_with<T: Element>(data: &[T], reduction: VectorReduction) -> Result<Value, Fault> {
+    if reduction != VectorReduction::Sum && data.is_empty() {
+        return Err(Fault::OutOfBounds);
+    }
+    match reduction {
+        VectorReduction::Sum => T::sum(data),
+        VectorReduction::Min => Ok(T::extreme(data, Ordering::Less)),
+        VectorReduction::Max => Ok(T::extreme(data, Ordering::Greater)),
+        VectorReduction::Mean => T::mean(data),
+    }
+}
+
+fn check_lengths(left: usize, right: usize) -> Result<(), Fault> {
+    if left != right {
+        return Err(Fault::InvalidOperation(format!("Vector lengths differ: {} and {}", left, right)));
+    }
+    Ok(())
+}
+
+fn contains_zero<T: Element>(operand: Operand<T>) -> bool {
+    match operand {
+        Operand::Vector(data) => data.iter().any(T::is_zero),
+        Operand::Scalar(value) => value.is_zero(),
+    }
+}
+
+/// Applies `function` to each pair of elements, repeating a scalar operand. The closure is
+/// inlined into each loop, so the loops over primitive types vectorize.
+fn zip<T, R>(left: Operand<T>, right: Operand<T>, mut function: impl FnMut(&T, &T) -> R) -> Vec<R> {
+    match (left, right) {
+        (Operand::Vector(left), Operand::Vector(right)) => left.iter().zip(right).map(|(left, right)| function(left, right)).collect(),
+        (Operand::Vector(left), Operand::Scalar(right)) => left.iter().map(|left| function(left, right)).collect(),
+        (Operand::Scalar(left), Operand::Vector(right)) => right.iter().map(|right| function(left, right)).collect(),
+        (Operand::Scalar(_), Operand::Scalar(_)) => unreachable!("One operand is always a vector"),
+    }
+}
+
+const LANES: usize = 8;
+
+/// Adds up `data` in `LANES` separate accumulators. Float addition isn't associative, so with a
+/// single accumulator the compiler couldn't vectorize the loop.
+fn lane_sum<T: Copy + Default + Add<Output = T>>(data: &[T]) -> T {
+    let chunks = data.chunks_exact(LANES);
+    let tail = chunks.remainder().iter().fold(T::default(), |sum, x| sum + *x);
+    let mut lanes = [T::default(); LANES];
+    for chunk in chunks {
+        for (lane, x) in lanes.iter_mut().zip(chunk) {
+            *lane = *lane + *x;
+        }
+    }
+    lanes.iter().fold(tail, |sum, x| sum + *x)
+}
+
+/// Like `lane_sum`, but over the products of two slices of the same length.
+fn lane_dot<T: Copy + Default + Add<Output = T> + Mul<Output = T>>(left: &[T], right: &[T]) -> T {
+    let (left_chunks, right_chunks) = (left.chunks_exact(LANES), right.chunks_exact(LANES));
+    let tail = left_chunks.remainder().iter().zip(right_chunks.remainder()).fold(T::default(), |sum, (x, y)| sum + *x * *y);
+    let mut lanes = [T::default(); LANES];
+    for (left, right) in left_chunks.zip(right_chunks) {
+        for (lane, (x, y)) in lanes.iter_mut().zip(left.iter().zip(right)) {
+            *lane = *lane + *x * *y;
+        }
+    }
+    lanes.iter().fold(tail, |sum, x| sum + *x)
+}
+
+/// Fixed width integers. Element-wise results wrap in the loop and an overflow flag is collected
+/// alongside, so the loop has no early exit. Sums and dot products use a 128 bit accumulator.
+macro_rules! fixed_width_element {
+    ($type:ty, $variant:ident, $wide:ty) => {
+        impl Element for $type {
+            fn operand(value: &Value) -> Option<Operand<'_, Self>> {
+                match value {
+                    Value::Vector(Vector::$variant(data)) => Some(Operand::Vector(data)),
+                    Value::Integer(Integer::$variant(value)) => Some(Operand::Scalar(value)),
+                    _ => None,
+                }
+            }
+
+            fn into_vector(data: Vec<Self>) -> Vector {
+                Vector::$variant(Arc::new(data))
+            }
+
+            fn into_value(value: Self) -> Value {
+                Value::Integer(Integer::$variant(value))
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == 0
+            }
+
+            fn elementwise(left: Operand<Self>, right: Operand<Self>, operation: VectorOperation) -> Result<Vec<Self>, Fault> {
+                let mut overflowed = false;
+                let mut overflowing = |(result, overflow): (Self, bool)| {
+                    overflowed |= overflow;
+                    result
+                };
+                let result = match operation {
+                    VectorOperation::Add => zip(left, right, |x, y| overflowing(x.overflowing_add(*y))),
+                    VectorOperation::Subtract => zip(left, right, |x, y| overflowing(x.overflowing_sub(*y))),
+                    VectorOperation::Multiply => zip(left, right, |x, y| overflowing(x.overflowing_mul(*y))),
+                    VectorOperation::Divide => zip(left, right, |x, y| overflowing(x.overflowing_div(*y))),
+                };
+                if overflowed {
+                    return Err(Fault::Overflow);
+                }
+                Ok(result)
+            }
+
+            fn sum(data: &[Self]) -> Result<Value, Fault> {
+                let sum = data.iter().try_fold(0 as $wide, |sum, x| sum.checked_add(*x as $wide)).ok_or(Fault::Overflow)?;
+                Ok(Value::Integer(Integer::of_type(IntegerType::$variant, malachite::Integer::from(sum))?))
+            }
+
+            fn dot(left: &[Self], right: &[Self]) -> Result<Value, Fault> {
+                let sum = left.iter().zip(right)
+                    .try_fold(0 as $wide, |sum, (x, y)| sum.checked_add((*x as $wide).checked_mul(*y as $wide)?))
+                    .ok_or(Fault::Overflow)?;
+                Ok(Value::Integer(Integer::of_type(IntegerType::$variant, malachite::Integer::from(sum))?))
+            }
+
+            fn mean(data: &[Self]) -> Result<Value, Fault> {
+                let sum: f64 = data.iter().map(|x| *x as f64).sum();
+                Ok(Value::Decimal(Decimal::F64(sum / data.len() as f64)))
+            }
+
+            fn extreme(data: &[Self], wanted: Ordering) -> Value {
+                let extreme = if wanted == Ordering::Less { data.iter().min() } else { data.iter().max() };
+                Self::into_value(*extreme.unwrap())
+            }
+        }
+    };
+}
+
+fixed_width_element!(u8, U8, u128);
+fixed_width_element!(u16, U16, u128);
+fixed_width_element!(u32, U32, u128);
+fixed_width_element!(u64, U64, u128);
+fixed_width_element!(i8, I8, i128);
+fixed_width_element!(i16, I16, i128);
+fixed_width_element!(i32, I32, i128);
+fixed_width_element!(i64, I64, i128);
+
+macro_rules! float_element {
+    ($type:ty, $variant:ident) => {
+        impl Element for $type {
+            fn operand(value: &Value) -> Option<Operand<'_, Self>> {
+                match value {
+                    Value::Vector(Vector::$variant(data)) => Some(Operand::Vector(data)),
+                    Value::Decimal(Decimal::$variant(value)) => Some(Operand::Scalar(value)),
+                    _ => None,
+                }
+            }
+
+            fn into_vector(data: Vec<Self>) -> Vector {
+                Vector::$variant(Arc::new(data))
+            }
+
+            fn into_value(value: Self) -> Value {
+                Value::Decimal(Decimal::$variant(value))
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == 0.0
+            }
+
+            fn elementwise(left: Operand<Self>, right: Operand<Self>, operation: VectorOperation) -> Result<Vec<Self>, Fault> {
+                let result = match operation {
+                    VectorOperation::Add => zip(left, right, |x, y| x + y),
+                    VectorOperation::Subtract => zip(left, right, |x, y| x - y),
+                    VectorOperation::Multiply => zip(left, right, |x, y| x * y),
+                    VectorOperation::Divide => zip(left, right, |x, y| x / y),
+                };
+                Ok(result)
+            }
+
+            fn sum(data: &[Self]) -> Result<Value, Fault> {
+                Ok(Self::into_value(lane_sum(data)))
+            }
+
+            fn dot(left: &[Self], right: &[Self]) -> Result<Value, Fault> {
+                Ok(Self::into_value(lane_dot(left, right)))
+            }
+
+            fn mean(data: &[Self]) -> Result<Value, Fault> {
+                Ok(Self::into_value(lane_sum(data) / data.len() as $type))
+            }
+
+            fn extreme(data: &[Self], wanted: Ordering) -> Value {
+                let pick = if wanted == Ordering::Less { <$type>::min } else { <$type>::max };
+                Self::into_value(data.iter().copied().fold(<$type>::NAN, pick))
+            }
+        }
+    };
+}
+
+float_element!(f32, F32);
+float_element!(f64, F64);
+
+/// Arbitrary precision types, where each operation is a closure over references that can fault.
+/// `$mean` turns the sum and the length into the mean, which can fault too.
+macro_rules! exact_element {
+    ($type:ty, $scalar:ident, $variant:ident, $zero:expr, $add:expr, $subtract:expr, $multiply:expr, $divide:expr, $mean:expr) => {
+        impl Element for $type {
+            fn operand(value: &Value) -> Option<Operand<'_, Self>> {
+                match value {
+                    Value::Vector(Vector::$variant(data)) => Some(Operand::Vector(data)),
+                    Value::$scalar($scalar::$variant(value)) => Some(Operand::Scalar(value)),
+                    _ => None,
+                }
+            }
+
+            fn into_vector(data: Vec<Self>) -> Vector {
+                Vector::$variant(Arc::new(data))
+            }
+
+            fn into_value(value: Self) -> Value {
+                Value::$scalar($scalar::$variant(value))
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == $zero
+            }
+
+            fn elementwise(left: Operand<Self>, right: Operand<Self>, operation: VectorOperation) -> Result<Vec<Self>, Fault> {
+                let operation: fn(&Self, &Self) -> Result<Self, Fault> = match operation {
+                    VectorOperation::Add => $add,
+                    VectorOperation::Subtract => $subtract,
+                    VectorOperation::Multiply => $multiply,
+                    VectorOperation::Divide => $divide,
+                };
+                zip(left, right, operation).into_iter().collect()
+            }
+
+            fn sum(data: &[Self]) -> Result<Value, Fault> {
+                let add: fn(&Self, &Self) -> Result<Self, Fault> = $add;
+                let sum = data.iter().try_fold($zero, |sum, x| add(&sum, x))?;
+                Ok(Self::into_value(sum))
+            }
+
+            fn dot(left: &[Self], right: &[Self]) -> Result<Value, Fault> {
+                let (add, multiply): (fn(&Self, &Self) -> Result<Self, Fault>, fn(&Self, &Self) -> Result<Self, Fault>) = ($add, $multiply);
+                let sum = left.iter().zip(right).try_fold($zero, |sum, (x, y)| add(&sum, &multiply(x, y)?))?;
+                Ok(Self::into_value(sum))
+            }
+
+            fn mean(data: &[Self]) -> Result<Value, Fault> {
+                let add: fn(&Self, &Self) -> Result<Self, Fault> = $add;
+                let mean: fn(Self, usize) -> Result<Value, Fault> = $mean;
+                mean(data.iter().try_fold($zero, |sum, x| add(&sum, x))?, data.len())
+            }
+
+            fn extreme(data: &[Self], wanted: Ordering) -> Value {
+                let mut best = &data[0];
+                for value in &data[1..] {
+                    if value.partial_cmp(best) == Some(wanted) {
+                        best = value;
+                    }
+                }
+                Self::into_value(best.clone())
+            }
+        }
+    };
+}
+
+exact_element!(Natural, Integer, Natural, Natural::ZERO,
+    |x, y| Ok(x + y),
+    |x, y| if x < y { Err(Fault::Underflow) } else { Ok(x - y) },
+    |x, y| Ok(x * y),
+    |x, y| Ok(x / y),
+    |sum, length| Ok(Value::Decimal(Decimal::Rational(Rational::from(sum) / Rational::from(length)))));
+exact_element!(malachite::Integer, Integer, Integer, malachite::Integer::ZERO,
+    |x, y| Ok(x + y),
+    |x, y| Ok(x - y),
+    |x, y| Ok(x * y),
+    |x, y| Ok(x / y),
+    |sum, length| Ok(Value::Decimal(Decimal::Rational(Rational::from(sum) / Rational::from(length)))));
+exact_element!(Rational, Decimal, Rational, Rational::ZERO,
+    |x, y| Ok(x + y),
+    |x, y| Ok(x - y),
+    |x, y| Ok(x * y),
+    |x, y| Ok(x / y),
+    |sum, length| Ok(Value::Decimal(Decimal::Rational(sum / Rational::from(length)))));
+exact_element!(BigDecimal, Decimal, BigDecimal, BigDecimal::zero(),
+    |x, y| Ok(x.clone() + y.clone()),
+    |x, y| Ok(x.clone() - y.clone()),
+    |x, y| Ok(x.clone() * y.clone()),
+    |x, y| big_decimal_quotient(x.clone(), y.clone()),
+    |sum, length| Ok(Value::Decimal(Decimal::BigDecimal(big_decimal_quotient(sum, BigDecimal::from(malachite::Integer::from(length)))?))));
@@ -0,0 +1,422 @@
+//! Runs the same functions with and without each optimization and checks that they return the
+//! same values or fail with the same fault.
+
+use std::collections::HashMap;
+use crayfish_vm3::instruction::{ComparisonType, Condition, FunctionSource, Instruction, JumpTarget, RealInstruction};
+use crayfish_vm3::instruction::RealInstruction::*;
+use crayfish_vm3::optimizer;
+use crayfish_vm3::program::module::Module;
+use crayfish_vm3::value::decimal::Decimal;
+use crayfish_vm3::value::function::Function;
+use crayfish_vm3::value::integer::Integer;
+use crayfish_vm3::{Value, Vm};
+
+fn function(argument_names: &[&str], code: Vec<RealInstruction>) -> Function {
+    let code = code.into_iter().map(Instruction::new).collect::<Vec<_>>();
+    let argument_names = argument_names.iter().map(|name| Box::from(*name)).collect::<Vec<_>>();
+    Function::new(code.into_boxed_slice(), argument_names.into_boxed_slice())
+}
+
+fn call(path: &str) -> RealInstruction {
+    FunctionCall(FunctionSource::Name(path.into()), Condition::Always)
+}
+
+fn lookup(name: &str) -> RealInstruction {
+    Lookup(name.into())
+}
+
+fn store(name: &str) -> RealInstruction {
+    Store(name.into())
+}
+
+fn relative(offset: isize) -> JumpTarget {
+    JumpTarget::Relative(offset)
+}
+
+/// Sums `0..n` with literals built by `literal`, so the same loop runs with different integer types.
+fn sum_to(literal: fn(u8) -> Integer) -> Function {
+    function(&["n"], vec![
+        IntegerNew(literal(0)),
+        store("total"),
+        IntegerNew(literal(0)),
+        store("i"),
+        lookup("i"),
+        lookup("n"),
+        Compare(ComparisonType::GreaterThanOrEqual),
+        Goto(relative(12), Condition::GreaterThanOrEqual),
+        Pop,
+        Pop,
+        lookup("total"),
+        lookup("i"),
+        IntegerAdd,
+        store("total"),
+        lookup("i"),
+        IntegerNew(literal(1)),
+        IntegerAdd,
+        store("i"),
+        Goto(relative(-14), Condition::Always),
+        Pop,
+        Pop,
+        lookup("total"),
+        Return(Condition::Always),
+    ])
+}
+
+/// Counts down by two and returns from inside the loop, so odd unsigned inputs underflow.
+fn countdown(literal: fn(u8) -> Integer) -> Function {
+    function(&["n"], vec![
+        lookup("n"),
+        IntegerNew(literal(0)),
+        Compare(ComparisonType::LessThanOrEqual),
+        Pop,
+        Return(Condition::LessThanOrEqual),
+        Pop,
+        lookup("n"),
+        IntegerNew(literal(2)),
+        IntegerSubtract,
+        store("n"),
+        Goto(relative(-10), Condition::Always),
+    ])
+}
+
+fn fib() -> Function {
+    function(&["n"], vec![
+        lookup("n"),
+        IntegerNew(Integer::I64(1)),
+        Compare(ComparisonType::LessThanOrEqual),
+        Goto(relative(3), Condition::GreaterThan),
+        Pop,
+        Return(Condition::Always),
+        Pop,
+        IntegerNew(Integer::I64(1)),
+        IntegerSubtract,
+        store("n"),
+        lookup("n"),
+        call("fib"),
+        lookup("n"),
+        IntegerNew(Integer::I64(1)),
+        IntegerSubtract,
+        call("fib"),
+        IntegerAdd,
+        Return(Condition::Always),
+    ])
+}
+
+/// Jump chains, jumps to the next instruction and `NoOp`s for the peephole pass to clean up.
+fn jumps() -> Function {
+    function(&["n"], vec![
+        NoOp,
+        Goto(relative(1), Condition::Always),
+        lookup("n"),
+        IntegerNew(Integer::I64(0)),
+        Compare(ComparisonType::Equal),
+        Goto(relative(3), Condition::Equal),
+        Goto(JumpTarget::Absolute(11), Condition::Always),
+        NoOp,
+        Goto(relative(5), Condition::Always),
+        IntegerNew(Integer::I64(1)),
+        Return(Condition::Always),
+        IntegerNew(Integer::I64(10)),
+        Goto(relative(3), Condition::Always),
+        IntegerNew(Integer::I64(20)),
+        NoOp,
+        Return(Condition::Always),
+    ])
+}
+
+/// Jumps before the start and just past the end of the function when `n` isn't positive.
+/// Inlining `square` makes the function long enough for the second jump to land inside it.
+fn leaves() -> Function {
+    function(&["n"], vec![
+        lookup("n"),
+        IntegerNew(Integer::I64(0)),
+        Compare(ComparisonType::Equal),
+        Goto(relative(-10), Condition::Equal),
+        Compare(ComparisonType::LessThan),
+        Goto(JumpTarget::Absolute(14), Condition::LessThan),
+        NoOp,
+        lookup("n"),
+        call("square"),
+        IntegerNew(Integer::I64(1)),
+        IntegerAdd,
+        store("n"),
+        Return(Condition::Always),
+    ])
+}
+
+/// Jumps right to the end of the function when `n` is zero, which runs off the end of the code.
+fn falls_off() -> Function {
+    function(&["n"], vec![
+        lookup("n"),
+        IntegerNew(Integer::I64(0)),
+        Compare(ComparisonType::Equal),
+        Goto(relative(3), Condition::Equal),
+        Pop,
+        Return(Condition::Always),
+    ])
+}
+
+fn divide() -> Function {
+    function(&["a", "b"], vec![
+        lookup("a"),
+        lookup("b"),
+        IntegerDivide,
+        Return(Condition::Always),
+    ])
+}
+
+fn subtract() -> Function {
+    function(&["a", "b"], vec![
+        lookup("a"),
+        lookup("b"),
+        IntegerSubtract,
+        Return(Condition::Always),
+    ])
+}
+
+fn square() -> Function {
+    function(&["x"], vec![
+        lookup("x"),
+        lookup("x"),
+        IntegerMultiply,
+        Return(Condition::Always),
+    ])
+}
+
+/// The smaller of `x` and 100, with a conditional return in the middle.
+fn clamp() -> Function {
+    function(&["x"], vec![
+        lookup("x"),
+        IntegerNew(Integer::I64(100)),
+        Compare(ComparisonType::LessThanOrEqual),
+        Pop,
+        Return(Condition::LessThanOrEqual),
+        Pop,
+        IntegerNew(Integer::I64(100)),
+        Return(Condition::Always),
+    ])
+}
+
+/// Calls small functions whose locals share names with its own.
+fn polynomial() -> Function {
+    function(&["x"], vec![
+        lookup("x"),
+        call("square"),
+        call("clamp"),
+        store("y"),
+        lookup("x"),
+        call("square"),
+        lookup("y"),
+        IntegerAdd,
+        IntegerNew(Integer::I64(1)),
+        lookup("x"),
+        call("subtract"),
+        IntegerAdd,
+        Return(Condition::Always),
+    ])
+}
+
+/// Inlines a loop twice into the same caller.
+fn sum_twice() -> Function {
+    function(&["n"], vec![
+        lookup("n"),
+        call("sum_to_i64"),
+        lookup("n"),
+        IntegerNew(Integer::I64(2)),
+        IntegerMultiply,
+        call("sum_to_i64"),
+        IntegerAdd,
+        Return(Condition::Always),
+    ])
+}
+
+/// Calls into a module with integer promotion, where mixing integer types doesn't fault.
+fn widen_outside() -> Function {
+    function(&["x"], vec![
+        lookup("x"),
+        call("wide::widen"),
+        Return(Condition::Always),
+    ])
+}
+
+fn widen() -> Function {
+    function(&["x"], vec![
+        lookup("x"),
+        IntegerNew(Integer::I64(1)),
+        IntegerAdd,
+        Return(Condition::Always),
+    ])
+}
+
+fn mean() -> Function {
+    function(&["a", "b"], vec![
+        lookup("a"),
+        lookup("b"),
+        DecimalAdd,
+        DecimalNew(Decimal::F64(2.0)),
+        DecimalDivide,
+        Return(Condition::Always),
+    ])
+}
+
+fn module() -> Module {
+    let mut wide = Module::new("wide", HashMap::new(), Vec::new(), HashMap::new());
+    wide.set_integer_promotion(true);
+    wide.add_function("widen", widen());
+
+    let mut module = Module::new("main", HashMap::new(), Vec::new(), HashMap::new());
+    module.set_integer_promotion(false);
+    module.add_module("wide", wide);
+    module.add_function("sum_to_u8", sum_to(Integer::U8));
+    module.add_function("sum_to_i64", sum_to(|value| Integer::I64(value.into())));
+    module.add_function("countdown_u8", countdown(Integer::U8));
+    module.add_function("countdown_i64", countdown(|value| Integer::I64(value.into())));
+    module.add_function("fib", fib());
+    module.add_function("jumps", jumps());
+    module.add_function("leaves", leaves());
+    module.add_function("falls_off", falls_off());
+    module.add_function("divide", divide());
+    module.add_function("subtract", subtract());
+    module.add_function("square", square());
+    module.add_function("clamp", clamp());
+    module.add_function("polynomial", polynomial());
+    module.add_function("sum_twice", sum_twice());
+    module.add_function("widen_outside", widen_outside());
+    module.add_function("mean", mean());
+    module
+}
+
+/// The functions to compare and the arguments to call each of them with.
+fn cases() -> Vec<(&'static str, Vec<Vec<Value>>)> {
+    let u8s = |values: &[u8]| values.iter().map(|value| vec![Value::from(*value)]).collect::<Vec<_>>();
+    let i64s = |values: &[i64]| values.iter().map(|value| vec![Value::from(*value)]).collect::<Vec<_>>();
+    vec![
+        ("sum_to_u8", u8s(&[0, 1, 10, 22, 23, 200])),
+        ("sum_to_i64", i64s(&[0, 1, 10, 1000, -5])),
+        ("countdown_u8", u8s(&[0, 4, 7, 200])),
+        ("countdown_i64", i64s(&[0, 4, 7, -3])),
+        ("fib", i64s(&[0, 1, 2, 15])),
+        ("jumps", i64s(&[0, 1])),
+        ("leaves", i64s(&[-1, 0, 1])),
+        ("falls_off", i64s(&[0, 1])),
+        ("divide", vec![
+            vec![Value::from(7i64), Value::from(2i64)],
+            vec![Value::from(7i64), Value::from(0i64)],
+            vec![Value::from(i64::MIN), Value::from(-1i64)],
+            vec![Value::from(7u8), Value::from(2i64)],
+        ]),
+        ("polynomial", i64s(&[0, 3, 11, 3_037_000_500])),
+        ("sum_twice", i64s(&[0, 10, 100])),
+        ("widen_outside", u8s(&[1, 255])),
+        ("mean", vec![
+            vec![Value::from(1.0f64), Value::from(2.0f64)],
+            vec![Value::from(f64::MAX), Value::from(f64::MAX)],
+            vec![Value::from(1.0f64), Value::from(1i64)],
+        ]),
+    ]
+}
+
+/// Calls the function on a fresh machine and renders the results or the fault for comparison.
+fn run(module: &Module, path: &str, arguments: &[Value]) -> String {
+    let mut vm = Vm::new(module.clone());
+    format!("{:?}", vm.call(path, arguments.to_vec()))
+}
+
+fn assert_equivalent(original: &Module, transformed: &Module, what: &str) {
+    for (path, argument_sets) in cases() {
+        for arguments in argument_sets {
+            assert_eq!(
+                run(original, path, &arguments),
+                run(transformed, path, &arguments),
+                "{} {} changed the result for {:?}", what, path, arguments,
+            );
+        }
+    }
+}
+
+fn has_calls(function: &Function) -> bool {
+    function.code.iter().any(|instruction| matches!(instruction.instruction, FunctionCall(_, _)))
+}
+
+#[test]
+fn optimized_matches_unoptimized() {
+    let original = module();
+    let mut optimized = module();
+    optimizer::optimize_module(&mut optimized);
+
+    let sum_to = |module: &Module| module.get_function(&"sum_to_i64".into()).unwrap().code.len();
+    assert!(sum_to(&optimized) < sum_to(&original));
+    assert_equivalent(&original, &optimized, "optimizing");
+}
+
+#[test]
+fn inlined_matches_not_inlined() {
+    let original = module();
+    let mut inlined = module();
+    optimizer::inline::inline_module(&mut inlined);
+
+    assert!(!has_calls(inlined.get_function(&"polynomial".into()).unwrap()));
+    assert!(!has_calls(inlined.get_function(&"sum_twice".into()).unwrap()));
+    assert!(has_calls(inlined.get_function(&"widen_outside".into()).unwrap()));
+    assert_equivalent(&original, &inlined, "inlining");
+}
+
+#[test]
+fn peephole_matches_original() {
+    let original = module();
+    let mut optimized = module();
+    optimized.for_each_function_mut(&mut |function| {
+        *function = optimizer::peephole::optimize(function);
+    });
+    assert_equivalent(&original, &optimized, "the peephole pass");
+}
+
+#[cfg(feature = "jit")]
+mod jit {
+    use super::*;
+
+    /// Wraps every case in a function that calls it by name, since only those calls get compiled.
+    fn with_callers(mut module: Module) -> Module {
+        for (path, _) in cases() {
+            let callee = module.get_function(&path.into()).unwrap().clone();
+            let mut code = callee.argument_names.iter().rev()
+                .map(|name| lookup(name))
+                .collect::<Vec<_>>();
+            code.push(call(path));
+            code.push(Return(Condition::Always));
+            let argument_names = callee.argument_names.iter().map(|name| name.as_ref()).collect::<Vec<_>>();
+            module.add_function(&format!("call_{}", path), function(&argument_names, code));
+        }
+        module
+    }
+
+    fn run_with_threshold(module: &Module, path: &str, arguments: &[Value], threshold: u32) -> String {
+        let mut vm = Vm::new(module.clone());
+        vm.core_mut().set_jit_threshold(threshold);
+        format!("{:?}", vm.call(format!("call_{}", path).as_str(), arguments.to_vec()))
+    }
+
+    fn assert_compiled_matches_interpreted(module: &Module) {
+        for (path, argument_sets) in cases() {
+            for arguments in argument_sets {
+                assert_eq!(
+                    run_with_threshold(module, path, &arguments, u32::MAX),
+                    run_with_threshold(module, path, &arguments, 0),
+                    "compiling {} changed the result for {:?}", path, arguments,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compiled_matches_interpreted() {
+        assert_compiled_matches_interpreted(&with_callers(module()));
+    }
+
+    #[test]
+    fn compiled_matches_interpreted_after_optimizing() {
+        let mut module = with_callers(module());
+        optimizer::optimize_module(&mut module);
+        assert_compiled_matches_interpreted(&module);
+    }
+}